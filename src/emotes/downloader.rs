@@ -5,9 +5,12 @@ use std::{borrow::BorrowMut, collections::HashMap, path::Path};
 use tokio::io::AsyncWriteExt;
 
 use crate::{
-    emotes::DownloadedEmotes,
+    emotes::{DownloadedBadges, DownloadedCheermotes, DownloadedEmotes},
     handlers::config::{CompleteConfig, FrontendConfig},
-    twitch::oauth::{get_channel_id, get_twitch_client, get_twitch_client_id},
+    twitch::{
+        oauth::{get_channel_id, get_twitch_client, get_twitch_client_id},
+        scopes::Feature,
+    },
     utils::pathing::cache_path,
 };
 
@@ -83,10 +86,14 @@ mod twitch {
     }
 
     // Twitch will not send all the emotes in one response, we use the cursor they return to query further emotes.
-    pub async fn get_user_emotes(client: &Client, user_id: &str) -> Result<EmoteMap> {
+    pub async fn get_user_emotes(
+        client: &Client,
+        api_url: &str,
+        user_id: &str,
+    ) -> Result<EmoteMap> {
         let mut user_emotes = client
             .get(format!(
-                "https://api.twitch.tv/helix/chat/emotes/user?user_id={user_id}",
+                "{api_url}/chat/emotes/user?user_id={user_id}",
             ))
             .send()
             .await?
@@ -97,7 +104,7 @@ mod twitch {
         while let Some(c) = user_emotes.pagination.cursor {
             let emotes =   client
             .get(format!(
-                "https://api.twitch.tv/helix/chat/emotes/user?user_id={user_id}&after={c}",
+                "{api_url}/chat/emotes/user?user_id={user_id}&after={c}",
             ))
             .send()
             .await?
@@ -347,6 +354,220 @@ mod frankerfacez {
     }
 }
 
+mod badges {
+    use crate::emotes::downloader::EmoteMap;
+    use color_eyre::Result;
+    use reqwest::Client;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug)]
+    struct Version {
+        id: String,
+        image_url_1x: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct BadgeSet {
+        set_id: String,
+        versions: Vec<Version>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct BadgeList {
+        data: Vec<BadgeSet>,
+    }
+
+    /// Badge images share the same URL across channels when they are the same image,
+    /// so the path of the URL is used as the filename.
+    fn badge_filename(url: &str) -> String {
+        let path = url
+            .split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .split_once('/')
+            .map_or(url, |(_, path)| path);
+
+        let path = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+
+        format!("badge_{path}")
+    }
+
+    /// Badges are mapped by their `set/version` name, the same way they are sent through IRC.
+    fn parse_badge_list(v: BadgeList) -> EmoteMap {
+        v.data
+            .into_iter()
+            .flat_map(|set| {
+                set.versions.into_iter().map(move |version| {
+                    (
+                        format!("{}/{}", set.set_id, version.id),
+                        (
+                            badge_filename(&version.image_url_1x),
+                            version.image_url_1x,
+                            false,
+                        ),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    // https://dev.twitch.tv/docs/api/reference/#get-global-chat-badges
+    // https://dev.twitch.tv/docs/api/reference/#get-channel-chat-badges
+    pub async fn get_badges(client: &Client, api_url: &str, channel_id: i32) -> Result<EmoteMap> {
+        let global_badges = client
+            .get(format!("{api_url}/chat/badges/global"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<BadgeList>()
+            .await?;
+
+        let channel_badges = client
+            .get(format!("{api_url}/chat/badges?broadcaster_id={channel_id}"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<BadgeList>()
+            .await?;
+
+        // Channel badges, such as subscriber badges, replace the global ones with the same name.
+        Ok(parse_badge_list(global_badges)
+            .into_iter()
+            .chain(parse_badge_list(channel_badges))
+            .collect())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_badge_list() {
+            let list = serde_json::from_str::<BadgeList>(
+                r#"{"data":[{"set_id":"subscriber","versions":[{"id":"0","image_url_1x":"https://static-cdn.jtvnw.net/badges/v1/5d9f2208-5dd8-11e7-8513-2ff4adfae661/1","image_url_2x":"","image_url_4x":"","title":"Subscriber"},{"id":"3","image_url_1x":"http://localhost:8080/badges/sub-3.png"}]}]}"#,
+            )
+            .unwrap();
+
+            let badges = parse_badge_list(list);
+
+            assert_eq!(
+                badges
+                    .get("subscriber/0")
+                    .map(|(filename, _, _)| filename.as_str()),
+                Some("badge_badges_v1_5d9f2208_5dd8_11e7_8513_2ff4adfae661_1")
+            );
+            assert_eq!(
+                badges
+                    .get("subscriber/3")
+                    .map(|(filename, _, _)| filename.as_str()),
+                Some("badge_badges_sub_3_png")
+            );
+        }
+    }
+}
+
+mod cheermotes {
+    use crate::emotes::downloader::EmoteMap;
+    use color_eyre::Result;
+    use reqwest::Client;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, Debug, Default)]
+    #[serde(default)]
+    struct Images {
+        #[serde(rename = "static")]
+        still: HashMap<String, String>,
+        animated: HashMap<String, String>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Themes {
+        dark: Images,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Tier {
+        min_bits: u64,
+        images: Themes,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Cheermote {
+        prefix: String,
+        tiers: Vec<Tier>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct CheermoteList {
+        data: Vec<Cheermote>,
+    }
+
+    /// Tiers are mapped by the lowercase prefix and the bits needed to reach them, separated by a `/`.
+    fn parse_cheermote_list(v: CheermoteList) -> EmoteMap {
+        v.data
+            .into_iter()
+            .flat_map(|cheermote| {
+                let prefix = cheermote.prefix.to_lowercase();
+
+                cheermote.tiers.into_iter().filter_map(move |tier| {
+                    let Images { animated, still } = tier.images.dark;
+
+                    let url = animated.get("1").or_else(|| still.get("1"))?.clone();
+
+                    Some((
+                        format!("{prefix}/{}", tier.min_bits),
+                        (format!("cheer_{prefix}_{}", tier.min_bits), url, false),
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    // https://dev.twitch.tv/docs/api/reference/#get-cheermotes
+    pub async fn get_cheermotes(
+        client: &Client,
+        api_url: &str,
+        channel_id: i32,
+    ) -> Result<EmoteMap> {
+        Ok(parse_cheermote_list(
+            client
+                .get(format!(
+                    "{api_url}/bits/cheermotes?broadcaster_id={channel_id}"
+                ))
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<CheermoteList>()
+                .await?,
+        ))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_cheermote_list() {
+            let list = serde_json::from_str::<CheermoteList>(
+                r##"{"data":[{"prefix":"Cheer","type":"global_first_party","tiers":[{"min_bits":100,"id":"100","color":"#9c3ee8","images":{"dark":{"animated":{"1":"http://localhost/cheer/100.gif"},"static":{"1":"http://localhost/cheer/100.png"}},"light":{"animated":{},"static":{}}}}]}]}"##,
+            )
+            .unwrap();
+
+            assert_eq!(
+                parse_cheermote_list(list).get("cheer/100"),
+                Some(&(
+                    "cheer_cheer_100".to_string(),
+                    "http://localhost/cheer/100.gif".to_string(),
+                    false
+                ))
+            );
+        }
+    }
+}
+
 async fn save_emote(path: &Path, mut res: Response) -> Result<()> {
     let mut file = tokio::fs::File::create(&path).await?;
 
@@ -420,15 +641,17 @@ pub async fn get_emotes(
     channel: &str,
 ) -> Result<(DownloadedEmotes, DownloadedEmotes)> {
     // Reuse the same client and headers for twitch requests
-    let twitch_client = get_twitch_client(config.twitch.token.as_deref()).await?;
-    let user_id = &get_twitch_client_id(None).await?.user_id;
+    let twitch_client = get_twitch_client(&config.twitch).await?;
+    let user_id = &get_twitch_client_id(&config.twitch).await?.user_id;
 
-    let channel_id = get_channel_id(&twitch_client, channel).await?;
+    let channel_id = get_channel_id(&twitch_client, &config.twitch.api_url, channel).await?;
 
     let enabled_emotes = get_enabled_emote_providers(&config.frontend);
 
-    let user_emotes = if enabled_emotes.contains(&EmoteProvider::Twitch) {
-        twitch::get_user_emotes(&twitch_client, user_id)
+    let user_emotes = if enabled_emotes.contains(&EmoteProvider::Twitch)
+        && Feature::UserEmotes.is_enabled(&config.twitch)
+    {
+        twitch::get_user_emotes(&twitch_client, &config.twitch.api_url, user_id)
             .await
             .unwrap_or_default()
     } else {
@@ -459,6 +682,56 @@ pub async fn get_emotes(
     ))
 }
 
+/// Global and channel badges, mapped by `set/version` to their filename.
+pub async fn get_badges(config: &CompleteConfig, channel: &str) -> Result<DownloadedBadges> {
+    let twitch_client = get_twitch_client(&config.twitch).await?;
+
+    let channel_id = get_channel_id(&twitch_client, &config.twitch.api_url, channel).await?;
+
+    let badges = badges::get_badges(&twitch_client, &config.twitch.api_url, channel_id).await?;
+
+    Ok(download_emotes(badges)
+        .await
+        .into_iter()
+        .map(|(name, (filename, _))| (name, filename))
+        .collect())
+}
+
+/// Global and channel cheermotes, mapped by their lowercase prefix to the filename of each tier.
+pub async fn get_cheermotes(
+    config: &CompleteConfig,
+    channel: &str,
+) -> Result<DownloadedCheermotes> {
+    let twitch_client = get_twitch_client(&config.twitch).await?;
+
+    let channel_id = get_channel_id(&twitch_client, &config.twitch.api_url, channel).await?;
+
+    let tiers =
+        cheermotes::get_cheermotes(&twitch_client, &config.twitch.api_url, channel_id).await?;
+
+    let mut cheermotes = DownloadedCheermotes::new();
+
+    for (name, (filename, _)) in download_emotes(tiers).await {
+        let Some((prefix, min_bits)) = name
+            .split_once('/')
+            .and_then(|(prefix, min_bits)| Some((prefix.to_string(), min_bits.parse().ok()?)))
+        else {
+            continue;
+        };
+
+        cheermotes
+            .entry(prefix)
+            .or_default()
+            .push((min_bits, filename));
+    }
+
+    for tiers in cheermotes.values_mut() {
+        tiers.sort_unstable();
+    }
+
+    Ok(cheermotes)
+}
+
 pub async fn get_twitch_emote(name: &str) -> Result<()> {
     // Checks if emote is already downloaded.
     let path = cache_path(name);
@@ -13,10 +13,13 @@ use tokio::sync::{
 };
 
 use crate::{
-    emotes::{downloader::get_emotes, graphics_protocol::Image},
+    emotes::{
+        downloader::{get_badges, get_cheermotes, get_emotes},
+        graphics_protocol::Image,
+    },
     handlers::config::CompleteConfig,
     utils::{
-        emotes::{emotes_enabled, get_emote_offset},
+        emotes::{badge_images_enabled, emotes_enabled, get_emote_offset},
         pathing::cache_path,
     },
 };
@@ -30,6 +33,12 @@ pub use graphics_protocol::{support_graphics_protocol, ApplyCommand, DecodedEmot
 // HashMap of emote name, emote filename, and if the emote is an overlay
 pub type DownloadedEmotes = BTreeMap<String, (String, bool)>;
 
+// HashMap of badge name as `set/version`, and badge filename
+pub type DownloadedBadges = BTreeMap<String, String>;
+
+// HashMap of lowercase cheermote prefix, and the bits needed for each tier along with its filename
+pub type DownloadedCheermotes = BTreeMap<String, Vec<(u64, String)>>;
+
 #[derive(Copy, Clone, Debug)]
 pub struct EmoteData {
     pub width: u32,
@@ -103,7 +112,7 @@ pub fn query_emotes(
 ) -> OSReceiver<(DownloadedEmotes, DownloadedEmotes)> {
     let (tx, mut rx) = tokio::sync::oneshot::channel();
 
-    if emotes_enabled(&config.frontend) {
+    if emotes_enabled(&config.frontend) && !config.twitch.anonymous {
         let config = config.clone();
         tokio::spawn(async move { send_emotes(&config, tx, channel).await });
     } else {
@@ -132,6 +141,69 @@ pub async fn send_emotes(
     }
 }
 
+pub fn query_badges(config: &CompleteConfig, channel: String) -> OSReceiver<DownloadedBadges> {
+    let (tx, mut rx) = tokio::sync::oneshot::channel();
+
+    if badge_images_enabled(&config.frontend) && !config.twitch.anonymous {
+        let config = config.clone();
+        tokio::spawn(async move { send_badges(&config, tx, channel).await });
+    } else {
+        rx.close();
+    }
+
+    rx
+}
+
+pub async fn send_badges(config: &CompleteConfig, tx: OSSender<DownloadedBadges>, channel: String) {
+    info!("Starting badges download.");
+    match get_badges(config, &channel).await {
+        Ok(badges) => {
+            info!("Badges downloaded.");
+            if tx.send(badges).is_err() {
+                warn!("Unable to send badges to main thread.");
+            }
+        }
+        Err(e) => {
+            warn!("Unable to download badges: {e}");
+        }
+    }
+}
+
+pub fn query_cheermotes(
+    config: &CompleteConfig,
+    channel: String,
+) -> OSReceiver<DownloadedCheermotes> {
+    let (tx, mut rx) = tokio::sync::oneshot::channel();
+
+    if emotes_enabled(&config.frontend) && !config.twitch.anonymous {
+        let config = config.clone();
+        tokio::spawn(async move { send_cheermotes(&config, tx, channel).await });
+    } else {
+        rx.close();
+    }
+
+    rx
+}
+
+pub async fn send_cheermotes(
+    config: &CompleteConfig,
+    tx: OSSender<DownloadedCheermotes>,
+    channel: String,
+) {
+    info!("Starting cheermotes download.");
+    match get_cheermotes(config, &channel).await {
+        Ok(cheermotes) => {
+            info!("Cheermotes downloaded.");
+            if tx.send(cheermotes).is_err() {
+                warn!("Unable to send cheermotes to main thread.");
+            }
+        }
+        Err(e) => {
+            warn!("Unable to download cheermotes: {e}");
+        }
+    }
+}
+
 pub static DECODE_EMOTE_SENDER: OnceLock<Sender<Image>> = OnceLock::new();
 
 pub fn decoder(mut rx: Receiver<Image>, tx: &Sender<Result<DecodedEmote, String>>) {
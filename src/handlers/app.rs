@@ -10,18 +10,22 @@ use tui::{
 use crate::{
     emotes::SharedEmotes,
     handlers::{
+        channels::{Channels, SharedChannels},
         config::{CompleteConfig, SharedCompleteConfig, Theme},
         data::MessageData,
         filters::{Filters, SharedFilters},
         state::State,
         storage::{SharedStorage, Storage},
         user_input::events::{Event, Key},
+        whispers::{SharedWhispers, Whispers},
     },
     terminal::TerminalAction,
+    twitch::{reply::ReplyParent, user_state::UserState},
     ui::{
         components::{Component, Components},
         statics::LINE_BUFFER_CAPACITY,
     },
+    utils::colors::hex_to_color,
 };
 
 pub type SharedMessages = Rc<RefCell<VecDeque<MessageData>>>;
@@ -32,8 +36,10 @@ pub struct App {
     pub components: Components,
     /// A config for the app and components to share.
     pub config: SharedCompleteConfig,
-    /// History of recorded messages (time, username, message, etc).
-    pub messages: SharedMessages,
+    /// Joined channels, each with their own history of recorded messages (time, username, message, etc).
+    pub channels: SharedChannels,
+    /// Whisper conversations with other users.
+    pub whispers: SharedWhispers,
     /// Data loaded in from a JSON file.
     pub storage: SharedStorage,
     /// Messages to be filtered out.
@@ -50,6 +56,8 @@ pub struct App {
     pub theme: Theme,
     /// Emotes
     pub emotes: SharedEmotes,
+    /// How the current user appears in chat, as sent by Twitch when logging in.
+    pub user_state: UserState,
 }
 
 macro_rules! shared {
@@ -64,6 +72,19 @@ impl App {
 
         let shared_config_borrow = shared_config.borrow();
 
+        let channels = shared!(Channels::new(
+            shared_config_borrow.terminal.maximum_messages
+        ));
+
+        // When starting on the dashboard, the channel is only joined once the user has selected one.
+        if shared_config_borrow.terminal.first_state != State::Dashboard {
+            channels.borrow_mut().join(&config.twitch.channel);
+        }
+
+        let whispers = shared!(Whispers::new(
+            shared_config_borrow.terminal.maximum_messages
+        ));
+
         let storage = shared!(Storage::new("storage.json", &shared_config_borrow.storage));
 
         if !storage
@@ -75,17 +96,14 @@ impl App {
 
         let filters = shared!(Filters::new("filters.txt", &shared_config_borrow.filters));
 
-        let messages = shared!(VecDeque::with_capacity(
-            shared_config_borrow.terminal.maximum_messages,
-        ));
-
         let emotes = SharedEmotes::default();
 
         let components = Components::new(
             &shared_config,
             storage.clone(),
             filters.clone(),
-            messages.clone(),
+            channels.clone(),
+            whispers.clone(),
             &emotes,
             startup_time,
         );
@@ -93,7 +111,8 @@ impl App {
         Self {
             components,
             config: shared_config.clone(),
-            messages,
+            channels,
+            whispers,
             storage,
             filters,
             state: shared_config_borrow.terminal.first_state.clone(),
@@ -102,9 +121,45 @@ impl App {
             buffer_suggestion: None,
             theme: shared_config_borrow.frontend.theme.clone(),
             emotes,
+            user_state: UserState::default(),
         }
     }
 
+    /// A message sent by the current user to the active channel,
+    /// made to look the same as messages from other users.
+    pub fn own_message(
+        &self,
+        payload: String,
+        highlight: bool,
+        reply_parent: Option<ReplyParent>,
+        is_emotes_enabled: bool,
+    ) -> MessageData {
+        let config = self.config.borrow();
+        let channels = self.channels.borrow();
+
+        let user_state = channels
+            .active()
+            .and_then(|channel| channel.user_state.as_ref())
+            .unwrap_or(&self.user_state);
+
+        let mut message = MessageData::new_user_message(
+            user_state.name(&config.twitch.username),
+            self.user_state.user_id.clone(),
+            false,
+            payload,
+            None,
+            highlight,
+            reply_parent,
+            &self.emotes,
+            is_emotes_enabled,
+        );
+
+        message.color = user_state.color.as_deref().and_then(hex_to_color);
+        message.badges.clone_from(&user_state.badges);
+
+        message
+    }
+
     pub fn draw(&mut self, f: &mut Frame) {
         let mut size = f.area();
 
@@ -173,33 +228,128 @@ impl App {
     }
 
     pub fn clear_messages(&mut self) {
-        self.messages.borrow_mut().clear();
+        if let Some(channel) = self.channels.borrow().active() {
+            channel.messages.borrow_mut().clear();
+        }
 
         self.components.chat.scroll_offset.jump_to(0);
     }
 
-    pub fn purge_user_messages(&self, user_id: &str) {
-        let messages = self
-            .messages
-            .borrow_mut()
+    /// Removes or greys out every message of a user, depending on the config.
+    /// Returns the amount of purged messages, along with the name of the user if any were found.
+    pub fn purge_user_messages(&self, channel: &str, user_id: &str) -> (usize, Option<String>) {
+        let Some(channel) = self
+            .channels
+            .borrow()
+            .get(channel)
+            .map(|c| c.messages.clone())
+        else {
+            return (0, None);
+        };
+
+        let mut messages = channel.borrow_mut();
+
+        let is_purged = |m: &MessageData| m.user_id.as_deref() == Some(user_id);
+
+        let name = messages
             .iter()
-            .filter(|&m| m.user_id.clone().map_or(true, |user| user != user_id))
-            .cloned()
-            .collect::<VecDeque<MessageData>>();
+            .find(|m| is_purged(m))
+            .map(|m| m.author.clone());
+
+        let purged = if self.config.borrow().frontend.grey_out_purged_messages {
+            let mut purged = 0;
 
-        self.messages.replace(messages);
+            for message in messages.iter_mut().filter(|m| is_purged(m) && !m.purged) {
+                message.purged = true;
+                purged += 1;
+            }
+
+            purged
+        } else {
+            let before = messages.len();
+            messages.retain(|m| !is_purged(m));
+            before - messages.len()
+        };
+
+        (purged, name)
     }
 
-    pub fn remove_message_with(&self, message_id: &str) {
-        let index = self
-            .messages
-            .borrow_mut()
-            .iter()
-            .position(|f| f.message_id.clone().is_some_and(|id| id == message_id));
+    pub fn remove_message_with(&self, channel: &str, message_id: &str) {
+        if let Some(channel) = self.channels.borrow().get(channel) {
+            let index = channel
+                .messages
+                .borrow()
+                .iter()
+                .position(|f| f.message_id.as_ref().is_some_and(|id| id == message_id));
 
-        if let Some(i) = index {
-            self.messages.borrow_mut().remove(i).unwrap();
+            if let Some(i) = index {
+                channel.messages.borrow_mut().remove(i).unwrap();
+            }
+        }
+    }
+
+    /// Makes a joined channel the one being displayed, keeping the scroll position of every channel.
+    pub fn set_active_channel(&mut self, name: &str) {
+        {
+            let mut channels = self.channels.borrow_mut();
+
+            if let Some(channel) = channels.active_mut() {
+                channel.scroll_offset = self.components.chat.scroll_offset.get_offset();
+            }
+
+            if !channels.set_active(name) {
+                return;
+            }
+        }
+
+        self.load_active_channel();
+    }
+
+    /// Removes a channel, returning the name of the channel that is now displayed, if any.
+    pub fn part_channel(&mut self, name: &str) -> Option<String> {
+        let was_active = self
+            .channels
+            .borrow()
+            .active()
+            .is_some_and(|c| c.name == name);
+
+        self.channels.borrow_mut().part(name);
+
+        if was_active {
+            self.load_active_channel();
         }
+
+        self.channels.borrow().active().map(|c| c.name.clone())
+    }
+
+    fn load_active_channel(&mut self) {
+        let channels = self.channels.borrow();
+
+        let Some(channel) = channels.active() else {
+            self.components.chat.scroll_offset.jump_to(0);
+            return;
+        };
+
+        self.components
+            .chat
+            .scroll_offset
+            .jump_to(channel.scroll_offset);
+
+        // The emote picker and our own messages use the emotes of the current channel.
+        self.emotes
+            .user_emotes
+            .borrow_mut()
+            .clone_from(&channel.user_emotes);
+        self.emotes
+            .global_emotes
+            .borrow_mut()
+            .clone_from(&channel.global_emotes);
+
+        self.config
+            .borrow_mut()
+            .twitch
+            .channel
+            .clone_from(&channel.name);
     }
 
     pub fn get_previous_state(&self) -> Option<State> {
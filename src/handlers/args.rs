@@ -1,4 +1,4 @@
-use clap::{builder::PossibleValue, Parser, ValueEnum};
+use clap::{builder::PossibleValue, Parser, Subcommand, ValueEnum};
 
 use crate::handlers::{
     config::{CompleteConfig, Palette, Theme},
@@ -85,6 +85,17 @@ pub struct Cli {
     /// Show a warning if the screen size is too small
     #[arg(short, long)]
     pub unsupported_screen_size: bool,
+    /// Read chat without logging in, without being able to send messages
+    #[arg(short, long)]
+    pub anonymous: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Log in through the browser, storing the tokens to be used instead of the one in the config
+    Login,
 }
 
 pub fn merge_args_into_config(config: &mut CompleteConfig, args: Cli) {
@@ -110,6 +121,8 @@ pub fn merge_args_into_config(config: &mut CompleteConfig, args: Cli) {
         config.twitch.channel = channel;
     }
 
+    config.twitch.anonymous = config.twitch.anonymous || args.anonymous;
+
     // Frontend arguments
     config.frontend.show_datetimes = config.frontend.show_datetimes || args.show_datetimes;
 
@@ -0,0 +1,756 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::oneshot::Receiver as OSReceiver;
+
+use crate::{
+    emotes::{DownloadedBadges, DownloadedCheermotes, DownloadedEmotes},
+    handlers::{app::SharedMessages, data::MessageData},
+    twitch::{
+        channel_event::{Poll, Prediction},
+        notice::{NoticeKind, OutgoingMessage},
+        polls::Votes,
+        room_state::RoomState,
+        stream_info::StreamStatus,
+        user_state::UserState,
+    },
+};
+
+pub type SharedChannels = Rc<RefCell<Channels>>;
+
+/// Everything that is tracked separately for each joined channel.
+#[derive(Debug)]
+pub struct ChannelData {
+    /// The name of the channel, without the leading `#`.
+    pub name: String,
+    /// History of recorded messages in this channel.
+    pub messages: SharedMessages,
+    /// How many messages are kept in the history before the oldest ones are dropped.
+    maximum_messages: usize,
+    /// Emotes that the current user can use, as queried for this channel.
+    pub user_emotes: DownloadedEmotes,
+    /// Global and third party emotes that can be received in this channel.
+    pub global_emotes: DownloadedEmotes,
+    /// Emotes that are still being downloaded for this channel.
+    pub emotes_rx: Option<OSReceiver<(DownloadedEmotes, DownloadedEmotes)>>,
+    /// Global and channel badge images that can be received in this channel.
+    pub badges: DownloadedBadges,
+    /// Badges that are still being downloaded for this channel.
+    pub badges_rx: Option<OSReceiver<DownloadedBadges>>,
+    /// Global and channel cheermotes that can be received in this channel.
+    pub cheermotes: DownloadedCheermotes,
+    /// Cheermotes that are still being downloaded for this channel.
+    pub cheermotes_rx: Option<OSReceiver<DownloadedCheermotes>>,
+    /// If the channel is live, as of the last check.
+    pub stream_status: StreamStatus,
+    /// The check that is still waiting for Twitch to respond.
+    pub stream_status_rx: Option<OSReceiver<StreamStatus>>,
+    /// When the stream status was last checked.
+    pub stream_checked: Option<Instant>,
+    /// The most recent poll of the channel, from `EventSub` or Helix.
+    pub poll: Option<Poll>,
+    /// The most recent prediction of the channel, from `EventSub` or Helix.
+    pub prediction: Option<Prediction>,
+    /// The poll and prediction check that is still waiting for Twitch to respond.
+    pub votes_rx: Option<OSReceiver<Votes>>,
+    /// When the poll and prediction were last checked.
+    pub votes_checked: Option<Instant>,
+    /// The last known chat settings of the channel.
+    pub room_state: RoomState,
+    /// How the current user appears in this channel, once it has been sent by Twitch.
+    pub user_state: Option<UserState>,
+    /// When a message of the current user was last sent to this channel, used for the slow mode cooldown.
+    pub last_sent: Option<Instant>,
+    /// IDs of the messages of the current user that are waiting to be sent, oldest first.
+    queued: VecDeque<usize>,
+    /// IDs of the messages of the current user that were sent,
+    /// but have not been confirmed or rejected by Twitch yet, oldest first.
+    unconfirmed: VecDeque<usize>,
+    /// ID given to the next message of the current user.
+    next_outgoing_id: usize,
+    /// Scroll offset of the chat window, restored when switching back to this channel.
+    pub scroll_offset: usize,
+    /// Messages received while the channel was not being displayed.
+    pub unread: usize,
+    /// Messages mentioning the current user received while the channel was not being displayed.
+    pub mentions: usize,
+}
+
+impl ChannelData {
+    pub fn new(name: String, maximum_messages: usize) -> Self {
+        Self {
+            name,
+            messages: Rc::new(RefCell::new(VecDeque::with_capacity(maximum_messages))),
+            maximum_messages,
+            user_emotes: DownloadedEmotes::default(),
+            global_emotes: DownloadedEmotes::default(),
+            emotes_rx: None,
+            badges: DownloadedBadges::default(),
+            badges_rx: None,
+            cheermotes: DownloadedCheermotes::default(),
+            cheermotes_rx: None,
+            stream_status: StreamStatus::default(),
+            stream_status_rx: None,
+            stream_checked: None,
+            poll: None,
+            prediction: None,
+            votes_rx: None,
+            votes_checked: None,
+            room_state: RoomState::default(),
+            user_state: None,
+            last_sent: None,
+            queued: VecDeque::new(),
+            unconfirmed: VecDeque::new(),
+            next_outgoing_id: 0,
+            scroll_offset: 0,
+            unread: 0,
+            mentions: 0,
+        }
+    }
+
+    /// Adds a message to the history, dropping the oldest ones past the maximum.
+    pub fn push_message(&self, message: MessageData) {
+        let mut messages = self.messages.borrow_mut();
+
+        messages.push_front(message);
+        messages.truncate(self.maximum_messages);
+    }
+
+    /// If the current user is a moderator or the broadcaster of this channel.
+    pub fn is_moderator(&self) -> bool {
+        self.user_state
+            .as_ref()
+            .is_some_and(UserState::is_moderator)
+    }
+
+    /// How long the current user has to wait before sending another message because of slow mode.
+    /// Moderators, VIPs and the broadcaster are not affected by it.
+    pub fn slow_mode_remaining(&self, now: Instant) -> Option<Duration> {
+        let privileged = self
+            .user_state
+            .as_ref()
+            .is_some_and(UserState::is_privileged);
+
+        if self.room_state.slow == 0 || privileged {
+            return None;
+        }
+
+        let ready_at = self.last_sent? + Duration::from_secs(self.room_state.slow);
+
+        (ready_at > now).then(|| ready_at - now)
+    }
+
+    /// If the stream status should be checked again, there being no check in progress.
+    pub fn stream_status_due(&self, now: Instant, interval: Duration) -> bool {
+        self.stream_status_rx.is_none()
+            && self
+                .stream_checked
+                .map_or(true, |checked| now.duration_since(checked) >= interval)
+    }
+
+    /// If the poll and prediction should be checked again, there being no check in progress.
+    pub fn votes_due(&self, now: Instant, interval: Duration) -> bool {
+        self.votes_rx.is_none()
+            && self
+                .votes_checked
+                .map_or(true, |checked| now.duration_since(checked) >= interval)
+    }
+
+    /// Replaces the poll being shown, unless it is an update to the same poll from before it ended,
+    /// as `EventSub` and Helix do not always agree on the order of things.
+    pub fn update_poll(&mut self, poll: Poll) {
+        let outdated = self
+            .poll
+            .as_ref()
+            .is_some_and(|current| current.id == poll.id && current.ended() && !poll.ended());
+
+        if !outdated {
+            self.poll = Some(poll);
+        }
+    }
+
+    /// Replaces the prediction being shown, unless it is an update to the same prediction from before it ended.
+    pub fn update_prediction(&mut self, prediction: Prediction) {
+        let outdated = self.prediction.as_ref().is_some_and(|current| {
+            current.id == prediction.id && current.ended() && !prediction.ended()
+        });
+
+        if !outdated {
+            self.prediction = Some(prediction);
+        }
+    }
+
+    pub fn update_votes(&mut self, votes: Votes) {
+        if let Some(poll) = votes.poll {
+            self.update_poll(poll);
+        }
+
+        if let Some(prediction) = votes.prediction {
+            self.update_prediction(prediction);
+        }
+    }
+
+    /// A restriction on what the current user can send in this channel, which Twitch would reject messages for.
+    pub fn restriction_warning(&self) -> Option<&'static str> {
+        if self.is_moderator() {
+            return None;
+        }
+
+        let subscriber = self.user_state.as_ref().is_some_and(|user_state| {
+            user_state
+                .badges
+                .iter()
+                .any(|badge| matches!(badge.name.as_str(), "subscriber" | "founder"))
+        });
+
+        if self.room_state.subs_only && !subscriber {
+            Some("subscribers-only")
+        } else if self.room_state.emote_only {
+            Some("emote-only")
+        } else {
+            None
+        }
+    }
+
+    /// Adds a message of the current user that is about to be sent,
+    /// keeping the text as it was typed so that it can be sent again if Twitch rejects it.
+    pub fn push_outgoing(&mut self, mut message: MessageData, text: String) {
+        let id = self.next_outgoing_id;
+        self.next_outgoing_id += 1;
+
+        message.outgoing = Some(OutgoingMessage::new(id, text));
+
+        self.queued.push_back(id);
+        self.push_message(message);
+    }
+
+    /// Moves the oldest queued message of the current user to the ones waiting for Twitch to respond.
+    pub fn mark_sent(&mut self, now: Instant) {
+        self.last_sent = Some(now);
+
+        if let Some(id) = self.queued.pop_front() {
+            self.unconfirmed.push_back(id);
+        }
+    }
+
+    /// Runs a function on the message of the current user with the given ID, if it is still in the buffer.
+    fn with_outgoing<T>(&self, id: usize, f: impl FnOnce(&mut MessageData) -> T) -> Option<T> {
+        self.messages
+            .borrow_mut()
+            .iter_mut()
+            .find(|message| message.outgoing.as_ref().is_some_and(|o| o.id == id))
+            .map(f)
+    }
+
+    /// Twitch has accepted the oldest sent message of the current user, which can no longer be sent again.
+    pub fn confirm_sent(&mut self, message_id: String) {
+        let Some(id) = self.unconfirmed.pop_front() else {
+            return;
+        };
+
+        self.with_outgoing(id, |message| {
+            message.outgoing = None;
+            message.message_id = Some(message_id);
+        });
+    }
+
+    /// Marks the oldest sent message of the current user as rejected by Twitch,
+    /// returning `false` if there is no message waiting for it.
+    pub fn mark_failed(&mut self, kind: &NoticeKind) -> bool {
+        let Some(id) = self.unconfirmed.pop_front() else {
+            return false;
+        };
+
+        self.with_outgoing(id, |message| {
+            if let Some(outgoing) = message.outgoing.as_mut() {
+                outgoing.failure = Some(kind.clone());
+            }
+        })
+        .is_some()
+    }
+
+    /// Removes the most recent message that Twitch rejected, so that it can be sent again.
+    pub fn take_failed(&self) -> Option<MessageData> {
+        let mut messages = self.messages.borrow_mut();
+
+        let index = messages.iter().position(|message| {
+            message
+                .outgoing
+                .as_ref()
+                .is_some_and(|outgoing| outgoing.failure.is_some())
+        })?;
+
+        messages.remove(index)
+    }
+
+    /// Every message in the buffer that is part of a reply thread, from oldest to newest.
+    pub fn reply_thread(&self, thread_id: &str) -> Vec<MessageData> {
+        self.messages
+            .borrow()
+            .iter()
+            .rev()
+            .filter(|m| m.thread_id() == Some(thread_id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// All the channels that are currently joined, in the order they were joined.
+#[derive(Debug)]
+pub struct Channels {
+    channels: Vec<ChannelData>,
+    active: usize,
+    maximum_messages: usize,
+}
+
+impl Channels {
+    pub const fn new(maximum_messages: usize) -> Self {
+        Self {
+            channels: vec![],
+            active: 0,
+            maximum_messages,
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.channels.iter().position(|c| c.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Adds a channel if it has not been joined yet.
+    /// Returns `true` if the channel is new.
+    pub fn join(&mut self, name: &str) -> bool {
+        if self.contains(name) {
+            return false;
+        }
+
+        self.channels
+            .push(ChannelData::new(name.to_string(), self.maximum_messages));
+
+        true
+    }
+
+    /// Removes a channel, keeping the active channel the same if it was not the one removed.
+    pub fn part(&mut self, name: &str) -> Option<ChannelData> {
+        let index = self.position(name)?;
+
+        let channel = self.channels.remove(index);
+
+        if index < self.active || self.active >= self.channels.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+
+        Some(channel)
+    }
+
+    /// Displays a channel, marking all of its messages as read.
+    pub fn set_active(&mut self, name: &str) -> bool {
+        self.position(name).is_some_and(|index| {
+            self.active = index;
+
+            let channel = &mut self.channels[index];
+            channel.unread = 0;
+            channel.mentions = 0;
+
+            true
+        })
+    }
+
+    /// The name of the channel after the active one, wrapping around to the first channel.
+    pub fn next_name(&self) -> Option<String> {
+        let index = (self.active + 1) % self.channels.len().max(1);
+
+        self.channels.get(index).map(|c| c.name.clone())
+    }
+
+    /// The name of the channel before the active one, wrapping around to the last channel.
+    pub fn previous_name(&self) -> Option<String> {
+        let index = self
+            .active
+            .checked_sub(1)
+            .unwrap_or_else(|| self.channels.len().saturating_sub(1));
+
+        self.channels.get(index).map(|c| c.name.clone())
+    }
+
+    /// The name of the next channel, starting after the active one, that has unread mentions.
+    pub fn next_mentioned_name(&self) -> Option<String> {
+        self.channels
+            .iter()
+            .cycle()
+            .skip(self.active + 1)
+            .take(self.channels.len())
+            .find(|c| c.mentions > 0)
+            .map(|c| c.name.clone())
+    }
+
+    pub const fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active(&self) -> Option<&ChannelData> {
+        self.channels.get(self.active)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut ChannelData> {
+        self.channels.get_mut(self.active)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChannelData> {
+        self.channels.iter().find(|c| c.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ChannelData> {
+        self.channels.iter_mut().find(|c| c.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ChannelData> {
+        self.channels.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ChannelData> {
+        self.channels.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        emotes::SharedEmotes,
+        twitch::{badges::Badge, reply::ReplyParent},
+    };
+
+    fn setup() -> Channels {
+        let mut channels = Channels::new(10);
+
+        for name in ["foo", "bar", "baz"] {
+            channels.join(name);
+        }
+
+        channels
+    }
+
+    #[test]
+    fn test_join_existing_channel() {
+        let mut channels = setup();
+
+        assert!(!channels.join("bar"));
+        assert_eq!(channels.channels.len(), 3);
+    }
+
+    #[test]
+    fn test_set_active_channel() {
+        let mut channels = setup();
+
+        assert!(channels.set_active("baz"));
+        assert_eq!(channels.active().unwrap().name, "baz");
+
+        assert!(!channels.set_active("qux"));
+        assert_eq!(channels.active().unwrap().name, "baz");
+    }
+
+    #[test]
+    fn test_set_active_channel_marks_as_read() {
+        let mut channels = setup();
+
+        let channel = channels.get_mut("bar").unwrap();
+        channel.unread = 5;
+        channel.mentions = 2;
+
+        channels.set_active("bar");
+
+        let channel = channels.get("bar").unwrap();
+        assert_eq!(channel.unread, 0);
+        assert_eq!(channel.mentions, 0);
+    }
+
+    #[test]
+    fn test_cycle_channels() {
+        let mut channels = setup();
+
+        assert_eq!(channels.next_name().as_deref(), Some("bar"));
+        assert_eq!(channels.previous_name().as_deref(), Some("baz"));
+
+        channels.set_active("baz");
+
+        assert_eq!(channels.next_name().as_deref(), Some("foo"));
+        assert_eq!(channels.previous_name().as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn test_next_mentioned_channel() {
+        let mut channels = setup();
+
+        assert_eq!(channels.next_mentioned_name(), None);
+
+        channels.get_mut("foo").unwrap().mentions = 1;
+        channels.set_active("bar");
+
+        assert_eq!(channels.next_mentioned_name().as_deref(), Some("foo"));
+    }
+
+    #[test]
+    fn test_reply_thread() {
+        let channels = setup();
+        let channel = channels.get("foo").unwrap();
+
+        let message = |id: &str, thread_id: Option<&str>| {
+            let mut message = MessageData::new_user_message(
+                "human".to_string(),
+                None,
+                false,
+                id.to_string(),
+                Some(id.to_string()),
+                false,
+                None,
+                &SharedEmotes::default(),
+                false,
+            );
+
+            message.reply_parent = thread_id.map(|thread_id| ReplyParent {
+                message_id: thread_id.to_string(),
+                thread_id: thread_id.to_string(),
+                user_login: "human".to_string(),
+                body: thread_id.to_string(),
+            });
+
+            message
+        };
+
+        channel.push_message(message("a", None));
+        channel.push_message(message("b", Some("a")));
+        channel.push_message(message("c", None));
+        channel.push_message(message("d", Some("a")));
+
+        let thread = channel
+            .reply_thread("a")
+            .into_iter()
+            .map(|m| m.payload)
+            .collect::<Vec<String>>();
+
+        assert_eq!(thread, ["a", "b", "d"]);
+    }
+
+    #[test]
+    fn test_push_message_truncates_to_maximum_messages() {
+        let channels = setup();
+        let channel = channels.get("foo").unwrap();
+
+        for i in 0..15 {
+            channel.push_message(MessageData::new_user_message(
+                "human".to_string(),
+                None,
+                false,
+                i.to_string(),
+                None,
+                false,
+                None,
+                &SharedEmotes::default(),
+                false,
+            ));
+        }
+
+        let messages = channel.messages.borrow();
+
+        assert_eq!(messages.len(), 10);
+        assert_eq!(messages.front().unwrap().payload, "14");
+        assert_eq!(messages.back().unwrap().payload, "5");
+    }
+
+    #[test]
+    fn test_mark_and_take_failed() {
+        let mut channels = setup();
+        let channel = channels.get_mut("foo").unwrap();
+        let now = Instant::now();
+
+        assert!(!channel.mark_failed(&NoticeKind::Duplicate));
+
+        for text in ["first", "second", "third"] {
+            let message = MessageData::new_user_message(
+                "human".to_string(),
+                None,
+                false,
+                text.to_string(),
+                None,
+                false,
+                None,
+                &SharedEmotes::default(),
+                false,
+            );
+
+            channel.push_outgoing(message, text.to_string());
+        }
+
+        // Messages that are still queued can not have been rejected yet.
+        assert!(!channel.mark_failed(&NoticeKind::SlowMode));
+
+        channel.mark_sent(now);
+        channel.mark_sent(now);
+        assert_eq!(channel.last_sent, Some(now));
+
+        channel.confirm_sent("abc".to_string());
+        assert!(channel.mark_failed(&NoticeKind::SlowMode));
+        assert!(!channel.mark_failed(&NoticeKind::SlowMode));
+
+        let failed = channel.take_failed().unwrap();
+        assert_eq!(failed.payload, "second");
+        assert!(channel.take_failed().is_none());
+
+        let messages = channel.messages.borrow();
+        let first = messages.back().unwrap();
+        assert_eq!(first.outgoing, None);
+        assert_eq!(first.message_id.as_deref(), Some("abc"));
+        assert!(messages.front().unwrap().outgoing.is_some());
+    }
+
+    #[test]
+    fn test_part_keeps_active_channel() {
+        let mut channels = setup();
+
+        channels.set_active("baz");
+        channels.part("foo");
+
+        assert_eq!(channels.active().unwrap().name, "baz");
+    }
+
+    #[test]
+    fn test_part_active_channel() {
+        let mut channels = setup();
+
+        channels.set_active("baz");
+        channels.part("baz");
+
+        assert_eq!(channels.active().unwrap().name, "bar");
+    }
+
+    #[test]
+    fn test_slow_mode_remaining() {
+        let mut channels = setup();
+        let now = Instant::now();
+
+        let channel = channels.get_mut("foo").unwrap();
+        channel.room_state.slow = 30;
+
+        assert_eq!(channel.slow_mode_remaining(now), None);
+
+        channel.last_sent = Some(now);
+        assert_eq!(
+            channel.slow_mode_remaining(now + Duration::from_secs(10)),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            channel.slow_mode_remaining(now + Duration::from_secs(30)),
+            None
+        );
+
+        channel.user_state = Some(UserState {
+            vip: true,
+            ..UserState::default()
+        });
+        assert_eq!(channel.slow_mode_remaining(now), None);
+
+        channel.user_state = Some(UserState {
+            moderator: true,
+            ..UserState::default()
+        });
+        assert_eq!(channel.slow_mode_remaining(now), None);
+    }
+
+    #[test]
+    fn test_restriction_warning() {
+        let mut channels = setup();
+
+        let channel = channels.get_mut("foo").unwrap();
+        assert_eq!(channel.restriction_warning(), None);
+
+        channel.room_state.emote_only = true;
+        channel.room_state.subs_only = true;
+        assert_eq!(channel.restriction_warning(), Some("subscribers-only"));
+
+        channel.user_state = Some(UserState {
+            badges: Badge::parse("subscriber/12", ""),
+            ..UserState::default()
+        });
+        assert_eq!(channel.restriction_warning(), Some("emote-only"));
+    }
+
+    #[test]
+    fn test_stream_status_due() {
+        let mut channels = setup();
+        let interval = Duration::from_secs(60);
+        let now = Instant::now();
+
+        let channel = channels.get_mut("foo").unwrap();
+        assert!(channel.stream_status_due(now, interval));
+
+        channel.stream_checked = Some(now);
+        assert!(!channel.stream_status_due(now + Duration::from_secs(30), interval));
+        assert!(channel.stream_status_due(now + interval, interval));
+
+        let (_tx, rx) = tokio::sync::oneshot::channel();
+        channel.stream_status_rx = Some(rx);
+        assert!(!channel.stream_status_due(now + interval, interval));
+    }
+
+    #[test]
+    fn test_votes_due() {
+        let mut channels = setup();
+        let interval = Duration::from_secs(30);
+        let now = Instant::now();
+
+        let channel = channels.get_mut("foo").unwrap();
+        assert!(channel.votes_due(now, interval));
+
+        channel.votes_checked = Some(now);
+        assert!(!channel.votes_due(now + Duration::from_secs(10), interval));
+        assert!(channel.votes_due(now + interval, interval));
+
+        let (_tx, rx) = tokio::sync::oneshot::channel();
+        channel.votes_rx = Some(rx);
+        assert!(!channel.votes_due(now + interval, interval));
+    }
+
+    #[test]
+    fn test_update_poll() {
+        let mut channels = setup();
+
+        let poll = Poll {
+            id: "1".to_string(),
+            title: "Heads or Tails?".to_string(),
+            choices: vec![],
+            ends_at: None,
+            ended_at: None,
+        };
+
+        let ended = Poll {
+            ended_at: Some(chrono::Utc::now()),
+            ..poll.clone()
+        };
+
+        let channel = channels.get_mut("foo").unwrap();
+
+        channel.update_poll(ended.clone());
+        channel.update_poll(poll.clone());
+        assert_eq!(channel.poll, Some(ended));
+
+        let next = Poll {
+            id: "2".to_string(),
+            ..poll
+        };
+
+        channel.update_poll(next.clone());
+        assert_eq!(channel.poll, Some(next));
+    }
+}
@@ -3,25 +3,37 @@ use serde::{Deserialize, Serialize};
 use serde_with::DeserializeFromStr;
 use std::{
     cell::RefCell,
+    collections::{hash_map::RandomState, BTreeMap},
     env,
+    fmt::Display,
     fs::{create_dir_all, read_to_string, File},
+    hash::{BuildHasher, Hasher},
     io::Write,
     path::Path,
     rc::Rc,
     str::FromStr,
 };
 use tokio::{runtime::Handle, task};
-use tui::widgets::BorderType;
+use tui::{layout::Direction, widgets::BorderType};
 
 use crate::{
     emotes::support_graphics_protocol,
     handlers::{
-        args::{merge_args_into_config, Cli},
+        args::{merge_args_into_config, Cli, Command},
         interactive::interactive_config,
         state::State,
     },
+    twitch::{
+        login::{start_command_session, start_session, StoredLogin},
+        oauth::{
+            check_token_format, get_twitch_client_id, is_connection_error, is_unauthorized,
+            with_oauth_prefix,
+        },
+        scopes::CHAT_READ,
+        token_command::run_token_command,
+    },
     utils::{
-        emotes::emotes_enabled,
+        emotes::{badge_images_enabled, emotes_enabled},
         pathing::{cache_path, config_path},
     },
 };
@@ -52,8 +64,34 @@ pub struct TwitchConfig {
     pub channel: String,
     /// The IRC channel to connect to.
     pub server: String,
+    /// The port of the IRC server.
+    pub port: u16,
+    /// If the connection to the IRC server should be encrypted.
+    pub tls: bool,
+    /// How to connect to Twitch chat, either through IRC or a WebSocket.
+    pub transport: Transport,
+    /// The WebSocket endpoint to connect to when using the WebSocket transport.
+    pub websocket_url: String,
+    /// The base URL of the Helix API, which can be pointed at a local server for testing.
+    pub api_url: String,
+    /// If channel events that are not sent through IRC, such as channel point redemptions, should be received.
+    pub eventsub: bool,
+    /// The `EventSub` WebSocket endpoint, which can be pointed at the Twitch CLI's mock server for testing.
+    pub eventsub_url: String,
+    /// The base URL of Twitch's authentication server, used for logging in and refreshing tokens.
+    pub auth_url: String,
+    /// Client ID of the application that `twt login` logs in through.
+    pub client_id: String,
     /// The authentication token for the IRC.
     pub token: Option<String>,
+    /// A command whose output is used as the token, such as `pass show twitch`.
+    /// It is run again whenever the token expires or is rejected.
+    pub token_command: Option<String>,
+    /// If chat should be read without logging in, which makes it impossible to send messages.
+    pub anonymous: bool,
+    /// Scopes granted to the token, once it has been validated.
+    #[serde(skip)]
+    pub granted_scopes: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -87,6 +125,8 @@ pub struct FiltersConfig {
     pub enabled: bool,
     /// If the regex filters should be reversed.
     pub reversed: bool,
+    /// If channel events such as subscriptions, raids and announcements should be hidden.
+    pub user_notices: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -100,12 +140,20 @@ pub struct FrontendConfig {
     pub username_shown: bool,
     /// The color palette.
     pub palette: Palette,
+    /// Where the colors of usernames come from.
+    pub username_color: UsernameColor,
     /// Show Title with time and channel.
     pub title_shown: bool,
+    /// Seconds between updates of the stream info shown in the title, with 0 disabling it.
+    pub stream_info_interval: u64,
     /// The amount of space between the chat window and the terminal border.
     pub margin: u16,
     /// Show twitch badges next to usernames.
     pub badges: bool,
+    /// Glyphs to show for badges, replacing the default ones. An empty glyph hides the badge.
+    pub badge_glyphs: BTreeMap<String, String>,
+    /// If badges should be drawn as images instead of glyphs (requires kitty terminal).
+    pub badge_images: bool,
     /// Theme, being either light or dark.
     pub theme: Theme,
     /// If the username should be highlighted when it appears in chat.
@@ -136,8 +184,12 @@ pub struct FrontendConfig {
     pub border_type: Border,
     /// If chat border should be hidden
     pub hide_chat_border: bool,
+    /// The direction in which split chat panes are placed next to each other.
+    pub split_direction: SplitDirection,
     /// If the usernames should be aligned to the right.
     pub right_align_usernames: bool,
+    /// If messages of timed out or banned users should be greyed out instead of removed.
+    pub grey_out_purged_messages: bool,
     /// Do not display the window size warning.
     pub show_unsupported_screen_size: bool,
 }
@@ -148,7 +200,19 @@ impl Default for TwitchConfig {
             username: String::new(),
             channel: String::new(),
             server: "irc.chat.twitch.tv".to_string(),
+            port: 6697,
+            tls: true,
+            transport: Transport::default(),
+            websocket_url: "wss://irc-ws.chat.twitch.tv:443".to_string(),
+            api_url: "https://api.twitch.tv/helix".to_string(),
+            eventsub: true,
+            eventsub_url: "wss://eventsub.wss.twitch.tv/ws".to_string(),
+            auth_url: "https://id.twitch.tv/oauth2".to_string(),
+            client_id: String::new(),
             token: None,
+            token_command: None,
+            anonymous: false,
+            granted_scopes: None,
         }
     }
 }
@@ -172,9 +236,13 @@ impl Default for FrontendConfig {
             datetime_format: "%a %b %e %T %Y".to_string(),
             username_shown: true,
             palette: Palette::default(),
+            username_color: UsernameColor::default(),
             title_shown: true,
+            stream_info_interval: 60,
             margin: 0,
             badges: false,
+            badge_glyphs: BTreeMap::new(),
+            badge_images: false,
             theme: Theme::default(),
             username_highlight: true,
             state_tabs: false,
@@ -190,12 +258,47 @@ impl Default for FrontendConfig {
             recent_channel_count: 5,
             border_type: Border::default(),
             hide_chat_border: false,
+            split_direction: SplitDirection::default(),
             right_align_usernames: false,
+            grey_out_purged_messages: false,
             show_unsupported_screen_size: true,
         }
     }
 }
 
+#[derive(Serialize, DeserializeFromStr, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    #[default]
+    Irc,
+    WebSocket,
+}
+
+impl FromStr for Transport {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "irc" => Ok(Self::Irc),
+            "websocket" => Ok(Self::WebSocket),
+            _ => bail!("Transport '{}' cannot be deserialized", s),
+        }
+    }
+}
+
+impl Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Irc => "irc",
+                Self::WebSocket => "websocket",
+            }
+        )
+    }
+}
+
 #[derive(Serialize, DeserializeFromStr, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Palette {
@@ -225,6 +328,31 @@ impl FromStr for Palette {
     }
 }
 
+#[derive(Serialize, DeserializeFromStr, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsernameColor {
+    /// The color chosen by the user on Twitch, falling back to the palette if they have none.
+    Twitch,
+    /// A color from the palette, based on the username.
+    #[default]
+    Palette,
+    /// The color chosen by the user on Twitch, made readable against the theme.
+    TwitchAdjusted,
+}
+
+impl FromStr for UsernameColor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "twitch" => Ok(Self::Twitch),
+            "palette" => Ok(Self::Palette),
+            "twitch_adjusted" => Ok(Self::TwitchAdjusted),
+            _ => bail!("Username color '{}' cannot be deserialized", s),
+        }
+    }
+}
+
 #[derive(Serialize, DeserializeFromStr, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Theme {
@@ -322,6 +450,35 @@ impl From<Border> for BorderType {
     }
 }
 
+#[derive(Serialize, DeserializeFromStr, Debug, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitDirection {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+impl FromStr for SplitDirection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            _ => bail!("Split direction '{}' cannot be deserialized", s),
+        }
+    }
+}
+
+impl From<SplitDirection> for Direction {
+    fn from(val: SplitDirection) -> Self {
+        match val {
+            SplitDirection::Horizontal => Self::Horizontal,
+            SplitDirection::Vertical => Self::Vertical,
+        }
+    }
+}
+
 pub trait ToVec<T> {
     fn to_vec(&self) -> Vec<T>;
 }
@@ -331,7 +488,18 @@ impl ToVec<(String, String)> for TwitchConfig {
         vec![
             ("Username".to_string(), self.username.to_string()),
             ("Channel".to_string(), self.channel.to_string()),
-            ("Server".to_string(), self.server.to_string()),
+            ("Transport".to_string(), self.transport.to_string()),
+            (
+                "Server".to_string(),
+                format!("{}:{}", self.server, self.port),
+            ),
+            ("TLS".to_string(), self.tls.to_string()),
+            ("WebSocket URL".to_string(), self.websocket_url.to_string()),
+            ("API URL".to_string(), self.api_url.clone()),
+            ("EventSub".to_string(), self.eventsub.to_string()),
+            ("EventSub URL".to_string(), self.eventsub_url.clone()),
+            ("Auth URL".to_string(), self.auth_url.clone()),
+            ("Anonymous".to_string(), self.anonymous.to_string()),
         ]
     }
 }
@@ -367,6 +535,7 @@ impl ToVec<(String, String)> for FiltersConfig {
         vec![
             ("Enabled".to_string(), self.enabled.to_string()),
             ("Reversed".to_string(), self.reversed.to_string()),
+            ("User notices".to_string(), self.user_notices.to_string()),
         ]
     }
 }
@@ -388,8 +557,13 @@ impl ToVec<(String, String)> for FrontendConfig {
             ),
             // ("".to_string(), self.palette.to_string()),
             ("Title shown".to_string(), self.title_shown.to_string()),
+            (
+                "Stream info interval".to_string(),
+                self.stream_info_interval.to_string(),
+            ),
             ("Margin".to_string(), self.margin.to_string()),
             ("Badges".to_string(), self.badges.to_string()),
+            ("Badge images".to_string(), self.badge_images.to_string()),
             // ("".to_string(), self.theme.to_string()),
             (
                 "Username highlight".to_string(),
@@ -432,11 +606,22 @@ impl ToVec<(String, String)> for FrontendConfig {
                 "Right aligned usernames".to_string(),
                 self.right_align_usernames.to_string(),
             ),
+            (
+                "Grey out purged messages".to_string(),
+                self.grey_out_purged_messages.to_string(),
+            ),
         ]
     }
 }
 
 fn persist_config(path: &Path, config: &CompleteConfig) -> Result<()> {
+    let mut config = config.clone();
+
+    // Tokens from a command are kept wherever the command got them from.
+    if config.twitch.token_command.is_some() {
+        config.twitch.token = None;
+    }
+
     let toml_string = toml::to_string(&config)?;
     let mut file = File::create(path)?;
 
@@ -446,6 +631,95 @@ fn persist_config(path: &Path, config: &CompleteConfig) -> Result<()> {
     Ok(())
 }
 
+/// Checks the token with Twitch before anything is started, so that problems are reported up front
+/// instead of when they are first needed. The granted scopes are kept to disable what the token cannot do.
+fn validate_token(twitch_config: &mut TwitchConfig) -> Result<()> {
+    let token = twitch_config.token.as_deref().unwrap_or_default();
+
+    let prefixed = with_oauth_prefix(token);
+
+    if prefixed != token {
+        eprintln!("The token is missing the `oauth:` prefix, which has been added to it.");
+    }
+
+    if let Err(problem) = check_token_format(&prefixed) {
+        bail!("The token {problem}.");
+    }
+
+    twitch_config.token = Some(prefixed);
+
+    let client_id = task::block_in_place(|| {
+        Handle::current().block_on(async { get_twitch_client_id(twitch_config).await })
+    });
+
+    match client_id {
+        Ok(client_id) => {
+            if !client_id.scopes.iter().any(|scope| scope == CHAT_READ) {
+                bail!("The token cannot be used to read chat, as it was not granted the `{CHAT_READ}` scope.");
+            }
+
+            twitch_config.granted_scopes = Some(client_id.scopes.clone());
+        }
+        Err(err) if is_unauthorized(&err) => {
+            if let Some(command) = &twitch_config.token_command {
+                bail!("The token from `{command}` is invalid or has expired.")
+            }
+
+            bail!("The token is invalid or has expired. Generate a new one, or run `twt login`.")
+        }
+        // Twitch being unreachable is reported once connecting to chat.
+        Err(err) if is_connection_error(&err) => {}
+        Err(err) => bail!("The token could not be validated: {err}"),
+    }
+
+    Ok(())
+}
+
+/// Runs `token_command` for the token, which overrides the one in the config.
+fn use_token_command(twitch_config: &mut TwitchConfig) -> Result<()> {
+    let Some(command) = twitch_config
+        .token_command
+        .clone()
+        .filter(|command| !command.trim().is_empty())
+    else {
+        twitch_config.token_command = None;
+
+        return Ok(());
+    };
+
+    let token = run_token_command(&command)?;
+
+    twitch_config.token = Some(token.clone());
+
+    start_command_session(command, token);
+
+    Ok(())
+}
+
+/// Without a token of their own, users that have run `twt login` use the stored one.
+fn use_stored_login(twitch_config: &mut TwitchConfig) {
+    if twitch_config.token.as_ref().is_some_and(|t| !t.is_empty()) {
+        return;
+    }
+
+    if let Some(login) = StoredLogin::load() {
+        if twitch_config.username.is_empty() {
+            twitch_config.username.clone_from(&login.login);
+        }
+
+        twitch_config.token = Some(login.token());
+
+        start_session(twitch_config, login);
+    }
+}
+
+/// Twitch lets anyone read chat without a token, as long as the username starts with `justinfan`.
+fn anonymous_username() -> String {
+    let seed = RandomState::new().build_hasher().finish();
+
+    format!("justinfan{}", seed % 100_000)
+}
+
 const RAW_DEFAULT_CONFIG_URL: &str =
     "https://raw.githubusercontent.com/Xithrius/twitch-tui/main/default-config.toml";
 
@@ -483,8 +757,9 @@ impl CompleteConfig {
         if !p.exists() {
             create_dir_all(p.parent().unwrap()).unwrap();
 
-            if let Some(config) = interactive_config() {
+            if let Some(mut config) = interactive_config() {
                 persist_config(p, &config)?;
+                use_stored_login(&mut config.twitch);
                 Ok(config)
             } else {
                 persist_default_config(p);
@@ -496,13 +771,26 @@ impl CompleteConfig {
                 Err(err) => bail!("Config could not be processed. Error: {:?}", err.message()),
             };
 
+            let logging_in = matches!(cli.command, Some(Command::Login));
+
             merge_args_into_config(&mut config, cli);
 
-            let token = env::var("TWT_TOKEN").ok();
-            if let Some(env_token) = token {
-                if !env_token.is_empty() {
-                    config.twitch.token = Some(env_token);
+            let env_token = env::var("TWT_TOKEN").ok().filter(|t| !t.is_empty());
+            let has_env_token = env_token.is_some();
+            if let Some(env_token) = env_token {
+                config.twitch.token = Some(env_token);
+            }
+
+            if config.twitch.anonymous {
+                config.twitch.username = anonymous_username();
+                config.twitch.token = None;
+            } else {
+                // The token from the environment takes precedence over the command.
+                if !has_env_token && !logging_in {
+                    use_token_command(&mut config.twitch)?;
                 }
+
+                use_stored_login(&mut config.twitch);
             }
 
             {
@@ -510,15 +798,28 @@ impl CompleteConfig {
 
                 let check_token = t.token.as_ref().map_or("", |t| t);
 
-                if t.username.is_empty() || t.channel.is_empty() || check_token.is_empty() {
-                    bail!("Twitch config section is missing one or more of the following: username, channel, token.");
+                if !logging_in
+                    && (t.channel.is_empty()
+                        || (!t.anonymous && (t.username.is_empty() || check_token.is_empty())))
+                {
+                    bail!("Twitch config section is missing one or more of the following: username, channel, token. Run `twt login` to log in without a token.");
                 }
+            }
 
-                if emotes_enabled(&config.frontend) && !support_graphics_protocol().unwrap_or(false)
-                {
+            if !logging_in && !config.twitch.anonymous {
+                validate_token(&mut config.twitch)?;
+            }
+
+            if (emotes_enabled(&config.frontend) || badge_images_enabled(&config.frontend))
+                && !support_graphics_protocol().unwrap_or(false)
+            {
+                if emotes_enabled(&config.frontend) {
                     eprintln!("This terminal does not support the graphics protocol.\nUse a terminal such as kitty, or disable emotes.");
                     std::process::exit(1);
                 }
+
+                // Badges have glyphs to fall back to.
+                config.frontend.badge_images = false;
             }
 
             // Channel names for the IRC connection can only be in lowercase.
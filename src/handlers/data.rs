@@ -8,21 +8,36 @@ use tui::{
     style::{Color, Color::Rgb, Modifier, Style},
     text::{Line, Span},
 };
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::{
-    emotes::{display_emote, load_emote, overlay_emote, DownloadedEmotes, EmoteData, SharedEmotes},
-    handlers::config::{FrontendConfig, Palette, Theme},
+    emotes::{
+        display_emote, load_emote, overlay_emote, DownloadedBadges, DownloadedEmotes, EmoteData,
+        SharedEmotes,
+    },
+    handlers::config::{FrontendConfig, Palette, Theme, UsernameColor},
+    twitch::{
+        badges::{badge_glyphs, Badge},
+        channel_event::ChannelEvent,
+        cheermotes::bits_line,
+        clear_chat::ClearChat,
+        notice::{Notice, NoticeKind, OutgoingMessage},
+        rate_limit::QueueStatus,
+        reply::ReplyParent,
+        room_state::RoomState,
+        user_notice::UserNotice,
+        user_state::UserState,
+    },
     ui::statics::NAME_MAX_CHARACTERS,
     utils::{
-        colors::{hsl_to_rgb, u32_to_color},
+        colors::{hsl_to_rgb, readable_color, u32_to_color},
         emotes::{
             get_emote_offset, UnicodePlaceholder, PRIVATE_USE_UNICODE, ZERO_WIDTH_SPACE,
             ZERO_WIDTH_SPACE_STR,
         },
         styles::{
-            DATETIME_DARK_STYLE, DATETIME_LIGHT_STYLE, HIGHLIGHT_NAME_DARK_STYLE,
-            HIGHLIGHT_NAME_LIGHT_STYLE, SEARCH_STYLE, SYSTEM_CHAT_STYLE,
+            DATETIME_DARK_STYLE, DATETIME_LIGHT_STYLE, FAILED_STYLE, HIGHLIGHT_NAME_DARK_STYLE,
+            HIGHLIGHT_NAME_LIGHT_STYLE, PURGED_STYLE, REPLY_STYLE, SEARCH_STYLE, SYSTEM_CHAT_STYLE,
         },
         text::split_cow_in_place,
     },
@@ -31,9 +46,26 @@ use crate::{
 static FUZZY_FINDER: Lazy<SkimMatcherV2> = Lazy::new(SkimMatcherV2::default);
 
 pub enum TwitchToTerminalAction {
-    Message(RawMessageData),
-    ClearChat(Option<String>),
-    DeleteMessage(String),
+    Message(Box<RawMessageData>),
+    /// A whisper sent directly to the current user, outside of any channel.
+    Whisper(Box<RawMessageData>),
+    /// Channel, and which messages should be removed.
+    ClearChat(String, ClearChat),
+    /// Channel, and the ID of the message to be removed.
+    DeleteMessage(String, String),
+    RoomState(String, RoomState),
+    /// A notice from Twitch, sent to a channel or to the current one if there is none.
+    Notice(Option<String>, Notice),
+    /// How the current user appears in a channel, or in every channel if there is none.
+    UserState(Option<String>, UserState),
+    /// Messages that are waiting to be sent because of rate limits.
+    OutgoingQueue(QueueStatus),
+    /// A message of the current user has been sent to a channel.
+    MessageSent(String),
+    /// Channel, and the ID Twitch gave to the oldest sent message of the current user after accepting it.
+    MessageConfirmed(String, String),
+    /// Channel, and an event that happened in it which was received through `EventSub`.
+    ChannelEvent(String, ChannelEvent),
 }
 
 enum Word {
@@ -51,10 +83,29 @@ pub struct MessageData {
     pub emotes: Vec<(Color, Color)>,
     pub message_id: Option<String>,
     pub highlight: bool,
+    /// The message being replied to, if this message is a reply.
+    pub reply_parent: Option<ReplyParent>,
+    /// The channel event this message was attached to, if any.
+    pub user_notice: Option<UserNotice>,
+    /// If the author was timed out or banned, with their messages being kept around greyed out.
+    pub purged: bool,
+    /// The color the author has chosen on Twitch, if any.
+    pub color: Option<Color>,
+    /// Badges of the author, shown in front of their name.
+    pub badges: Vec<Badge>,
+    /// Images of the badges that could be loaded, with their id, pid and width in columns,
+    /// used instead of the badge glyphs when there are any.
+    pub badge_images: Vec<(Color, Color, u16)>,
+    /// Amount of bits cheered along with the message, if any.
+    pub bits: Option<u64>,
+    /// The message as it was sent by the current user, if it was sent from here.
+    pub outgoing: Option<OutgoingMessage>,
 }
 
 #[derive(Debug, Clone)]
 pub struct RawMessageData {
+    /// The channel this message was sent in, or `None` if it should go to the current channel.
+    pub channel: Option<String>,
     pub time_sent: DateTime<Local>,
     pub author: String,
     pub user_id: Option<String>,
@@ -63,10 +114,16 @@ pub struct RawMessageData {
     pub emotes: DownloadedEmotes,
     pub message_id: Option<String>,
     pub highlight: bool,
+    pub reply_parent: Option<ReplyParent>,
+    pub user_notice: Option<UserNotice>,
+    pub color: Option<Color>,
+    pub badges: Vec<Badge>,
+    pub bits: Option<u64>,
 }
 
 impl RawMessageData {
     pub fn new(
+        channel: Option<String>,
         author: String,
         user_id: Option<String>,
         system: bool,
@@ -74,8 +131,10 @@ impl RawMessageData {
         emotes: DownloadedEmotes,
         message_id: Option<String>,
         highlight: bool,
+        reply_parent: Option<ReplyParent>,
     ) -> Self {
         Self {
+            channel,
             time_sent: Local::now(),
             author,
             user_id,
@@ -84,6 +143,11 @@ impl RawMessageData {
             emotes,
             message_id,
             highlight,
+            reply_parent,
+            user_notice: None,
+            color: None,
+            badges: vec![],
+            bits: None,
         }
     }
 }
@@ -99,6 +163,7 @@ impl MessageData {
         payload: String,
         message_id: Option<String>,
         highlight: bool,
+        reply_parent: Option<ReplyParent>,
         emotes: &SharedEmotes,
         is_emotes_enabled: bool,
     ) -> Self {
@@ -119,20 +184,30 @@ impl MessageData {
             emotes,
             message_id,
             highlight,
+            reply_parent,
+            user_notice: None,
+            purged: false,
+            color: None,
+            badges: vec![],
+            badge_images: vec![],
+            bits: None,
+            outgoing: None,
         }
     }
 
-    /// Used to create a message and parse its emotes using global emotes, and twitch emotes provided through [`RawMessageData`]
+    /// Used to create a message and parse its emotes using the global emotes of the channel it was sent in,
+    /// and twitch emotes provided through [`RawMessageData`]
     pub fn from_twitch_message(
         msg: RawMessageData,
         emotes: &SharedEmotes,
+        channel_emotes: &DownloadedEmotes,
         is_emotes_enabled: bool,
     ) -> Self {
         let (payload, emotes) = Self::parse_emotes(
             msg.payload,
             emotes,
             &msg.emotes,
-            &emotes.global_emotes.borrow(),
+            channel_emotes,
             is_emotes_enabled,
         );
 
@@ -145,10 +220,23 @@ impl MessageData {
             emotes,
             message_id: msg.message_id,
             highlight: msg.highlight,
+            reply_parent: msg.reply_parent,
+            user_notice: msg.user_notice,
+            purged: false,
+            color: msg.color,
+            badges: msg.badges,
+            badge_images: vec![],
+            bits: msg.bits,
+            outgoing: None,
         }
     }
 
-    pub fn reparse_emotes(&mut self, emotes: &SharedEmotes, is_emotes_enabled: bool) {
+    pub fn reparse_emotes(
+        &mut self,
+        emotes: &SharedEmotes,
+        channel_emotes: &DownloadedEmotes,
+        is_emotes_enabled: bool,
+    ) {
         // Small hack to avoid cloning `self.payload`
         let mut payload = String::new();
         swap(&mut payload, &mut self.payload);
@@ -156,7 +244,7 @@ impl MessageData {
         let (payload, emotes) = Self::parse_emotes(
             payload,
             emotes,
-            &emotes.global_emotes.borrow(),
+            channel_emotes,
             &DownloadedEmotes::default(),
             is_emotes_enabled,
         );
@@ -165,6 +253,49 @@ impl MessageData {
         self.emotes.extend(emotes);
     }
 
+    /// Loads the images of the author's badges, which are then shown instead of the badge glyphs.
+    /// Badges that have no image in `badges` are left out.
+    pub fn load_badge_images(&mut self, emotes: &SharedEmotes, badges: &DownloadedBadges) {
+        if badges.is_empty() || self.system {
+            return;
+        }
+
+        let Some(&cell_size) = emotes.cell_size.get() else {
+            return;
+        };
+
+        self.badge_images = self
+            .badges
+            .iter()
+            .filter_map(|badge| {
+                let filename = badges.get(&format!("{}/{}", badge.name, badge.version))?;
+
+                // Images of channel badges differ between channels, so they are loaded by filename.
+                let loaded_badge = load_emote(
+                    filename,
+                    filename,
+                    false,
+                    &mut emotes.info.borrow_mut(),
+                    cell_size,
+                )
+                .map_err(|e| warn!("Unable to load badge {} ({filename}): {e}", badge.name))
+                .ok()?;
+
+                let cols = (loaded_badge.width as f32 / cell_size.0).ceil() as u16;
+
+                display_emote(loaded_badge.hash, loaded_badge.n, cols)
+                    .map_err(|e| warn!("Unable to display badge: {e}"))
+                    .ok()?;
+
+                Some((
+                    u32_to_color(loaded_badge.hash),
+                    u32_to_color(loaded_badge.n),
+                    cols,
+                ))
+            })
+            .collect();
+    }
+
     fn is_emote<'a>(
         word: &str,
         set1: &'a DownloadedEmotes,
@@ -298,6 +429,75 @@ impl MessageData {
         (payload, emotes)
     }
 
+    /// If the message mentions the given username as a whole word, with or without a leading `@`,
+    /// ignoring case.
+    pub fn mentions(&self, username: &str) -> bool {
+        !username.is_empty()
+            && self
+                .payload
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .any(|word| word.eq_ignore_ascii_case(username))
+    }
+
+    /// ID of the reply thread this message is part of, which is its own ID if it could start one.
+    pub fn thread_id(&self) -> Option<&str> {
+        self.reply_parent
+            .as_ref()
+            .map(|reply| reply.thread_id.as_str())
+            .or(self.message_id.as_deref())
+    }
+
+    /// Builds the reply parent for a message replying to this one.
+    pub fn as_reply_parent(&self) -> Option<ReplyParent> {
+        let message_id = self.message_id.clone()?;
+
+        Some(ReplyParent {
+            thread_id: self
+                .thread_id()
+                .map_or_else(|| message_id.clone(), ToString::to_string),
+            message_id,
+            user_login: self.author.clone(),
+            body: self.payload.clone(),
+        })
+    }
+
+    /// A single line of context for replies, cut off if it does not fit within the width.
+    fn reply_line(reply: &ReplyParent, width: usize) -> Line<'static> {
+        let mut context = format!("\u{21b3} replying to @{}: {}", reply.user_login, reply.body);
+
+        if context.width() > width {
+            let mut truncated = String::new();
+            let mut truncated_width = 0;
+
+            for c in context.chars() {
+                let char_width = c.width().unwrap_or(0);
+
+                // Leave space for the ellipsis.
+                if truncated_width + char_width >= width {
+                    break;
+                }
+
+                truncated.push(c);
+                truncated_width += char_width;
+            }
+
+            truncated.push('\u{2026}');
+            context = truncated;
+        }
+
+        Line::from(Span::styled(context, *REPLY_STYLE))
+    }
+
+    /// Lines below a message that Twitch rejected, with the reason it gave.
+    fn failure_lines(reason: &str, width: usize) -> Vec<Line<'static>> {
+        let text = format!("\u{2717} Not sent, {reason}. Press R to retry.");
+
+        textwrap::wrap(&text, width)
+            .into_iter()
+            .map(|line| Line::from(Span::styled(line.into_owned(), *FAILED_STYLE)))
+            .collect()
+    }
+
     fn hash_username(&self, palette: &Palette) -> Color {
         let hash = f64::from(
             self.author
@@ -319,6 +519,19 @@ impl MessageData {
         Rgb(rgb[0], rgb[1], rgb[2])
     }
 
+    /// The color of the author's name, depending on where the user wants it to come from.
+    fn username_color(&self, frontend_config: &FrontendConfig) -> Color {
+        let twitch_color = match frontend_config.username_color {
+            UsernameColor::Palette => None,
+            UsernameColor::Twitch => self.color,
+            UsernameColor::TwitchAdjusted => self
+                .color
+                .map(|color| readable_color(color, &frontend_config.theme)),
+        };
+
+        twitch_color.unwrap_or_else(|| self.hash_username(&frontend_config.palette))
+    }
+
     fn char_to_byte_indices(s: &str, char_indices: impl Iterator<Item = usize>) -> Vec<usize> {
         let mut chars = s.chars();
         let mut positions = 0..;
@@ -457,12 +670,22 @@ impl MessageData {
         username_highlight: Option<&str>,
     ) -> Vec<Line> {
         // Theme styles
-        let fg = self.hash_username(&frontend_config.palette);
+        let fg = self.username_color(frontend_config);
 
-        let message_theme = if self.highlight {
+        let failure = self
+            .outgoing
+            .as_ref()
+            .and_then(|outgoing| outgoing.failure.as_ref());
+
+        let message_theme = if self.purged || failure.is_some() {
+            *PURGED_STYLE
+        } else if self.highlight {
             Style::default().fg(fg).add_modifier(Modifier::ITALIC)
         } else {
-            Style::default()
+            self.user_notice
+                .as_ref()
+                .and_then(UserNotice::message_style)
+                .unwrap_or_default()
         };
         let username_theme = match frontend_config.theme {
             Theme::Dark => *HIGHLIGHT_NAME_DARK_STYLE,
@@ -470,6 +693,8 @@ impl MessageData {
         };
         let author_theme = if self.system {
             *SYSTEM_CHAT_STYLE
+        } else if self.purged {
+            *PURGED_STYLE
         } else {
             Style::default().fg(fg)
         };
@@ -518,9 +743,34 @@ impl MessageData {
         // Add 1 for the space after the timestamp
         let time_sent_len = time_sent.as_ref().map_or(0, |t| t.len() + 1);
 
+        let badges = if !frontend_config.badges || self.system {
+            vec![]
+        } else if self.badge_images.is_empty() {
+            let glyphs = badge_glyphs(&self.badges, &frontend_config.badge_glyphs);
+
+            if glyphs.is_empty() {
+                vec![]
+            } else {
+                vec![Span::styled(glyphs, author_theme)]
+            }
+        } else {
+            self.badge_images
+                .iter()
+                .map(|&(id, pid, cols)| {
+                    Self::get_emote_span(
+                        UnicodePlaceholder::new(cols.into()).string(),
+                        &mut &[(id, pid)][..],
+                    )
+                })
+                .collect()
+        };
+
+        // Badges are shown in front of the name, but are not a part of it.
+        let name_width = badges.iter().map(Span::width).sum::<usize>() + self.author.width();
+
         let prefix_len = if frontend_config.username_shown {
             // Add 2 for the ": "
-            time_sent_len + self.author.len() + 2
+            time_sent_len + name_width + 2
         } else {
             time_sent_len
         };
@@ -532,17 +782,27 @@ impl MessageData {
             width - window_margin * 2
         } - 1;
 
+        let mut notice_lines = self
+            .user_notice
+            .as_ref()
+            .map(|notice| notice.to_lines(wrap_limit))
+            .unwrap_or_default();
+
+        notice_lines.extend(self.bits.map(bits_line));
+
         let prefix = " ".repeat(prefix_len);
         let opts = textwrap::Options::new(wrap_limit).initial_indent(&prefix);
         let wrapped_message = textwrap::wrap(&self.payload, opts);
+
+        // Channel events are not required to have a message attached to them.
         if wrapped_message.is_empty() {
-            return vec![];
+            return notice_lines;
         }
         let mut lines = wrapped_message.into_iter();
 
         let username_alignment = if frontend_config.username_shown {
             if frontend_config.right_align_usernames {
-                NAME_MAX_CHARACTERS.saturating_sub(self.author.width()) + 1
+                NAME_MAX_CHARACTERS.saturating_sub(name_width) + 1
             } else {
                 1
             }
@@ -560,6 +820,8 @@ impl MessageData {
         }
 
         if frontend_config.username_shown {
+            first_row.extend(badges);
+
             first_row.extend(vec![
                 Span::styled(&self.author, author_theme),
                 Span::raw(": "),
@@ -589,7 +851,13 @@ impl MessageData {
 
         let mut space_idx = space_iter.next();
 
-        let mut rows = vec![Line::from(first_row)];
+        let mut rows: Vec<Line> = notice_lines;
+
+        if let Some(reply) = &self.reply_parent {
+            rows.push(Self::reply_line(reply, wrap_limit));
+        }
+
+        rows.push(Line::from(first_row));
 
         rows.extend(lines.map(|line| {
             while space_idx.is_some_and(|x| x < next_index) {
@@ -611,6 +879,10 @@ impl MessageData {
             ))
         }));
 
+        if let Some(reason) = failure.and_then(NoticeKind::reason) {
+            rows.extend(Self::failure_lines(reason, wrap_limit));
+        }
+
         rows
     }
 }
@@ -627,20 +899,70 @@ impl<'conf> DataBuilder<'conf> {
     }
 
     pub fn user(
+        channel: String,
         user: String,
         user_id: Option<String>,
         payload: String,
         emotes: DownloadedEmotes,
         message_id: Option<String>,
         highlight: bool,
+        reply_parent: Option<ReplyParent>,
+        color: Option<Color>,
+        badges: Vec<Badge>,
+        bits: Option<u64>,
     ) -> TwitchToTerminalAction {
-        TwitchToTerminalAction::Message(RawMessageData::new(
-            user, user_id, false, payload, emotes, message_id, highlight,
-        ))
+        let mut message = RawMessageData::new(
+            Some(channel),
+            user,
+            user_id,
+            false,
+            payload,
+            emotes,
+            message_id,
+            highlight,
+            reply_parent,
+        );
+
+        message.color = color;
+        message.badges = badges;
+        message.bits = bits;
+
+        TwitchToTerminalAction::Message(Box::new(message))
+    }
+
+    pub fn user_notice(
+        channel: String,
+        user: String,
+        user_id: Option<String>,
+        payload: String,
+        emotes: DownloadedEmotes,
+        message_id: Option<String>,
+        user_notice: UserNotice,
+        color: Option<Color>,
+        badges: Vec<Badge>,
+    ) -> TwitchToTerminalAction {
+        let mut message = RawMessageData::new(
+            Some(channel),
+            user,
+            user_id,
+            false,
+            payload,
+            emotes,
+            message_id,
+            false,
+            None,
+        );
+
+        message.user_notice = Some(user_notice);
+        message.color = color;
+        message.badges = badges;
+
+        TwitchToTerminalAction::Message(Box::new(message))
     }
 
     pub fn system(self, payload: String) -> TwitchToTerminalAction {
-        TwitchToTerminalAction::Message(RawMessageData::new(
+        TwitchToTerminalAction::Message(Box::new(RawMessageData::new(
+            None,
             "System".to_string(),
             None,
             true,
@@ -648,11 +970,13 @@ impl<'conf> DataBuilder<'conf> {
             DownloadedEmotes::default(),
             None,
             false,
-        ))
+            None,
+        )))
     }
 
-    pub fn twitch(self, payload: String) -> TwitchToTerminalAction {
-        TwitchToTerminalAction::Message(RawMessageData::new(
+    pub fn twitch(self, channel: Option<String>, payload: String) -> TwitchToTerminalAction {
+        TwitchToTerminalAction::Message(Box::new(RawMessageData::new(
+            channel,
             "Twitch".to_string(),
             None,
             true,
@@ -660,7 +984,8 @@ impl<'conf> DataBuilder<'conf> {
             DownloadedEmotes::default(),
             None,
             false,
-        ))
+            None,
+        )))
     }
 }
 
@@ -682,12 +1007,85 @@ mod tests {
                 emotes: vec![],
                 message_id: None,
                 highlight: false,
+                reply_parent: None,
+                user_notice: None,
+                purged: false,
+                color: None,
+                badges: vec![],
+                badge_images: vec![],
+                bits: None,
+                outgoing: None,
             }
             .hash_username(&Palette::Pastel),
             Rgb(159, 223, 221)
         );
     }
 
+    #[test]
+    fn test_username_color_mode() {
+        let mut message = MessageData::new_user_message(
+            "human".to_string(),
+            None,
+            false,
+            "beep boop".to_string(),
+            None,
+            false,
+            None,
+            &SharedEmotes::default(),
+            false,
+        );
+
+        let mut frontend_config = FrontendConfig {
+            username_color: UsernameColor::Twitch,
+            ..Default::default()
+        };
+
+        // Users without a Twitch color always use the palette.
+        assert_eq!(message.username_color(&frontend_config), Rgb(159, 223, 221));
+
+        message.color = Some(Rgb(0, 0, 139));
+        assert_eq!(message.username_color(&frontend_config), Rgb(0, 0, 139));
+
+        frontend_config.username_color = UsernameColor::TwitchAdjusted;
+        assert_eq!(message.username_color(&frontend_config), Rgb(50, 50, 255));
+
+        frontend_config.username_color = UsernameColor::Palette;
+        assert_eq!(message.username_color(&frontend_config), Rgb(159, 223, 221));
+    }
+
+    #[test]
+    fn test_message_mentions_username() {
+        let mut message = MessageData {
+            time_sent: DateTime::default(),
+            author: "human".to_string(),
+            user_id: None,
+            system: false,
+            payload: "hello @Xithrius".to_string(),
+            emotes: vec![],
+            message_id: None,
+            highlight: false,
+            reply_parent: None,
+            user_notice: None,
+            purged: false,
+            color: None,
+            badges: vec![],
+            badge_images: vec![],
+            bits: None,
+            outgoing: None,
+        };
+
+        assert!(message.mentions("xithrius"));
+        assert!(!message.mentions("human"));
+        assert!(!message.mentions(""));
+
+        message.payload = "bobby, hello".to_string();
+        assert!(!message.mentions("bob"));
+        assert!(message.mentions("bobby"));
+
+        message.payload = "hi bob!".to_string();
+        assert!(message.mentions("bob"));
+    }
+
     const EMOTES_ID_PID: [(Color, Color); 3] = [
         (Color::Red, Color::Green),
         (Color::Black, Color::Cyan),
@@ -951,6 +1349,7 @@ mod tests {
     #[test]
     fn build_vec_with_wraps_and_highlights() {
         let raw_message = RawMessageData::new(
+            None,
             "foo".to_string(),
             None,
             false,
@@ -958,9 +1357,15 @@ mod tests {
             BTreeMap::new(),
             None,
             false,
+            None,
         );
 
-        let data = MessageData::from_twitch_message(raw_message, &SharedEmotes::default(), false);
+        let data = MessageData::from_twitch_message(
+            raw_message,
+            &SharedEmotes::default(),
+            &DownloadedEmotes::default(),
+            false,
+        );
 
         let frontendconfig = FrontendConfig {
             show_datetimes: false,
@@ -998,4 +1403,130 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn build_vec_with_badges() {
+        let mut raw_message = RawMessageData::new(
+            None,
+            "foo".to_string(),
+            None,
+            false,
+            "bar".to_string(),
+            BTreeMap::new(),
+            None,
+            false,
+            None,
+        );
+
+        raw_message.badges = Badge::parse("moderator/1,subscriber/12", "subscriber/14");
+
+        let data = MessageData::from_twitch_message(
+            raw_message,
+            &SharedEmotes::default(),
+            &DownloadedEmotes::default(),
+            false,
+        );
+
+        let frontendconfig = FrontendConfig {
+            show_datetimes: false,
+            badges: true,
+            badge_glyphs: BTreeMap::from([("subscriber".to_string(), "[{info}]".to_string())]),
+            ..FrontendConfig::default()
+        };
+
+        let lines = data.to_vec(&frontendconfig, 20, None, None);
+
+        // Badges are kept apart from the name, which keeps its color.
+        assert_eq!(data.author, "foo");
+        assert_eq!(
+            lines,
+            vec![Line::from(vec![
+                Span::styled(
+                    "\u{1F528}[14]",
+                    Style::default().fg(data.hash_username(&Palette::Pastel))
+                ),
+                Span::styled("foo", data.hash_username(&Palette::Pastel)),
+                Span::raw(": "),
+                Span::raw("bar")
+            ])]
+        );
+    }
+
+    #[test]
+    fn build_vec_with_bits() {
+        let mut raw_message = RawMessageData::new(
+            None,
+            "foo".to_string(),
+            None,
+            false,
+            "Cheer100 bar".to_string(),
+            BTreeMap::new(),
+            None,
+            false,
+            None,
+        );
+
+        raw_message.bits = Some(100);
+
+        let data = MessageData::from_twitch_message(
+            raw_message,
+            &SharedEmotes::default(),
+            &DownloadedEmotes::default(),
+            false,
+        );
+
+        let frontendconfig = FrontendConfig {
+            show_datetimes: false,
+            ..FrontendConfig::default()
+        };
+
+        let lines = data.to_vec(&frontendconfig, 30, None, None);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], bits_line(100));
+        assert_eq!(lines[1].spans[2], Span::raw("Cheer100 bar"));
+    }
+
+    #[test]
+    fn build_vec_with_reply_context() {
+        let raw_message = RawMessageData::new(
+            None,
+            "foo".to_string(),
+            None,
+            false,
+            "bar".to_string(),
+            BTreeMap::new(),
+            None,
+            false,
+            Some(ReplyParent {
+                message_id: "1".to_string(),
+                thread_id: "1".to_string(),
+                user_login: "baz".to_string(),
+                body: "a long message".to_string(),
+            }),
+        );
+
+        let data = MessageData::from_twitch_message(
+            raw_message,
+            &SharedEmotes::default(),
+            &DownloadedEmotes::default(),
+            false,
+        );
+
+        let frontendconfig = FrontendConfig {
+            show_datetimes: false,
+            ..FrontendConfig::default()
+        };
+
+        let lines = data.to_vec(&frontendconfig, 28, None, None);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            Line::from(Span::styled(
+                "\u{21b3} replying to @baz: a lo\u{2026}",
+                *REPLY_STYLE
+            ))
+        );
+    }
 }
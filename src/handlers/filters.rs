@@ -2,7 +2,10 @@ use std::{cell::RefCell, fs::read_to_string, rc::Rc};
 
 use regex::Regex;
 
-use crate::{handlers::config::FiltersConfig, utils::pathing::config_path};
+use crate::{
+    handlers::{config::FiltersConfig, data::MessageData},
+    utils::pathing::config_path,
+};
 
 pub type SharedFilters = Rc<RefCell<Filters>>;
 
@@ -11,6 +14,7 @@ pub struct Filters {
     captures: Vec<Regex>,
     enabled: bool,
     reversed: bool,
+    user_notices: bool,
 }
 
 impl Filters {
@@ -29,6 +33,7 @@ impl Filters {
             ),
             enabled: config.enabled,
             reversed: config.reversed,
+            user_notices: config.user_notices,
         }
     }
 
@@ -44,6 +49,11 @@ impl Filters {
         self.reversed
     }
 
+    /// If a message should be hidden, either because of its contents or because it is a channel event.
+    pub fn filtered(&self, message: &MessageData) -> bool {
+        (self.user_notices && message.user_notice.is_some()) || self.contaminated(&message.payload)
+    }
+
     pub const fn enabled(&self) -> bool {
         self.enabled
     }
@@ -59,17 +69,30 @@ impl Filters {
     pub fn reverse(&mut self) {
         self.reversed = !self.reversed;
     }
+
+    pub const fn user_notices(&self) -> bool {
+        self.user_notices
+    }
+
+    pub fn toggle_user_notices(&mut self) {
+        self.user_notices = !self.user_notices;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        emotes::SharedEmotes,
+        twitch::user_notice::{UserNotice, UserNoticeKind},
+    };
 
     fn setup() -> Filters {
         Filters {
             captures: vec![Regex::new("^bad.*$").unwrap()],
             enabled: true,
             reversed: false,
+            user_notices: false,
         }
     }
 
@@ -104,4 +127,32 @@ mod tests {
 
         assert!(filters.contaminated("not a bad word"));
     }
+
+    #[test]
+    fn test_filtered_user_notices() {
+        let mut filters = setup();
+
+        let mut message = MessageData::new_user_message(
+            "human".to_string(),
+            None,
+            false,
+            "hello".to_string(),
+            None,
+            false,
+            None,
+            &SharedEmotes::default(),
+            false,
+        );
+
+        message.user_notice = Some(UserNotice {
+            kind: UserNoticeKind::Sub,
+            system_message: "human subscribed!".to_string(),
+        });
+
+        assert!(!filters.filtered(&message));
+
+        filters.toggle_user_notices();
+
+        assert!(filters.filtered(&message));
+    }
 }
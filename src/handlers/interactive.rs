@@ -1,6 +1,10 @@
 use dialoguer::{console::Style, theme::ColorfulTheme, Confirm, Input};
+use tokio::{runtime::Handle, task};
 
-use crate::handlers::config::{CompleteConfig, TwitchConfig};
+use crate::{
+    handlers::config::{CompleteConfig, TwitchConfig},
+    twitch::login::login,
+};
 
 pub(super) fn interactive_config() -> Option<CompleteConfig> {
     let theme = ColorfulTheme {
@@ -17,34 +21,52 @@ pub(super) fn interactive_config() -> Option<CompleteConfig> {
         return None;
     }
 
-    let username: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Username: ")
-        .interact_text()
-        .unwrap();
+    let browser_login = Confirm::with_theme(&theme)
+        .with_prompt("Do you want to log in through your browser instead of pasting a token?")
+        .interact()
+        .ok()?;
 
-    let token: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Token: ")
-        .interact_text()
-        .unwrap();
+    let mut twitch = TwitchConfig::default();
+
+    if browser_login {
+        twitch.client_id = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Client ID of your application: ")
+            .interact_text()
+            .unwrap();
+
+        // The tokens are stored separately, and used in place of the token in the config.
+        let tokens = task::block_in_place(|| Handle::current().block_on(login(&twitch)))
+            .map_err(|err| eprintln!("{err}"))
+            .ok()?;
+
+        twitch.username = tokens.login;
+    } else {
+        twitch.username = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Username: ")
+            .interact_text()
+            .unwrap();
+
+        twitch.token = Some(
+            Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Token: ")
+                .interact_text()
+                .unwrap(),
+        );
+    }
 
-    let channel: String = Input::with_theme(&ColorfulTheme::default())
+    twitch.channel = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("Channel: ")
         .interact_text()
         .unwrap();
 
-    let server: String = Input::with_theme(&ColorfulTheme::default())
+    twitch.server = Input::with_theme(&ColorfulTheme::default())
         .with_prompt("IRC server: ")
         .default("irc.chat.twitch.tv".to_string())
         .interact_text()
         .unwrap();
 
     Some(CompleteConfig {
-        twitch: TwitchConfig {
-            username,
-            channel,
-            server,
-            token: Some(token),
-        },
+        twitch,
         ..Default::default()
     })
 }
@@ -1,5 +1,6 @@
 pub mod app;
 pub mod args;
+pub mod channels;
 pub mod config;
 pub mod data;
 pub mod filters;
@@ -7,3 +8,4 @@ mod interactive;
 pub mod state;
 pub mod storage;
 pub mod user_input;
+pub mod whispers;
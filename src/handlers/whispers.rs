@@ -0,0 +1,164 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use crate::handlers::data::MessageData;
+
+pub type SharedWhispers = Rc<RefCell<Whispers>>;
+
+/// A whisper conversation with a single user.
+#[derive(Debug)]
+pub struct WhisperThread {
+    /// Login name of the other user in the conversation.
+    pub user: String,
+    /// Whispers sent and received in this conversation, from newest to oldest.
+    pub messages: VecDeque<MessageData>,
+    /// Whispers received while the conversation was not being displayed.
+    pub unread: usize,
+}
+
+impl WhisperThread {
+    const fn new(user: String) -> Self {
+        Self {
+            user,
+            messages: VecDeque::new(),
+            unread: 0,
+        }
+    }
+}
+
+/// All whisper conversations, with the most recently active one first.
+#[derive(Debug)]
+pub struct Whispers {
+    threads: Vec<WhisperThread>,
+    maximum_messages: usize,
+}
+
+impl Whispers {
+    pub const fn new(maximum_messages: usize) -> Self {
+        Self {
+            threads: vec![],
+            maximum_messages,
+        }
+    }
+
+    /// Adds a whisper to the conversation with a user, moving it to the top of the list.
+    pub fn push(&mut self, user: &str, message: MessageData, unread: bool) {
+        let mut thread = self
+            .threads
+            .iter()
+            .position(|t| t.user == user)
+            .map_or_else(
+                || WhisperThread::new(user.to_string()),
+                |index| self.threads.remove(index),
+            );
+
+        thread.messages.push_front(message);
+        thread.messages.truncate(self.maximum_messages);
+
+        if unread {
+            thread.unread += 1;
+        }
+
+        self.threads.insert(0, thread);
+    }
+
+    /// Marks every whisper in the conversation with a user as read.
+    pub fn mark_read(&mut self, user: &str) {
+        if let Some(thread) = self.threads.iter_mut().find(|t| t.user == user) {
+            thread.unread = 0;
+        }
+    }
+
+    /// Total amount of unread whispers across all conversations.
+    pub fn unread(&self) -> usize {
+        self.threads.iter().map(|t| t.unread).sum()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&WhisperThread> {
+        self.threads.get(index)
+    }
+
+    pub fn position(&self, user: &str) -> Option<usize> {
+        self.threads.iter().position(|t| t.user == user)
+    }
+
+    pub fn len(&self) -> usize {
+        self.threads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &WhisperThread> {
+        self.threads.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emotes::SharedEmotes;
+
+    fn message(payload: &str) -> MessageData {
+        MessageData::new_user_message(
+            "human".to_string(),
+            None,
+            false,
+            payload.to_string(),
+            None,
+            false,
+            None,
+            &SharedEmotes::default(),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_most_recent_thread_first() {
+        let mut whispers = Whispers::new(10);
+
+        whispers.push("foo", message("a"), false);
+        whispers.push("bar", message("b"), false);
+        whispers.push("foo", message("c"), false);
+
+        let users = whispers.iter().map(|t| t.user.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(users, ["foo", "bar"]);
+        assert_eq!(whispers.get(0).unwrap().messages.len(), 2);
+    }
+
+    #[test]
+    fn test_unread_whispers() {
+        let mut whispers = Whispers::new(10);
+
+        whispers.push("foo", message("a"), true);
+        whispers.push("foo", message("b"), true);
+        whispers.push("bar", message("c"), true);
+        whispers.push("baz", message("d"), false);
+
+        assert_eq!(whispers.unread(), 3);
+
+        whispers.mark_read("foo");
+
+        assert_eq!(whispers.unread(), 1);
+    }
+
+    #[test]
+    fn test_thread_truncated_to_maximum_messages() {
+        let mut whispers = Whispers::new(2);
+
+        for payload in ["a", "b", "c"] {
+            whispers.push("foo", message(payload), false);
+        }
+
+        let payloads = whispers
+            .get(0)
+            .unwrap()
+            .messages
+            .iter()
+            .map(|m| m.payload.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(payloads, ["c", "b"]);
+    }
+}
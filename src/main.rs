@@ -24,8 +24,12 @@ use std::thread;
 use tokio::sync::{broadcast, mpsc};
 
 use crate::{
-    handlers::{app::App, args::Cli, config::CompleteConfig},
-    utils::emotes::emotes_enabled,
+    handlers::{
+        app::App,
+        args::{Cli, Command},
+        config::CompleteConfig,
+    },
+    utils::emotes::{badge_images_enabled, emotes_enabled},
 };
 
 mod commands;
@@ -71,7 +75,10 @@ async fn main() -> Result<()> {
 
     color_eyre::install().unwrap();
 
-    let mut config = CompleteConfig::new(Cli::parse())
+    let cli = Cli::parse();
+    let command = cli.command.clone();
+
+    let mut config = CompleteConfig::new(cli)
         .wrap_err("Configuration error.")
         .unwrap();
 
@@ -79,6 +86,12 @@ async fn main() -> Result<()> {
 
     info!("Logging system initialised");
 
+    if command == Some(Command::Login) {
+        twitch::login::login(&config.twitch).await?;
+
+        return Ok(());
+    }
+
     let (twitch_tx, terminal_rx) = mpsc::channel(100);
     let (terminal_tx, twitch_rx) = broadcast::channel(100);
 
@@ -86,7 +99,7 @@ async fn main() -> Result<()> {
 
     info!("Started tokio communication channels.");
 
-    let decoded_rx = if emotes_enabled(&config.frontend) {
+    let decoded_rx = if emotes_enabled(&config.frontend) || badge_images_enabled(&config.frontend) {
         // We need to probe the terminal for it's size before starting the tui,
         // as writing on stdout on a different thread can interfere.
         match crossterm::terminal::window_size() {
@@ -114,7 +127,8 @@ async fn main() -> Result<()> {
                 config.frontend.betterttv_emotes = false;
                 config.frontend.seventv_emotes = false;
                 config.frontend.frankerfacez_emotes = false;
-                warn!("Unable to query terminal for it's dimensions, disabling emotes. {e}");
+                config.frontend.badge_images = false;
+                warn!("Unable to query terminal for it's dimensions, disabling emotes and badge images. {e}");
                 None
             }
         }
@@ -124,6 +138,14 @@ async fn main() -> Result<()> {
 
     let cloned_config = config.clone();
 
+    let eventsub_config = config.clone();
+    let eventsub_tx = twitch_tx.clone();
+    let eventsub_rx = terminal_tx.subscribe();
+
+    tokio::task::spawn(async move {
+        twitch::eventsub::twitch_eventsub(eventsub_config, eventsub_tx, eventsub_rx).await;
+    });
+
     tokio::task::spawn(async move {
         twitch::twitch_irc(config, twitch_tx, twitch_rx).await;
     });
@@ -1,10 +1,17 @@
 use log::{debug, info, warn};
-use std::time::Duration;
-use tokio::sync::{broadcast::Sender, mpsc::Receiver};
+use std::time::{Duration, Instant};
+use tokio::sync::{
+    broadcast::Sender,
+    mpsc::Receiver,
+    oneshot::{error::TryRecvError, Receiver as OSReceiver},
+};
 
 use crate::{
     commands::{init_terminal, quit_terminal, reset_terminal},
-    emotes::{display_emote, query_emotes, ApplyCommand, DecodedEmote},
+    emotes::{
+        display_emote, query_badges, query_cheermotes, query_emotes, ApplyCommand, DecodedEmote,
+        DownloadedEmotes,
+    },
     handlers::{
         app::App,
         config::CompleteConfig,
@@ -12,10 +19,20 @@ use crate::{
         state::State,
         user_input::events::{Config, Events, Key},
     },
-    twitch::{oauth::get_twitch_client_id, TwitchAction},
-    utils::emotes::emotes_enabled,
+    twitch::{
+        channel_event::ChannelEvent,
+        cheermotes::cheermote_emotes,
+        polls::{can_read_votes, query_votes},
+        stream_info::query_stream_status,
+        TwitchAction,
+    },
+    utils::emotes::{badge_images_enabled, emotes_enabled},
 };
 
+/// How often the poll and prediction of the own channel are checked,
+/// catching the ones that started before `EventSub` was connected.
+const VOTES_INTERVAL: Duration = Duration::from_secs(30);
+
 pub enum TerminalAction {
     Quit,
     BackOneLayer,
@@ -47,25 +64,144 @@ pub async fn ui_driver(
         tick_rate: Duration::from_millis(config.terminal.delay),
     });
 
-    let mut erx = query_emotes(&config, config.twitch.channel.clone());
+    if let Some(channel) = app.channels.borrow_mut().active_mut() {
+        channel.emotes_rx = Some(query_emotes(&config, channel.name.clone()));
+        channel.badges_rx = Some(query_badges(&config, channel.name.clone()));
+        channel.cheermotes_rx = Some(query_cheermotes(&config, channel.name.clone()));
+    }
 
     let mut terminal = init_terminal(&config.frontend);
 
     terminal.clear().unwrap();
 
     let is_emotes_enabled = emotes_enabled(&config.frontend);
+    let is_badge_images_enabled = badge_images_enabled(&config.frontend);
+
+    // Anonymous users cannot make requests to the Helix API.
+    let stream_info_interval = (!config.twitch.anonymous)
+        .then(|| Duration::from_secs(config.frontend.stream_info_interval))
+        .filter(|interval| !interval.is_zero());
+
+    // Helix only shows the polls and predictions of the channel that the token belongs to.
+    let is_votes_enabled = can_read_votes(&config.twitch);
 
     loop {
+        if let Some(interval) = stream_info_interval {
+            // Check if the channels shown in chat are live every so often
+            for channel in app.channels.borrow_mut().iter_mut() {
+                match channel.stream_status_rx.as_mut().map(OSReceiver::try_recv) {
+                    Some(Ok(status)) => {
+                        channel.stream_status = status;
+                        channel.stream_status_rx = None;
+                    }
+                    Some(Err(TryRecvError::Closed)) => channel.stream_status_rx = None,
+                    Some(Err(TryRecvError::Empty)) | None => {}
+                }
+
+                let now = Instant::now();
+
+                if app.components.chat.shows_channel(&channel.name)
+                    && channel.stream_status_due(now, interval)
+                {
+                    channel.stream_checked = Some(now);
+                    channel.stream_status_rx =
+                        Some(query_stream_status(&config.twitch, channel.name.clone()));
+                }
+            }
+        }
+
+        if is_votes_enabled {
+            // Check the poll and prediction of the own channel every so often
+            for channel in app.channels.borrow_mut().iter_mut() {
+                match channel.votes_rx.as_mut().map(OSReceiver::try_recv) {
+                    Some(Ok(votes)) => {
+                        channel.update_votes(votes);
+                        channel.votes_rx = None;
+                    }
+                    Some(Err(TryRecvError::Closed)) => channel.votes_rx = None,
+                    Some(Err(TryRecvError::Empty)) | None => {}
+                }
+
+                let now = Instant::now();
+
+                if channel.name.eq_ignore_ascii_case(&config.twitch.username)
+                    && app.components.chat.shows_channel(&channel.name)
+                    && channel.votes_due(now, VOTES_INTERVAL)
+                {
+                    channel.votes_checked = Some(now);
+                    channel.votes_rx = Some(query_votes(&config.twitch, channel.name.clone()));
+                }
+            }
+        }
+
+        if is_badge_images_enabled {
+            // Check if we have received any badges
+            for channel in app.channels.borrow_mut().iter_mut() {
+                let Some(Ok(badges)) = channel.badges_rx.as_mut().map(OSReceiver::try_recv) else {
+                    continue;
+                };
+
+                channel.badges_rx = None;
+                channel.badges = badges;
+
+                for message in &mut *channel.messages.borrow_mut() {
+                    message.load_badge_images(&app.emotes, &channel.badges);
+                }
+            }
+        }
+
         if is_emotes_enabled {
             // Check if we have received any emotes
-            if let Ok((user_emotes, global_emotes)) = erx.try_recv() {
-                *app.emotes.user_emotes.borrow_mut() = user_emotes;
-                *app.emotes.global_emotes.borrow_mut() = global_emotes;
+            let mut channels = app.channels.borrow_mut();
+            let active_index = channels.active_index();
 
-                for message in &mut *app.messages.borrow_mut() {
-                    message.reparse_emotes(&app.emotes, is_emotes_enabled);
+            for (index, channel) in channels.iter_mut().enumerate() {
+                let Some(Ok((user_emotes, global_emotes))) =
+                    channel.emotes_rx.as_mut().map(OSReceiver::try_recv)
+                else {
+                    continue;
+                };
+
+                channel.emotes_rx = None;
+                channel.user_emotes = user_emotes;
+                channel.global_emotes = global_emotes;
+
+                for message in &mut *channel.messages.borrow_mut() {
+                    message.reparse_emotes(&app.emotes, &channel.global_emotes, is_emotes_enabled);
                 }
-            };
+
+                if index == active_index {
+                    app.emotes
+                        .user_emotes
+                        .borrow_mut()
+                        .clone_from(&channel.user_emotes);
+                    app.emotes
+                        .global_emotes
+                        .borrow_mut()
+                        .clone_from(&channel.global_emotes);
+                }
+            }
+
+            // Check if we have received any cheermotes
+            for channel in channels.iter_mut() {
+                let Some(Ok(cheermotes)) = channel.cheermotes_rx.as_mut().map(OSReceiver::try_recv)
+                else {
+                    continue;
+                };
+
+                channel.cheermotes_rx = None;
+                channel.cheermotes = cheermotes;
+
+                for message in &mut *channel.messages.borrow_mut() {
+                    if message.bits.is_some() {
+                        let cheermotes = cheermote_emotes(&message.payload, &channel.cheermotes);
+
+                        message.reparse_emotes(&app.emotes, &cheermotes, is_emotes_enabled);
+                    }
+                }
+            }
+
+            drop(channels);
 
             // Check if we need to load a decoded emote
             if let Some(rx) = &mut drx {
@@ -83,6 +219,11 @@ pub async fn ui_driver(
                             app.emotes.user_emotes.borrow_mut().remove(&name);
                             app.emotes.global_emotes.borrow_mut().remove(&name);
                             app.emotes.info.borrow_mut().remove(&name);
+
+                            for channel in app.channels.borrow_mut().iter_mut() {
+                                channel.user_emotes.remove(&name);
+                                channel.global_emotes.remove(&name);
+                            }
                         }
                     }
                 }
@@ -92,28 +233,188 @@ pub async fn ui_driver(
         if let Ok(msg) = rx.try_recv() {
             match msg {
                 TwitchToTerminalAction::Message(m) => {
-                    app.messages
-                        .borrow_mut()
-                        .push_front(MessageData::from_twitch_message(
+                    let mut channels = app.channels.borrow_mut();
+
+                    let active_name = channels.active().map(|c| c.name.clone());
+
+                    // Messages without a channel are meant for the channel currently being displayed.
+                    let channel = m
+                        .channel
+                        .as_ref()
+                        .or(active_name.as_ref())
+                        .and_then(|name| channels.get_mut(name));
+
+                    if let Some(channel) = channel {
+                        let is_active = active_name.is_some_and(|active| active == channel.name);
+
+                        let mut m = *m;
+
+                        // Cheermotes are displayed the same way as the emotes of the message.
+                        if is_emotes_enabled && m.bits.is_some() {
+                            m.emotes
+                                .extend(cheermote_emotes(&m.payload, &channel.cheermotes));
+                        }
+
+                        let mut message_data = MessageData::from_twitch_message(
                             m,
                             &app.emotes,
+                            &channel.global_emotes,
                             is_emotes_enabled,
-                        ));
+                        );
+
+                        if is_badge_images_enabled {
+                            message_data.load_badge_images(&app.emotes, &channel.badges);
+                        }
+
+                        if !is_active {
+                            // Keep the scroll position of channels that are not being scrolled right now.
+                            if channel.scroll_offset > 0 {
+                                channel.scroll_offset += 1;
+                            }
+
+                            if !message_data.system
+                                && !app.components.chat.shows_channel(&channel.name)
+                            {
+                                channel.unread += 1;
+
+                                if message_data.mentions(&app.config.borrow().twitch.username) {
+                                    channel.mentions += 1;
+                                }
+                            }
+                        }
+
+                        channel.push_message(message_data);
 
-                    // If scrolling is enabled, pad for more messages.
-                    if app.components.chat.scroll_offset.get_offset() > 0 {
-                        app.components.chat.scroll_offset.up();
+                        // If scrolling is enabled, pad for more messages.
+                        if is_active && app.components.chat.scroll_offset.get_offset() > 0 {
+                            app.components.chat.scroll_offset.up();
+                        }
                     }
                 }
-                TwitchToTerminalAction::ClearChat(user_id) => {
-                    if let Some(user) = user_id {
-                        app.purge_user_messages(user.as_str());
-                    } else {
-                        app.clear_messages();
+                TwitchToTerminalAction::Whisper(m) => {
+                    let user = m.author.clone();
+
+                    let message_data = MessageData::from_twitch_message(
+                        *m,
+                        &app.emotes,
+                        &DownloadedEmotes::default(),
+                        is_emotes_enabled,
+                    );
+
+                    let unread = !app.components.chat.shows_whisper_conversation(&user);
+
+                    app.whispers.borrow_mut().push(&user, message_data, unread);
+                }
+                TwitchToTerminalAction::ClearChat(channel, clear_chat) => {
+                    let (purged, name) = clear_chat.user_id.as_ref().map_or_else(
+                        || {
+                            if let Some(channel) = app.channels.borrow().get(&channel) {
+                                channel.messages.borrow_mut().clear();
+                            }
+
+                            (0, None)
+                        },
+                        |user_id| app.purge_user_messages(&channel, user_id),
+                    );
+
+                    let notice = MessageData::new_user_message(
+                        "Twitch".to_string(),
+                        None,
+                        true,
+                        clear_chat.notice(name.as_deref(), purged),
+                        None,
+                        false,
+                        None,
+                        &app.emotes,
+                        false,
+                    );
+
+                    if let Some(channel) = app.channels.borrow().get(&channel) {
+                        channel.push_message(notice);
                     }
                 }
-                TwitchToTerminalAction::DeleteMessage(message_id) => {
-                    app.remove_message_with(message_id.as_str());
+                TwitchToTerminalAction::DeleteMessage(channel, message_id) => {
+                    app.remove_message_with(&channel, message_id.as_str());
+                }
+                TwitchToTerminalAction::Notice(channel, notice) => {
+                    let mut channels = app.channels.borrow_mut();
+
+                    let channel = match channel {
+                        Some(name) => channels.get_mut(&name),
+                        None => channels.active_mut(),
+                    };
+
+                    if let Some(channel) = channel {
+                        // Rejected messages are marked as such, instead of the notice being shown on its own.
+                        let marked =
+                            notice.kind.reason().is_some() && channel.mark_failed(&notice.kind);
+
+                        if !marked {
+                            channel.push_message(MessageData::new_user_message(
+                                "Twitch".to_string(),
+                                None,
+                                true,
+                                notice.message,
+                                None,
+                                false,
+                                None,
+                                &app.emotes,
+                                false,
+                            ));
+                        }
+                    }
+                }
+                TwitchToTerminalAction::RoomState(channel, room_state) => {
+                    if let Some(channel) = app.channels.borrow_mut().get_mut(&channel) {
+                        channel.room_state = room_state;
+                    }
+                }
+                TwitchToTerminalAction::UserState(None, user_state) => {
+                    app.user_state = user_state;
+                }
+                TwitchToTerminalAction::UserState(Some(channel), user_state) => {
+                    if let Some(channel) = app.channels.borrow_mut().get_mut(&channel) {
+                        channel.user_state = Some(user_state);
+                    }
+                }
+                TwitchToTerminalAction::OutgoingQueue(queue_status) => {
+                    app.components.chat.set_queue_status(queue_status);
+                }
+                TwitchToTerminalAction::MessageSent(channel) => {
+                    if let Some(channel) = app.channels.borrow_mut().get_mut(&channel) {
+                        channel.mark_sent(Instant::now());
+                    }
+                }
+                TwitchToTerminalAction::MessageConfirmed(channel, message_id) => {
+                    if let Some(channel) = app.channels.borrow_mut().get_mut(&channel) {
+                        channel.confirm_sent(message_id);
+                    }
+                }
+                TwitchToTerminalAction::ChannelEvent(channel, event) => {
+                    if let Some(channel) = app.channels.borrow_mut().get_mut(&channel) {
+                        if let Some(message) = event.message() {
+                            channel.push_message(MessageData::new_user_message(
+                                "Twitch".to_string(),
+                                None,
+                                true,
+                                message,
+                                None,
+                                false,
+                                None,
+                                &app.emotes,
+                                false,
+                            ));
+                        }
+
+                        // Polls and predictions are also shown above chat as they go on.
+                        match event {
+                            ChannelEvent::Poll(poll, _) => channel.update_poll(poll),
+                            ChannelEvent::Prediction(prediction, _) => {
+                                channel.update_prediction(prediction);
+                            }
+                            _ => {}
+                        }
+                    }
                 }
             }
         }
@@ -148,7 +449,8 @@ pub async fn ui_driver(
                         tx.send(TwitchAction::ClearMessages).unwrap();
                     }
                     TerminalAction::Enter(action) => match action {
-                        TwitchAction::Privmsg(message) => {
+                        TwitchAction::Privmsg(ref message)
+                        | TwitchAction::Reply(ref message, _) => {
                             const ME_COMMAND: &str = "/me ";
 
                             let (msg, highlight) = message.strip_prefix(ME_COMMAND).map_or_else(
@@ -156,34 +458,75 @@ pub async fn ui_driver(
                                 |msg| (msg.to_string(), true),
                             );
 
-                            let user_id = get_twitch_client_id(config.twitch.token.as_deref())
-                                .await
-                                .map(|x| x.user_id.clone())
-                                .ok();
+                            let reply_parent = match action {
+                                TwitchAction::Reply(_, ref reply_parent) => {
+                                    Some(reply_parent.clone())
+                                }
+                                _ => None,
+                            };
+
+                            let mut message_data =
+                                app.own_message(msg, highlight, reply_parent, is_emotes_enabled);
+
+                            if let Some(channel) = app.channels.borrow_mut().active_mut() {
+                                if is_badge_images_enabled {
+                                    message_data.load_badge_images(&app.emotes, &channel.badges);
+                                }
+
+                                channel.push_outgoing(message_data, message.clone());
+                            }
+
+                            tx.send(action.clone()).unwrap();
+                        }
+                        TwitchAction::Join(channel) => {
+                            // Already joined channels keep their messages and emotes,
+                            // only new channels need to have their emotes downloaded.
+                            if app.channels.borrow_mut().join(&channel) {
+                                if let Some(c) = app.channels.borrow_mut().get_mut(&channel) {
+                                    c.emotes_rx = Some(query_emotes(&config, channel.clone()));
+                                    c.badges_rx = Some(query_badges(&config, channel.clone()));
+                                    c.cheermotes_rx =
+                                        Some(query_cheermotes(&config, channel.clone()));
+                                }
+                            }
+
+                            app.set_active_channel(&channel);
+
+                            tx.send(TwitchAction::Join(channel)).unwrap();
+
+                            app.set_state(State::Normal);
+                        }
+                        TwitchAction::Part(channel) => {
+                            tx.send(TwitchAction::Part(channel.clone())).unwrap();
 
+                            // Messages are now sent to the channel being displayed instead.
+                            if let Some(active) = app.part_channel(&channel) {
+                                tx.send(TwitchAction::Join(active)).unwrap();
+                            } else {
+                                app.set_state(State::Dashboard);
+                            }
+                        }
+                        TwitchAction::Whisper(ref recipient, ref message) => {
                             let message_data = MessageData::new_user_message(
-                                config.twitch.username.to_string(),
-                                user_id,
+                                config.twitch.username.clone(),
+                                None,
+                                false,
+                                message.clone(),
+                                None,
                                 false,
-                                msg,
                                 None,
-                                highlight,
                                 &app.emotes,
                                 is_emotes_enabled,
                             );
 
-                            app.messages.borrow_mut().push_front(message_data);
+                            app.whispers
+                                .borrow_mut()
+                                .push(recipient, message_data, false);
 
-                            tx.send(TwitchAction::Privmsg(message)).unwrap();
+                            tx.send(action.clone()).unwrap();
                         }
-                        TwitchAction::Join(channel) => {
-                            app.clear_messages();
-                            app.emotes.unload();
-
-                            tx.send(TwitchAction::Join(channel.clone())).unwrap();
-                            erx = query_emotes(&config, channel);
-
-                            app.set_state(State::Normal);
+                        TwitchAction::Moderate(_) | TwitchAction::Vote(_) => {
+                            tx.send(action.clone()).unwrap();
                         }
                         TwitchAction::ClearMessages => {}
                     },
@@ -1,77 +1,131 @@
-use irc::proto::Message;
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::BuildHasher,
+};
 
-const VIP_BADGE: char = '\u{1F48E}';
-const MODERATOR_BADGE: char = '\u{1F528}';
-const SUBSCRIBER_BADGE: char = '\u{2B50}';
-const PRIME_GAMING_BADGE: char = '\u{1F451}';
+/// Glyphs shown for badges that have not been given one in the config.
+const DEFAULT_GLYPHS: [(&str, &str); 12] = [
+    ("broadcaster", "\u{1F3A5}"),
+    ("staff", "\u{1F527}"),
+    ("admin", "\u{1F6E1}"),
+    ("global_mod", "\u{1F6E1}"),
+    ("moderator", "\u{1F528}"),
+    ("vip", "\u{1F48E}"),
+    ("partner", "\u{2705}"),
+    ("founder", "\u{1F3C5}"),
+    ("subscriber", "\u{2B50}"),
+    ("bits", "\u{25C6}"),
+    ("sub-gifter", "\u{1F381}"),
+    ("premium", "\u{1F451}"),
+];
 
-pub fn retrieve_user_badges(name: &mut String, message: &Message, badges_enabled: bool) {
-    let mut badges = String::new();
+/// A chat badge, parsed from the `badges` and `badge-info` tags.
+/// <https://dev.twitch.tv/docs/irc/tags/#privmsg-tags>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Badge {
+    /// Name of the badge set, such as `subscriber` or `bits`.
+    pub name: String,
+    /// Version of the badge, such as the subscription tier or the amount of bits cheered.
+    pub version: String,
+    /// More precise information, such as the exact amount of months subscribed for.
+    pub info: Option<String>,
+}
 
-    if let Some(ref tags) = message.tags {
-        let mut vip_badge = None;
-        let mut moderator_badge = None;
-        let mut subscriber_badge = None;
-        let mut prime_badge = None;
-        let mut display_name = None;
+impl Badge {
+    /// Badges in the order that Twitch sends them in, being `name/version` pairs separated by commas.
+    /// The `badge-info` tag uses the same format, but only for badges that have more information.
+    pub fn parse(badges: &str, badge_info: &str) -> Vec<Self> {
+        let info = badge_info
+            .split(',')
+            .filter_map(|badge| badge.split_once('/'))
+            .collect::<HashMap<&str, &str>>();
 
-        for tag in tags {
-            if tag.0 == *"display-name" {
-                if let Some(ref value) = tag.1 {
-                    display_name = Some(value.to_string());
+        badges
+            .split(',')
+            .filter_map(|badge| badge.split_once('/'))
+            .map(|(name, version)| Self {
+                name: name.to_string(),
+                version: version.to_string(),
+                info: info.get(name).map(ToString::to_string),
+            })
+            .collect()
+    }
 
-                    // Assuming when found, that there is only one instance of
-                    // the 'display-name' tag.
-                    if !badges_enabled {
-                        break;
-                    }
-                }
-            }
+    pub fn from_tags<S: BuildHasher>(tags: &HashMap<&str, &str, S>) -> Vec<Self> {
+        Self::parse(
+            tags.get("badges").copied().unwrap_or_default(),
+            tags.get("badge-info").copied().unwrap_or_default(),
+        )
+    }
 
-            if tag.0 == *"badges" {
-                if let Some(ref value) = tag.1 {
-                    if !value.is_empty() && value.contains("vip") {
-                        vip_badge = Some(VIP_BADGE);
-                    }
-                    if !value.is_empty() && value.contains("moderator") {
-                        moderator_badge = Some(MODERATOR_BADGE);
-                    }
-                    if !value.is_empty() && value.contains("subscriber") {
-                        subscriber_badge = Some(SUBSCRIBER_BADGE);
-                    }
-                    if !value.is_empty() && value.contains("premium") {
-                        prime_badge = Some(PRIME_GAMING_BADGE);
-                    }
-                }
-            }
-        }
+    /// The glyph configured for this badge, falling back to the default one.
+    /// `{version}` and `{info}` within the glyph are replaced by the details of the badge.
+    pub fn glyph(&self, glyphs: &BTreeMap<String, String>) -> Option<String> {
+        let glyph = glyphs.get(&self.name).map(String::as_str).or_else(|| {
+            DEFAULT_GLYPHS
+                .iter()
+                .find(|(name, _)| *name == self.name)
+                .map(|(_, glyph)| *glyph)
+        })?;
 
-        if let Some(display_name) = display_name {
-            *name = display_name;
+        Some(
+            glyph
+                .replace("{version}", &self.version)
+                .replace("{info}", self.info.as_deref().unwrap_or(&self.version)),
+        )
+    }
+}
 
-            if !badges_enabled {
-                return;
-            }
-        }
+/// Glyphs of every badge that has one, to be shown in front of the user's name.
+pub fn badge_glyphs(badges: &[Badge], glyphs: &BTreeMap<String, String>) -> String {
+    badges
+        .iter()
+        .filter_map(|badge| badge.glyph(glyphs))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if let Some(badge) = vip_badge {
-            badges.push(badge);
-        }
+    #[test]
+    fn test_parse_badges_with_info() {
+        let badges = Badge::parse("broadcaster/1,subscriber/3012,bits/1000", "subscriber/22");
+
+        assert_eq!(
+            badges,
+            [
+                Badge {
+                    name: "broadcaster".to_string(),
+                    version: "1".to_string(),
+                    info: None,
+                },
+                Badge {
+                    name: "subscriber".to_string(),
+                    version: "3012".to_string(),
+                    info: Some("22".to_string()),
+                },
+                Badge {
+                    name: "bits".to_string(),
+                    version: "1000".to_string(),
+                    info: None,
+                },
+            ]
+        );
+        assert!(Badge::parse("", "").is_empty());
+    }
 
-        if let Some(badge) = moderator_badge {
-            badges.push(badge);
-        }
+    #[test]
+    fn test_badge_glyphs() {
+        let badges = Badge::parse("moderator/1,subscriber/12,glhf-pledge/1", "subscriber/14");
 
-        if let Some(badge) = subscriber_badge {
-            badges.push(badge);
-        }
+        assert_eq!(badge_glyphs(&badges, &BTreeMap::new()), "\u{1F528}\u{2B50}");
 
-        if let Some(badge) = prime_badge {
-            badges.push(badge);
-        }
+        let glyphs = BTreeMap::from([
+            ("moderator".to_string(), String::new()),
+            ("subscriber".to_string(), "[{info}]".to_string()),
+        ]);
 
-        if !badges.is_empty() {
-            *name = badges.clone() + name;
-        }
+        assert_eq!(badge_glyphs(&badges, &glyphs), "[14]");
     }
 }
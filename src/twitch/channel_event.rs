@@ -0,0 +1,416 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use serde_with::{serde_as, DisplayFromStr, PickFirst};
+
+/// Which part of a poll, prediction or hype train an event is about, from the end of its subscription type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Begin,
+    Progress,
+    /// Predictions stop taking new predictions before they end.
+    Lock,
+    End,
+}
+
+impl Phase {
+    fn from_subscription_type(subscription_type: &str) -> Option<Self> {
+        Some(match subscription_type.rsplit('.').next()? {
+            "begin" => Self::Begin,
+            "progress" => Self::Progress,
+            "lock" => Self::Lock,
+            "end" => Self::End,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PollChoice {
+    pub title: String,
+    /// Votes for the choice, which are not sent when the poll begins.
+    #[serde(default)]
+    pub votes: u64,
+}
+
+/// A poll running in a channel, as sent through `EventSub` or Helix.
+/// <https://dev.twitch.tv/docs/eventsub/eventsub-reference/#channel-poll-begin-event>
+#[serde_as]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Poll {
+    pub id: String,
+    pub title: String,
+    pub choices: Vec<PollChoice>,
+    /// When voting ends, which Helix does not send.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    /// When the poll ended, once it has.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl Poll {
+    /// The choice with the most votes, if anyone voted.
+    pub fn winner(&self) -> Option<&PollChoice> {
+        self.choices
+            .iter()
+            .filter(|choice| choice.votes > 0)
+            .max_by_key(|choice| choice.votes)
+    }
+
+    pub const fn ended(&self) -> bool {
+        self.ended_at.is_some()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PredictionOutcome {
+    pub id: String,
+    pub title: String,
+    /// Either `blue` or `pink`, in any case.
+    pub color: String,
+    /// Amount of users that predicted the outcome, which is not sent when the prediction begins.
+    #[serde(default)]
+    pub users: u64,
+    #[serde(default)]
+    pub channel_points: u64,
+}
+
+/// A prediction running in a channel, as sent through `EventSub` or Helix.
+/// <https://dev.twitch.tv/docs/eventsub/eventsub-reference/#channel-prediction-begin-event>
+#[serde_as]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Prediction {
+    pub id: String,
+    pub title: String,
+    pub outcomes: Vec<PredictionOutcome>,
+    /// When predictions are no longer taken, which Helix does not send.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub locks_at: Option<DateTime<Utc>>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub locked_at: Option<DateTime<Utc>>,
+    /// When the prediction was resolved or canceled, once it has been.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+    /// The outcome that won, once the prediction has been resolved.
+    pub winning_outcome_id: Option<String>,
+}
+
+impl Prediction {
+    pub fn winner(&self) -> Option<&PredictionOutcome> {
+        let winning_outcome_id = self.winning_outcome_id.as_ref()?;
+
+        self.outcomes
+            .iter()
+            .find(|outcome| &outcome.id == winning_outcome_id)
+    }
+
+    pub const fn ended(&self) -> bool {
+        self.ended_at.is_some()
+    }
+
+    /// If predictions are no longer taken, which is the case once the prediction has ended.
+    pub const fn locked(&self) -> bool {
+        self.locked_at.is_some() || self.ended()
+    }
+}
+
+/// A hype train going on in a channel.
+/// <https://dev.twitch.tv/docs/eventsub/eventsub-reference/#hype-train-begin-event>
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HypeTrain {
+    pub level: u64,
+}
+
+#[derive(Deserialize)]
+struct Reward {
+    title: String,
+    cost: u64,
+}
+
+#[derive(Deserialize)]
+struct Redemption {
+    user_name: String,
+    #[serde(default)]
+    user_input: String,
+    reward: Reward,
+}
+
+#[derive(Deserialize)]
+struct Follow {
+    user_name: String,
+}
+
+#[derive(Deserialize)]
+struct ShoutoutCreate {
+    to_broadcaster_user_name: String,
+    viewer_count: u64,
+}
+
+#[derive(Deserialize)]
+struct ShoutoutReceive {
+    from_broadcaster_user_name: String,
+    viewer_count: u64,
+}
+
+/// Twitch sends the fields of ad breaks as strings in some places and as values in others.
+#[serde_as]
+#[derive(Deserialize)]
+struct AdBreak {
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    duration_seconds: u64,
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    is_automatic: bool,
+}
+
+#[derive(Deserialize)]
+struct Broadcaster {
+    broadcaster_user_login: String,
+}
+
+/// Something that happened in a channel that is not sent through IRC, received through `EventSub`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelEvent {
+    /// A channel point reward was redeemed by a user, along with the text they entered.
+    Redemption {
+        user: String,
+        reward: String,
+        cost: u64,
+        input: String,
+    },
+    Follow(String),
+    Poll(Poll, Phase),
+    Prediction(Prediction, Phase),
+    HypeTrain(HypeTrain, Phase),
+    /// The channel gave a shoutout to another channel, in front of this many viewers.
+    ShoutoutSent(String, u64),
+    /// Another channel gave this channel a shoutout, in front of this many viewers.
+    ShoutoutReceived(String, u64),
+    /// An ad break started, lasting this many seconds, and if it was started automatically.
+    AdBreak(u64, bool),
+}
+
+fn parse<T: DeserializeOwned>(event: &Value) -> Result<T> {
+    Ok(T::deserialize(event)?)
+}
+
+impl ChannelEvent {
+    /// The channel a notification was sent for along with its event,
+    /// or `None` if the subscription type is not one that is handled.
+    pub fn from_notification(
+        subscription_type: &str,
+        event: &Value,
+    ) -> Result<Option<(String, Self)>> {
+        let phase = Phase::from_subscription_type(subscription_type);
+
+        let channel_event = match subscription_type {
+            "channel.channel_points_custom_reward_redemption.add" => {
+                let redemption = parse::<Redemption>(event)?;
+
+                Self::Redemption {
+                    user: redemption.user_name,
+                    reward: redemption.reward.title,
+                    cost: redemption.reward.cost,
+                    input: redemption.user_input,
+                }
+            }
+            "channel.follow" => Self::Follow(parse::<Follow>(event)?.user_name),
+            "channel.poll.begin" | "channel.poll.progress" | "channel.poll.end" => {
+                Self::Poll(parse(event)?, phase.unwrap_or(Phase::Progress))
+            }
+            "channel.prediction.begin"
+            | "channel.prediction.progress"
+            | "channel.prediction.lock"
+            | "channel.prediction.end" => {
+                Self::Prediction(parse(event)?, phase.unwrap_or(Phase::Progress))
+            }
+            "channel.hype_train.begin" | "channel.hype_train.end" => {
+                Self::HypeTrain(parse(event)?, phase.unwrap_or(Phase::Progress))
+            }
+            "channel.shoutout.create" => {
+                let shoutout = parse::<ShoutoutCreate>(event)?;
+
+                Self::ShoutoutSent(shoutout.to_broadcaster_user_name, shoutout.viewer_count)
+            }
+            "channel.shoutout.receive" => {
+                let shoutout = parse::<ShoutoutReceive>(event)?;
+
+                Self::ShoutoutReceived(shoutout.from_broadcaster_user_name, shoutout.viewer_count)
+            }
+            "channel.ad_break.begin" => {
+                let ad_break = parse::<AdBreak>(event)?;
+
+                Self::AdBreak(ad_break.duration_seconds, ad_break.is_automatic)
+            }
+            _ => return Ok(None),
+        };
+
+        let channel = parse::<Broadcaster>(event)?.broadcaster_user_login;
+
+        Ok(Some((channel, channel_event)))
+    }
+
+    /// What to show in chat for the event, or `None` if it only updates something already shown.
+    pub fn message(&self) -> Option<String> {
+        Some(match self {
+            Self::Redemption {
+                user,
+                reward,
+                cost,
+                input,
+            } if input.is_empty() => format!("{user} redeemed {reward} ({cost} points)."),
+            Self::Redemption {
+                user,
+                reward,
+                cost,
+                input,
+            } => format!("{user} redeemed {reward} ({cost} points): {input}"),
+            Self::Follow(user) => format!("{user} is now following the channel."),
+            Self::Poll(poll, Phase::Begin) => format!("Poll started: {}", poll.title),
+            Self::Poll(poll, Phase::End) => poll.winner().map_or_else(
+                || format!("Poll ended: {}", poll.title),
+                |winner| {
+                    format!(
+                        "Poll ended: {} - {} won with {} votes.",
+                        poll.title, winner.title, winner.votes
+                    )
+                },
+            ),
+            Self::Prediction(prediction, Phase::Begin) => {
+                format!("Prediction started: {}", prediction.title)
+            }
+            Self::Prediction(prediction, Phase::Lock) => {
+                format!("Predictions are locked: {}", prediction.title)
+            }
+            Self::Prediction(prediction, Phase::End) => prediction.winner().map_or_else(
+                || format!("Prediction canceled: {}", prediction.title),
+                |winner| {
+                    format!(
+                        "Prediction ended: {} - {} won.",
+                        prediction.title, winner.title
+                    )
+                },
+            ),
+            Self::HypeTrain(_, Phase::Begin) => "A hype train has started!".to_string(),
+            Self::HypeTrain(hype_train, Phase::End) => {
+                format!("The hype train ended at level {}.", hype_train.level)
+            }
+            Self::ShoutoutSent(channel, viewers) => {
+                format!("Gave {channel} a shoutout in front of {viewers} viewers.")
+            }
+            Self::ShoutoutReceived(channel, viewers) => {
+                format!("{channel} gave the channel a shoutout in front of {viewers} viewers.")
+            }
+            Self::AdBreak(seconds, automatic) => format!(
+                "{} ad break of {} started.",
+                if *automatic { "An automatic" } else { "An" },
+                Duration(*seconds)
+            ),
+            Self::Poll(..) | Self::Prediction(..) | Self::HypeTrain(..) => return None,
+        })
+    }
+}
+
+/// A length of time in seconds, shown such as `1m 30s`.
+struct Duration(u64);
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.0 / 60, self.0 % 60) {
+            (0, seconds) => write!(f, "{seconds}s"),
+            (minutes, 0) => write!(f, "{minutes}m"),
+            (minutes, seconds) => write!(f, "{minutes}m {seconds}s"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(subscription_type: &str, event: &str) -> (String, ChannelEvent) {
+        let event = serde_json::from_str::<Value>(event).unwrap();
+
+        ChannelEvent::from_notification(subscription_type, &event)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_redemption() {
+        let (channel, event) = notification(
+            "channel.channel_points_custom_reward_redemption.add",
+            r#"{"id":"17fa2df1-ad76-4804-bfa5-a40ef63efe63","broadcaster_user_id":"1337","broadcaster_user_login":"cool_user","broadcaster_user_name":"Cool_User","user_id":"9001","user_login":"cooler_user","user_name":"Cooler_User","user_input":"pogchamp","status":"unfulfilled","reward":{"id":"92af127c-7326-4483-a52b-b0da0be61c01","title":"title","cost":100,"prompt":"reward prompt"},"redeemed_at":"2020-07-15T17:16:03.17106713Z"}"#,
+        );
+
+        assert_eq!(channel, "cool_user");
+        assert_eq!(
+            event.message().unwrap(),
+            "Cooler_User redeemed title (100 points): pogchamp"
+        );
+    }
+
+    #[test]
+    fn test_poll_end() {
+        let (_, event) = notification(
+            "channel.poll.end",
+            r#"{"id":"1243456","broadcaster_user_id":"1337","broadcaster_user_login":"cool_user","broadcaster_user_name":"Cool_User","title":"Aren't shoes just really hard socks?","choices":[{"id":"123","title":"Blue","bits_votes":50,"channel_points_votes":70,"votes":120},{"id":"124","title":"Yellow","bits_votes":100,"channel_points_votes":40,"votes":140},{"id":"125","title":"Green","bits_votes":10,"channel_points_votes":70,"votes":80}],"bits_voting":{"is_enabled":true,"amount_per_vote":10},"channel_points_voting":{"is_enabled":true,"amount_per_vote":10},"status":"completed","started_at":"2020-07-15T17:16:03.17106713Z","ended_at":"2020-07-15T17:16:11.17106713Z"}"#,
+        );
+
+        let ChannelEvent::Poll(ref poll, Phase::End) = event else {
+            panic!("Event should be the end of a poll");
+        };
+
+        assert_eq!(poll.winner().unwrap().title, "Yellow");
+        assert!(poll.ended());
+        assert_eq!(
+            event.message().unwrap(),
+            "Poll ended: Aren't shoes just really hard socks? - Yellow won with 140 votes."
+        );
+    }
+
+    #[test]
+    fn test_prediction_progress() {
+        let (_, event) = notification(
+            "channel.prediction.progress",
+            r#"{"id":"1243456","broadcaster_user_id":"1337","broadcaster_user_login":"cool_user","broadcaster_user_name":"Cool_User","title":"Aren't shoes just really hard socks?","outcomes":[{"id":"1243456","title":"Yeah!","color":"blue","users":10,"channel_points":15000,"top_predictors":[]},{"id":"2243456","title":"No!","color":"pink","users":3,"channel_points":4000,"top_predictors":[]}],"started_at":"2020-07-15T17:16:03.17106713Z","locks_at":"2020-07-15T17:21:03.17106713Z"}"#,
+        );
+
+        let ChannelEvent::Prediction(ref prediction, Phase::Progress) = event else {
+            panic!("Event should be the progress of a prediction");
+        };
+
+        assert_eq!(prediction.outcomes[1].title, "No!");
+        assert!(prediction.winner().is_none());
+        assert_eq!(event.message(), None);
+    }
+
+    #[test]
+    fn test_ad_break() {
+        let (_, event) = notification(
+            "channel.ad_break.begin",
+            r#"{"duration_seconds":"90","started_at":"2019-11-16T10:11:12.634234626Z","is_automatic":"false","broadcaster_user_id":"1337","broadcaster_user_login":"cool_user","broadcaster_user_name":"Cool_User","requester_user_id":"1337","requester_user_login":"cool_user","requester_user_name":"Cool_User"}"#,
+        );
+
+        assert_eq!(event, ChannelEvent::AdBreak(90, false));
+        assert_eq!(event.message().unwrap(), "An ad break of 1m 30s started.");
+    }
+
+    #[test]
+    fn test_unknown_subscription_type() {
+        let event =
+            serde_json::from_str::<Value>(r#"{"broadcaster_user_login":"cool_user"}"#).unwrap();
+
+        assert!(ChannelEvent::from_notification("channel.update", &event)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,18 +1,21 @@
-use std::{
-    fmt::Display,
-    string::{String, ToString},
-    vec::Vec,
-};
+use std::{cmp::Reverse, collections::HashMap, fmt::Display, string::String, vec::Vec};
 
-use color_eyre::Result;
+use color_eyre::{eyre::bail, Result};
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 
-use crate::{handlers::config::TwitchConfig, ui::components::utils::SearchItemGetter};
+use crate::{
+    handlers::config::TwitchConfig,
+    ui::components::utils::{SearchItem, SearchItemGetter},
+};
 
-use super::oauth::{get_twitch_client, get_twitch_client_id};
+use super::{
+    oauth::{get_twitch_client, get_twitch_client_id},
+    scopes::Feature,
+};
 
-const FOLLOWER_COUNT: usize = 100;
+/// The most amount of items Twitch sends in a single page.
+const PAGE_SIZE: usize = 100;
 
 #[derive(Deserialize, Debug, Clone, Default)]
 #[allow(dead_code)]
@@ -29,67 +32,242 @@ impl Display for FollowingUser {
     }
 }
 
+/// A live stream of a followed channel.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct FollowedStream {
+    pub user_login: String,
+    pub game_name: String,
+    pub viewer_count: u64,
+}
+
 #[derive(Deserialize, Debug, Clone, Default)]
-#[allow(dead_code)]
 struct Pagination {
     cursor: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Clone, Default)]
-#[allow(dead_code)]
-pub struct FollowingList {
-    pub total: u64,
-    pub data: Vec<FollowingUser>,
+#[derive(Deserialize, Debug, Clone)]
+struct Page<T> {
+    data: Vec<T>,
+    #[serde(default)]
     pagination: Pagination,
 }
 
+/// A followed channel, along with its stream if it is live.
+#[derive(Debug, Clone)]
+pub struct FollowedChannel {
+    pub user: FollowingUser,
+    pub stream: Option<FollowedStream>,
+}
+
+impl Display for FollowedChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.stream {
+            Some(stream) if stream.game_name.is_empty() => write!(
+                f,
+                "\u{25CF} {} ({} viewers)",
+                self.user, stream.viewer_count
+            ),
+            Some(stream) => write!(
+                f,
+                "\u{25CF} {} - {} ({} viewers)",
+                self.user, stream.game_name, stream.viewer_count
+            ),
+            None => write!(f, "  {}", self.user),
+        }
+    }
+}
+
+impl SearchItem for FollowedChannel {
+    fn channel(&self) -> String {
+        self.user.broadcaster_login.clone()
+    }
+}
+
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct Following {
     // TODO: Don't re-create client on new requests
     // client: &Client,
     twitch_config: TwitchConfig,
-    list: FollowingList,
+}
+
+/// Every item of a paginated Helix endpoint, following the cursor until the last page.
+async fn get_all_pages<T: DeserializeOwned>(client: &Client, url: &str) -> Result<Vec<T>> {
+    let mut items = vec![];
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let mut request = client.get(url).query(&[("first", PAGE_SIZE)]);
+
+        if let Some(after) = &cursor {
+            request = request.query(&[("after", after)]);
+        }
+
+        let page = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Page<T>>()
+            .await?;
+
+        items.extend(page.data);
+
+        match page.pagination.cursor {
+            Some(next) if !next.is_empty() && cursor.as_ref() != Some(&next) => {
+                cursor = Some(next);
+            }
+            _ => break,
+        }
+    }
+
+    Ok(items)
 }
 
 // https://dev.twitch.tv/docs/api/reference/#get-followed-channels
-pub async fn get_user_following(client: &Client, user_id: &str) -> Result<FollowingList> {
-    Ok(client
-        .get(format!(
-            "https://api.twitch.tv/helix/channels/followed?user_id={user_id}&first={FOLLOWER_COUNT}",
-        ))
-        .send()
-        .await?
-        .error_for_status()?
-        .json::<FollowingList>()
-        .await?)
+pub async fn get_user_following(
+    client: &Client,
+    api_url: &str,
+    user_id: &str,
+) -> Result<Vec<FollowingUser>> {
+    get_all_pages(
+        client,
+        &format!("{api_url}/channels/followed?user_id={user_id}"),
+    )
+    .await
+}
+
+// https://dev.twitch.tv/docs/api/reference/#get-followed-streams
+pub async fn get_followed_streams(
+    client: &Client,
+    api_url: &str,
+    user_id: &str,
+) -> Result<Vec<FollowedStream>> {
+    get_all_pages(
+        client,
+        &format!("{api_url}/streams/followed?user_id={user_id}"),
+    )
+    .await
+}
+
+/// A channel that the current user is a moderator of.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModeratedChannel {
+    pub broadcaster_id: String,
+}
+
+// https://dev.twitch.tv/docs/api/reference/#get-moderated-channels
+pub async fn get_moderated_channels(
+    client: &Client,
+    api_url: &str,
+    user_id: &str,
+) -> Result<Vec<ModeratedChannel>> {
+    get_all_pages(
+        client,
+        &format!("{api_url}/moderation/channels?user_id={user_id}"),
+    )
+    .await
+}
+
+/// Followed channels with their streams, live channels coming first with the most watched ones at the top.
+/// Offline channels stay in the order they were followed in.
+fn merge_streams(users: Vec<FollowingUser>, streams: Vec<FollowedStream>) -> Vec<FollowedChannel> {
+    let mut streams = streams
+        .into_iter()
+        .map(|stream| (stream.user_login.clone(), stream))
+        .collect::<HashMap<String, FollowedStream>>();
+
+    let mut channels = users
+        .into_iter()
+        .map(|user| FollowedChannel {
+            stream: streams.remove(&user.broadcaster_login),
+            user,
+        })
+        .collect::<Vec<FollowedChannel>>();
+
+    channels
+        .sort_by_key(|channel| Reverse(channel.stream.as_ref().map(|stream| stream.viewer_count)));
+
+    channels
 }
 
-pub async fn get_following(twitch_config: &TwitchConfig) -> Result<FollowingList> {
-    let client = get_twitch_client(twitch_config.token.as_deref()).await?;
-    let user_id = &get_twitch_client_id(None).await?.user_id;
+pub async fn get_following(twitch_config: &TwitchConfig) -> Result<Vec<FollowedChannel>> {
+    let client = get_twitch_client(twitch_config).await?;
+    let user_id = &get_twitch_client_id(twitch_config).await?.user_id;
+
+    let users = get_user_following(&client, &twitch_config.api_url, user_id).await?;
+    let streams = get_followed_streams(&client, &twitch_config.api_url, user_id).await?;
 
-    get_user_following(&client, user_id).await
+    Ok(merge_streams(users, streams))
 }
 
 impl Following {
-    pub fn new(twitch_config: TwitchConfig) -> Self {
-        Self {
-            twitch_config,
-            list: FollowingList::default(),
+    pub const fn new(twitch_config: TwitchConfig) -> Self {
+        Self { twitch_config }
+    }
+}
+
+impl SearchItemGetter<FollowedChannel> for Following {
+    async fn get_items(&mut self) -> Result<Vec<FollowedChannel>> {
+        if !Feature::Following.is_enabled(&self.twitch_config) {
+            bail!("The token is missing the scopes to get followed channels.");
         }
+
+        get_following(&self.twitch_config).await
     }
 }
 
-impl SearchItemGetter<String> for Following {
-    async fn get_items(&mut self) -> Result<Vec<String>> {
-        let following = get_following(&self.twitch_config).await;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(login: &str) -> FollowingUser {
+        FollowingUser {
+            broadcaster_login: login.to_string(),
+            ..FollowingUser::default()
+        }
+    }
+
+    fn stream(login: &str, game_name: &str, viewer_count: u64) -> FollowedStream {
+        FollowedStream {
+            user_login: login.to_string(),
+            game_name: game_name.to_string(),
+            viewer_count,
+        }
+    }
 
-        following.map(|v| {
-            v.data
+    #[test]
+    fn test_live_channels_first() {
+        let channels = merge_streams(
+            vec![user("foo"), user("bar"), user("baz"), user("qux")],
+            vec![stream("baz", "Just Chatting", 10), stream("qux", "", 0)],
+        );
+
+        assert_eq!(
+            channels
                 .iter()
                 .map(ToString::to_string)
-                .collect::<Vec<String>>()
-        })
+                .collect::<Vec<String>>(),
+            [
+                "\u{25CF} baz - Just Chatting (10 viewers)",
+                "\u{25CF} qux (0 viewers)",
+                "  foo",
+                "  bar",
+            ]
+        );
+        assert_eq!(channels[0].channel(), "baz");
+    }
+
+    #[test]
+    fn test_parse_page() {
+        let page = serde_json::from_str::<Page<FollowingUser>>(
+            r#"{"total":2,"data":[{"broadcaster_id":"11111","broadcaster_login":"userloginname","broadcaster_name":"UserDisplayName","followed_at":"2022-05-24T22:22:08Z"}],"pagination":{"cursor":"eyJiIjpudWxsLCJhIjp7Ik9mZnNldCI6NX19"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.data[0].broadcaster_login, "userloginname");
+        assert!(page.pagination.cursor.is_some());
+
+        let page = serde_json::from_str::<Page<FollowedStream>>(r#"{"data":[]}"#).unwrap();
+
+        assert!(page.pagination.cursor.is_none());
     }
 }
@@ -0,0 +1,122 @@
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::{
+    emotes::{DownloadedCheermotes, DownloadedEmotes},
+    utils::styles::NO_COLOR,
+};
+
+/// Bits needed to reach each tier, along with the color Twitch uses for it.
+/// <https://dev.twitch.tv/docs/api/reference/#get-cheermotes>
+const TIER_COLORS: [(u64, Color); 5] = [
+    (10000, Color::Rgb(244, 48, 33)),
+    (5000, Color::Rgb(0, 153, 254)),
+    (1000, Color::Rgb(29, 178, 165)),
+    (100, Color::Rgb(156, 62, 232)),
+    (1, Color::Rgb(151, 151, 151)),
+];
+
+/// Style of cheers, using the color of the tier reached by the amount of bits.
+pub fn bits_style(bits: u64) -> Style {
+    if *NO_COLOR {
+        return Style::default().add_modifier(Modifier::BOLD);
+    }
+
+    let color = TIER_COLORS
+        .iter()
+        .find(|(min_bits, _)| bits >= *min_bits)
+        .map_or(Color::Gray, |(_, color)| *color);
+
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
+/// A line showing the amount of bits cheered, shown above the message.
+pub fn bits_line(bits: u64) -> Line<'static> {
+    let unit = if bits == 1 { "bit" } else { "bits" };
+
+    Line::from(Span::styled(
+        format!("\u{25C6} Cheered {bits} {unit}"),
+        bits_style(bits),
+    ))
+}
+
+/// Splits a cheermote such as `Cheer100` into its lowercase prefix and amount of bits.
+fn split_cheer(word: &str) -> Option<(String, u64)> {
+    let prefix = word.trim_end_matches(|c: char| c.is_ascii_digit());
+
+    if prefix.is_empty() || prefix.len() == word.len() {
+        return None;
+    }
+
+    Some((prefix.to_lowercase(), word[prefix.len()..].parse().ok()?))
+}
+
+/// Every cheermote within the message, mapped to the image of the tier its amount of bits reached,
+/// so that they can be displayed the same way as emotes.
+pub fn cheermote_emotes(payload: &str, cheermotes: &DownloadedCheermotes) -> DownloadedEmotes {
+    if cheermotes.is_empty() {
+        return DownloadedEmotes::default();
+    }
+
+    payload
+        .split(' ')
+        .filter_map(|word| {
+            let (prefix, bits) = split_cheer(word)?;
+
+            // Tiers are sorted by the amount of bits needed to reach them.
+            let (_, filename) = cheermotes
+                .get(&prefix)?
+                .iter()
+                .rev()
+                .find(|(min_bits, _)| bits >= *min_bits)?;
+
+            Some((word.to_string(), (filename.clone(), false)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cheermotes() -> DownloadedCheermotes {
+        DownloadedCheermotes::from([(
+            "cheer".to_string(),
+            vec![
+                (1, "cheer_cheer_1".to_string()),
+                (100, "cheer_cheer_100".to_string()),
+                (1000, "cheer_cheer_1000".to_string()),
+            ],
+        )])
+    }
+
+    #[test]
+    fn test_cheermote_emotes() {
+        let emotes = cheermote_emotes("Cheer250 nice cheer1 Cheer Kappa100 100", &cheermotes());
+
+        assert_eq!(
+            emotes,
+            DownloadedEmotes::from([
+                (
+                    "Cheer250".to_string(),
+                    ("cheer_cheer_100".to_string(), false)
+                ),
+                ("cheer1".to_string(), ("cheer_cheer_1".to_string(), false)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_bits_line() {
+        assert_eq!(
+            bits_line(1).spans[0].content,
+            "\u{25C6} Cheered 1 bit".to_string()
+        );
+        assert_eq!(
+            bits_style(5000).fg,
+            (!*NO_COLOR).then_some(Color::Rgb(0, 153, 254))
+        );
+    }
+}
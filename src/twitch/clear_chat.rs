@@ -0,0 +1,91 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+use crate::utils::text::format_duration;
+
+/// A moderator either cleared the whole chat, or purged the messages of a single user.
+/// <https://dev.twitch.tv/docs/irc/tags/#clearchat-tags>
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClearChat {
+    /// ID of the user that was timed out or banned, or `None` if the whole chat was cleared.
+    pub user_id: Option<String>,
+    /// Login name of the user, sent as the trailing parameter of the command.
+    pub user_login: Option<String>,
+    /// The amount of seconds the user was timed out for, or `None` if they were banned.
+    pub ban_duration: Option<u64>,
+}
+
+impl ClearChat {
+    /// Parameters are the channel, followed by the login name of the user if there is one.
+    pub fn new<S: BuildHasher>(tags: &HashMap<&str, &str, S>, parameters: &[String]) -> Self {
+        Self {
+            user_id: tags.get("target-user-id").map(ToString::to_string),
+            user_login: parameters
+                .get(1)
+                .map(|login| login.trim().to_string())
+                .filter(|login| !login.is_empty()),
+            ban_duration: tags.get("ban-duration").and_then(|d| d.parse().ok()),
+        }
+    }
+
+    /// What happened, with the name of the user falling back to what could be found elsewhere.
+    pub fn notice(&self, fallback_login: Option<&str>, purged: usize) -> String {
+        if self.user_id.is_none() {
+            return "Chat cleared by a moderator.".to_string();
+        }
+
+        let user = self
+            .user_login
+            .as_deref()
+            .or(fallback_login)
+            .unwrap_or("Unknown user");
+
+        let punishment = self.ban_duration.map_or_else(
+            || "banned".to_string(),
+            |duration| format!("timed out for {}", format_duration(duration)),
+        );
+
+        match purged {
+            0 => format!("{user} was {punishment}."),
+            1 => format!("{user} was {punishment}, 1 message purged."),
+            _ => format!("{user} was {punishment}, {purged} messages purged."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_notice() {
+        let clear_chat = ClearChat::new(
+            &HashMap::from([("target-user-id", "123"), ("ban-duration", "600")]),
+            &["#xithrius".to_string(), "human".to_string()],
+        );
+
+        assert_eq!(
+            clear_chat.notice(None, 3),
+            "human was timed out for 10 minutes, 3 messages purged."
+        );
+    }
+
+    #[test]
+    fn test_ban_notice_with_fallback_login() {
+        let clear_chat = ClearChat::new(
+            &HashMap::from([("target-user-id", "123")]),
+            &["#xithrius".to_string()],
+        );
+
+        assert_eq!(clear_chat.notice(Some("human"), 0), "human was banned.");
+    }
+
+    #[test]
+    fn test_clear_chat_notice() {
+        let clear_chat = ClearChat::new(&HashMap::<&str, &str>::new(), &["#xithrius".to_string()]);
+
+        assert_eq!(
+            clear_chat.notice(Some("human"), 5),
+            "Chat cleared by a moderator."
+        );
+    }
+}
@@ -1,49 +1,213 @@
-use std::cmp::min;
-use std::time::Duration;
+use std::{cmp::min, io, time::Duration};
 
+use futures::{
+    channel::mpsc::{unbounded, UnboundedSender},
+    stream::{self, BoxStream},
+    SinkExt, StreamExt,
+};
 use irc::{
-    client::{prelude::Config, Client, ClientStream},
+    client::{
+        prelude::{Capability, Config},
+        Client,
+    },
     error::Error::{self, PingTimeout},
+    proto::{CapSubCommand, Command, Message},
 };
+use log::debug;
 use tokio::{sync::mpsc::Sender, time::sleep};
+use tokio_tungstenite::{connect_async, tungstenite};
 
-use crate::handlers::{
-    config::CompleteConfig,
-    data::{DataBuilder, TwitchToTerminalAction},
+use crate::{
+    handlers::{
+        config::{CompleteConfig, Transport},
+        data::{DataBuilder, TwitchToTerminalAction},
+    },
+    twitch::oauth::current_token,
 };
 
+/// Messages received from Twitch, regardless of the transport they came through.
+pub type TwitchStream = BoxStream<'static, Result<Message, Error>>;
+
+/// Sends messages to Twitch through whichever transport the connection was made with.
+pub enum TwitchClient {
+    Irc(Box<Client>),
+    /// Messages are written to the socket by a separate task.
+    WebSocket(UnboundedSender<Message>),
+}
+
+impl TwitchClient {
+    pub fn send<M: Into<Message>>(&self, message: M) -> Result<(), Error> {
+        match self {
+            Self::Irc(client) => client.send(message),
+            Self::WebSocket(sender) => sender
+                .unbounded_send(message.into())
+                .map_err(|_| Error::AsyncChannelClosed),
+        }
+    }
+
+    pub fn send_join(&self, channel: String) -> Result<(), Error> {
+        self.send(Command::JOIN(channel, None, None))
+    }
+
+    pub fn send_part(&self, channel: String) -> Result<(), Error> {
+        self.send(Command::PART(channel, None))
+    }
+
+    pub fn send_cap_req(&self, capabilities: &[Capability]) -> Result<(), Error> {
+        let capabilities = capabilities
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<&str>>()
+            .join(" ");
+
+        self.send(Command::CAP(
+            None,
+            CapSubCommand::REQ,
+            None,
+            Some(capabilities),
+        ))
+    }
+}
+
+/// The channel to join when connecting, there being none after the last channel was parted.
+fn current_channel(config: &CompleteConfig) -> Option<String> {
+    (!config.twitch.channel.is_empty()).then(|| format!("#{}", config.twitch.channel))
+}
+
 /// Initialize the config and send it to the client to connect to an IRC channel.
-async fn create_client_stream(config: CompleteConfig) -> Result<(Client, ClientStream), Error> {
+async fn create_irc_stream(config: &CompleteConfig) -> Result<(TwitchClient, TwitchStream), Error> {
     let irc_config = Config {
         nickname: Some(config.twitch.username.clone()),
         server: Some(config.twitch.server.clone()),
-        channels: vec![format!("#{}", config.twitch.channel)],
+        channels: current_channel(config).into_iter().collect(),
         password: config.twitch.token.clone(),
-        port: Some(6697),
-        use_tls: Some(true),
+        port: Some(config.twitch.port),
+        use_tls: Some(config.twitch.tls),
         ping_timeout: Some(10),
         ping_time: Some(10),
         ..Default::default()
     };
 
-    let mut client = Client::from_config(irc_config.clone()).await?;
+    let mut client = Client::from_config(irc_config).await?;
 
     client.identify()?;
 
-    let stream = client.stream()?;
+    let stream = client.stream()?.boxed();
+
+    Ok((TwitchClient::Irc(Box::new(client)), stream))
+}
+
+/// A single WebSocket frame can contain multiple IRC messages, each on its own line.
+/// Lines that are not valid IRC messages are skipped.
+fn parse_websocket_frame(text: &str) -> Vec<Message> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match line.parse::<Message>() {
+            Ok(message) => Some(message),
+            Err(err) => {
+                debug!("Unable to parse IRC message {line:?} from WebSocket: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+fn websocket_error(err: tungstenite::Error) -> Error {
+    Error::Io(io::Error::other(err))
+}
+
+/// Connects to Twitch chat through a WebSocket, for networks where IRC ports are blocked.
+/// The same IRC messages are sent over the socket, one per text frame.
+/// <https://dev.twitch.tv/docs/irc/#connecting-to-the-twitch-irc-server>
+async fn create_websocket_stream(
+    config: &CompleteConfig,
+) -> Result<(TwitchClient, TwitchStream), Error> {
+    let (socket, _) = connect_async(config.twitch.websocket_url.as_str())
+        .await
+        .map_err(websocket_error)?;
+
+    let (mut sink, source) = socket.split();
+    let (sender, mut receiver) = unbounded::<Message>();
+
+    tokio::spawn(async move {
+        while let Some(message) = receiver.next().await {
+            if let Err(err) = sink
+                .send(tungstenite::Message::text(message.to_string()))
+                .await
+            {
+                debug!("Unable to send message through WebSocket: {err}");
+                break;
+            }
+        }
+    });
+
+    let client = TwitchClient::WebSocket(sender.clone());
+
+    if let Some(token) = &config.twitch.token {
+        client.send(Command::PASS(token.clone()))?;
+    }
+
+    client.send(Command::NICK(config.twitch.username.clone()))?;
+    if let Some(channel) = current_channel(config) {
+        client.send_join(channel)?;
+    }
+
+    let stream = source
+        .flat_map(move |frame| {
+            let messages = match frame {
+                Ok(tungstenite::Message::Text(text)) => parse_websocket_frame(&text)
+                    .into_iter()
+                    .map(Ok)
+                    .collect(),
+                Ok(_) => vec![],
+                Err(err) => vec![Err(websocket_error(err))],
+            };
+
+            // Unlike the IRC client, pings have to be answered manually.
+            for message in messages.iter().flatten() {
+                if let Command::PING(ref server, _) = message.command {
+                    let _ = sender.unbounded_send(Command::PONG(server.clone(), None).into());
+                }
+            }
+
+            stream::iter(messages)
+        })
+        // Closing the socket ends the stream, which is treated as an error to trigger a reconnect.
+        .chain(stream::once(async {
+            Err(Error::Io(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "WebSocket connection closed",
+            )))
+        }))
+        .boxed();
 
     Ok((client, stream))
 }
 
+async fn create_client_stream(
+    config: &CompleteConfig,
+) -> Result<(TwitchClient, TwitchStream), Error> {
+    // Tokens from `twt login` may have been refreshed since the config was loaded.
+    let mut config = config.clone();
+    config.twitch.token = current_token(&config.twitch)
+        .await
+        .map_err(|err| Error::Io(io::Error::other(err.to_string())))?;
+
+    match config.twitch.transport {
+        Transport::Irc => create_irc_stream(&config).await,
+        Transport::WebSocket => create_websocket_stream(&config).await,
+    }
+}
+
 pub async fn wait_client_stream(
     tx: Sender<TwitchToTerminalAction>,
     data_builder: DataBuilder<'_>,
     config: CompleteConfig,
-) -> (Client, ClientStream) {
+) -> (TwitchClient, TwitchStream) {
     let mut timeout = 1;
 
     loop {
-        match create_client_stream(config.clone()).await {
+        match create_client_stream(&config).await {
             Ok(v) => return v,
             Err(err) => match err {
                 Error::Io(io) => tx
@@ -70,7 +234,7 @@ pub async fn client_stream_reconnect(
     tx: Sender<TwitchToTerminalAction>,
     data_builder: DataBuilder<'_>,
     config: &CompleteConfig,
-) -> (Client, ClientStream) {
+) -> (TwitchClient, TwitchStream) {
     match err {
         PingTimeout => {
             tx.send(data_builder.system("Ping to Twitch has timed out.".to_string()))
@@ -94,3 +258,74 @@ pub async fn client_stream_reconnect(
 
     (client, stream)
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_websocket_frame() {
+        let messages = parse_websocket_frame(
+            ":tmi.twitch.tv 001 justinfan123 :Welcome, GLHF!\r\n\
+             PING :tmi.twitch.tv\r\n\
+             \r\n",
+        );
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            messages[1].command,
+            Command::PING("tmi.twitch.tv".to_string(), None)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_websocket_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = accept_async(stream).await.unwrap();
+
+            let mut received = vec![];
+
+            for _ in 0..3 {
+                if let Some(Ok(tungstenite::Message::Text(text))) = socket.next().await {
+                    received.push(text.trim_end().to_string());
+                }
+            }
+
+            socket
+                .send(tungstenite::Message::text(
+                    ":xithrius!xithrius@xithrius.tmi.twitch.tv PRIVMSG #xithrius :hello\r\n",
+                ))
+                .await
+                .unwrap();
+
+            received
+        });
+
+        let mut config = CompleteConfig::default();
+        config.twitch.username = "justinfan123".to_string();
+        config.twitch.channel = "xithrius".to_string();
+        config.twitch.token = Some("oauth:token".to_string());
+        config.twitch.transport = Transport::WebSocket;
+        config.twitch.websocket_url = format!("ws://{address}");
+
+        let (_client, mut stream) = create_client_stream(&config).await.unwrap();
+
+        let message = stream.next().await.unwrap().unwrap();
+
+        assert_eq!(
+            message.command,
+            Command::PRIVMSG("#xithrius".to_string(), "hello".to_string())
+        );
+        assert_eq!(
+            server.await.unwrap(),
+            ["PASS oauth:token", "NICK justinfan123", "JOIN #xithrius"]
+        );
+    }
+}
@@ -0,0 +1,740 @@
+use std::{
+    cmp::min,
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use color_eyre::{
+    eyre::{bail, ContextCompat},
+    Result,
+};
+use futures::StreamExt;
+use log::{info, warn};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::{
+    net::TcpStream,
+    sync::{broadcast::Receiver, mpsc::Sender},
+    time::{sleep, timeout},
+};
+use tokio_tungstenite::{connect_async, tungstenite, MaybeTlsStream, WebSocketStream};
+
+use crate::{
+    handlers::{
+        config::{CompleteConfig, TwitchConfig},
+        data::TwitchToTerminalAction,
+        state::State,
+    },
+    twitch::{
+        channel_event::ChannelEvent,
+        channels::get_moderated_channels,
+        moderation::helix_error,
+        oauth::{get_channel_id, get_twitch_client, get_twitch_client_id},
+        polls::{POLL_SCOPES, PREDICTION_SCOPES},
+        scopes::has_scope,
+        TwitchAction,
+    },
+};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// How long Twitch has to send the welcome message after connecting.
+const WELCOME_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Added to the keepalive timeout that Twitch asks for, so that a slow keepalive is not mistaken for a lost connection.
+const KEEPALIVE_MARGIN: Duration = Duration::from_secs(3);
+
+/// Twitch may send a message more than once, so the IDs of this many recent messages are kept to skip duplicates.
+const RECENT_MESSAGES: usize = 100;
+
+/// Scope needed to know which channels the current user is a moderator of.
+pub const MODERATED_CHANNELS_SCOPE: &str = "user:read:moderated_channels";
+
+/// What the current user is in a channel, which decides the events of it that can be subscribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Broadcaster,
+    Moderator,
+    Viewer,
+}
+
+/// An event that can be subscribed to, along with what it needs to be subscribed to.
+/// <https://dev.twitch.tv/docs/eventsub/eventsub-subscription-types/>
+struct SubscriptionType {
+    name: &'static str,
+    version: &'static str,
+    /// Any of these scopes allows subscribing.
+    scopes: &'static [&'static str],
+    /// If the subscription is made as a moderator of the channel, rather than as its broadcaster.
+    moderator: bool,
+}
+
+impl SubscriptionType {
+    /// If the current user is allowed to subscribe to this event in a channel.
+    const fn allows(&self, role: Role) -> bool {
+        match role {
+            Role::Broadcaster => true,
+            Role::Moderator => self.moderator,
+            Role::Viewer => false,
+        }
+    }
+}
+
+const fn subscription_type(
+    name: &'static str,
+    version: &'static str,
+    scopes: &'static [&'static str],
+    moderator: bool,
+) -> SubscriptionType {
+    SubscriptionType {
+        name,
+        version,
+        scopes,
+        moderator,
+    }
+}
+
+const SUBSCRIPTION_TYPES: [SubscriptionType; 14] = [
+    subscription_type(
+        "channel.channel_points_custom_reward_redemption.add",
+        "1",
+        &["channel:read:redemptions", "channel:manage:redemptions"],
+        false,
+    ),
+    subscription_type("channel.follow", "2", &["moderator:read:followers"], true),
+    subscription_type("channel.poll.begin", "1", &POLL_SCOPES, false),
+    subscription_type("channel.poll.progress", "1", &POLL_SCOPES, false),
+    subscription_type("channel.poll.end", "1", &POLL_SCOPES, false),
+    subscription_type("channel.prediction.begin", "1", &PREDICTION_SCOPES, false),
+    subscription_type(
+        "channel.prediction.progress",
+        "1",
+        &PREDICTION_SCOPES,
+        false,
+    ),
+    subscription_type("channel.prediction.lock", "1", &PREDICTION_SCOPES, false),
+    subscription_type("channel.prediction.end", "1", &PREDICTION_SCOPES, false),
+    subscription_type(
+        "channel.hype_train.begin",
+        "2",
+        &["channel:read:hype_train"],
+        false,
+    ),
+    subscription_type(
+        "channel.hype_train.end",
+        "2",
+        &["channel:read:hype_train"],
+        false,
+    ),
+    subscription_type(
+        "channel.shoutout.create",
+        "1",
+        &["moderator:read:shoutouts"],
+        true,
+    ),
+    subscription_type(
+        "channel.shoutout.receive",
+        "1",
+        &["moderator:read:shoutouts"],
+        true,
+    ),
+    subscription_type("channel.ad_break.begin", "1", &["channel:read:ads"], false),
+];
+
+#[derive(Deserialize)]
+struct Metadata {
+    message_id: String,
+    message_type: String,
+}
+
+#[derive(Deserialize)]
+struct Session {
+    id: String,
+    /// Seconds without any message after which the connection should be considered lost.
+    keepalive_timeout_seconds: Option<u64>,
+    /// Where to connect to when Twitch asks for the connection to be moved.
+    reconnect_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Subscription {
+    #[serde(default)]
+    id: String,
+    #[serde(rename = "type")]
+    name: String,
+    #[serde(default)]
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionList {
+    data: Vec<Subscription>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Payload {
+    session: Option<Session>,
+    subscription: Option<Subscription>,
+    event: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct Frame {
+    metadata: Metadata,
+    #[serde(default)]
+    payload: Payload,
+}
+
+/// A message sent through an `EventSub` WebSocket.
+/// <https://dev.twitch.tv/docs/eventsub/websocket-reference/>
+enum EventSubMessage {
+    Welcome(Session),
+    Keepalive,
+    Notification {
+        subscription_type: String,
+        event: Value,
+    },
+    /// Twitch is about to close the connection, and the session continues at this URL.
+    Reconnect(String),
+    /// A subscription was removed by Twitch, such as when the token was revoked.
+    Revocation {
+        subscription_type: String,
+        status: String,
+    },
+}
+
+impl EventSubMessage {
+    /// The ID of a message along with the message, or `None` if it is of a type that is not handled.
+    fn parse(text: &str) -> Result<Option<(String, Self)>> {
+        let Frame { metadata, payload } = serde_json::from_str(text)?;
+
+        let message = match metadata.message_type.as_str() {
+            "session_welcome" => {
+                Self::Welcome(payload.session.context("Welcome message has no session")?)
+            }
+            "session_keepalive" => Self::Keepalive,
+            "notification" => Self::Notification {
+                subscription_type: payload
+                    .subscription
+                    .context("Notification has no subscription")?
+                    .name,
+                event: payload.event.context("Notification has no event")?,
+            },
+            "session_reconnect" => Self::Reconnect(
+                payload
+                    .session
+                    .and_then(|session| session.reconnect_url)
+                    .context("Reconnect message has no URL")?,
+            ),
+            "revocation" => {
+                let subscription = payload
+                    .subscription
+                    .context("Revocation has no subscription")?;
+
+                Self::Revocation {
+                    subscription_type: subscription.name,
+                    status: subscription.status,
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some((metadata.message_id, message)))
+    }
+}
+
+/// The next message of the connection, failing once it has been closed.
+async fn next_message(socket: &mut Socket) -> Result<(String, EventSubMessage)> {
+    loop {
+        match socket
+            .next()
+            .await
+            .context("EventSub connection was closed")??
+        {
+            tungstenite::Message::Text(text) => {
+                if let Some(message) = EventSubMessage::parse(&text)? {
+                    return Ok(message);
+                }
+            }
+            tungstenite::Message::Close(_) => bail!("EventSub connection was closed"),
+            _ => {}
+        }
+    }
+}
+
+/// A connection to `EventSub`, which has been welcomed into a session.
+struct Connection {
+    socket: Socket,
+    session_id: String,
+    /// How long to wait for a message before the connection is considered lost.
+    keepalive: Duration,
+}
+
+impl Connection {
+    async fn connect(url: &str) -> Result<Self> {
+        let (mut socket, _) = connect_async(url).await?;
+
+        let (_, message) = timeout(WELCOME_TIMEOUT, next_message(&mut socket)).await??;
+
+        let EventSubMessage::Welcome(session) = message else {
+            bail!("EventSub did not start with a welcome message");
+        };
+
+        Ok(Self {
+            socket,
+            session_id: session.id,
+            keepalive: Duration::from_secs(session.keepalive_timeout_seconds.unwrap_or(10))
+                + KEEPALIVE_MARGIN,
+        })
+    }
+}
+
+/// What the current user is in a channel. Moderators can only be told apart from viewers
+/// with the `user:read:moderated_channels` scope, without which they are treated as viewers.
+async fn channel_role(
+    client: &Client,
+    twitch_config: &TwitchConfig,
+    user_id: &str,
+    broadcaster_id: &str,
+) -> Result<Role> {
+    if user_id == broadcaster_id {
+        return Ok(Role::Broadcaster);
+    }
+
+    if !has_scope(twitch_config, MODERATED_CHANNELS_SCOPE) {
+        return Ok(Role::Viewer);
+    }
+
+    let moderated = get_moderated_channels(client, &twitch_config.api_url, user_id).await?;
+
+    Ok(
+        if moderated
+            .iter()
+            .any(|channel| channel.broadcaster_id == broadcaster_id)
+        {
+            Role::Moderator
+        } else {
+            Role::Viewer
+        },
+    )
+}
+
+/// Subscribes the session to an event of a channel, returning the ID of the subscription
+/// or `None` if the session was already subscribed to it.
+/// <https://dev.twitch.tv/docs/api/reference/#create-eventsub-subscription>
+async fn subscribe(
+    client: &Client,
+    api_url: &str,
+    session_id: &str,
+    broadcaster_id: &str,
+    user_id: &str,
+    subscription_type: &SubscriptionType,
+) -> Result<Option<String>> {
+    let mut condition = json!({ "broadcaster_user_id": broadcaster_id });
+
+    if subscription_type.moderator {
+        condition["moderator_user_id"] = json!(user_id);
+    }
+
+    let response = client
+        .post(format!("{api_url}/eventsub/subscriptions"))
+        .json(&json!({
+            "type": subscription_type.name,
+            "version": subscription_type.version,
+            "condition": condition,
+            "transport": {
+                "method": "websocket",
+                "session_id": session_id,
+            },
+        }))
+        .send()
+        .await?;
+
+    // The session is already subscribed to the event.
+    if response.status() == StatusCode::CONFLICT {
+        return Ok(None);
+    }
+
+    if !response.status().is_success() {
+        bail!(helix_error(response).await);
+    }
+
+    let subscriptions = response.json::<SubscriptionList>().await?;
+
+    Ok(subscriptions
+        .data
+        .into_iter()
+        .next()
+        .map(|subscription| subscription.id))
+}
+
+/// Removes a subscription, so that its events are no longer sent.
+/// <https://dev.twitch.tv/docs/api/reference/#delete-eventsub-subscription>
+async fn unsubscribe(client: &Client, api_url: &str, subscription_id: &str) -> Result<()> {
+    let response = client
+        .delete(format!("{api_url}/eventsub/subscriptions"))
+        .query(&[("id", subscription_id)])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        bail!(helix_error(response).await);
+    }
+
+    Ok(())
+}
+
+/// Subscribes the session to every event of a channel that the token has the scopes for,
+/// and that the current user is allowed to subscribe to as its broadcaster or as one of its moderators.
+/// Returns the IDs of the subscriptions, for them to be removed when leaving the channel.
+async fn subscribe_channel(
+    twitch_config: &TwitchConfig,
+    session_id: &str,
+    channel: &str,
+) -> Vec<String> {
+    let subscribe_all = async {
+        let client = get_twitch_client(twitch_config).await?;
+        let user_id = &get_twitch_client_id(twitch_config).await?.user_id;
+        let broadcaster_id = get_channel_id(&client, &twitch_config.api_url, channel)
+            .await?
+            .to_string();
+
+        let role = channel_role(&client, twitch_config, user_id, &broadcaster_id).await?;
+
+        if role == Role::Viewer {
+            info!("Not subscribing to events in {channel}, as only its broadcaster and moderators can.");
+        }
+
+        let mut subscription_ids = vec![];
+
+        for subscription_type in SUBSCRIPTION_TYPES.iter().filter(|subscription_type| {
+            subscription_type.allows(role)
+                && subscription_type
+                    .scopes
+                    .iter()
+                    .any(|scope| has_scope(twitch_config, scope))
+        }) {
+            match subscribe(
+                &client,
+                &twitch_config.api_url,
+                session_id,
+                &broadcaster_id,
+                user_id,
+                subscription_type,
+            )
+            .await
+            {
+                Ok(subscription_id) => subscription_ids.extend(subscription_id),
+                Err(err) => warn!(
+                    "Unable to subscribe to {} in {channel}: {err}",
+                    subscription_type.name
+                ),
+            }
+        }
+
+        Ok::<Vec<String>, color_eyre::Report>(subscription_ids)
+    };
+
+    match subscribe_all.await {
+        Ok(subscription_ids) => {
+            info!(
+                "Subscribed to {} kinds of events in {channel}.",
+                subscription_ids.len()
+            );
+
+            subscription_ids
+        }
+        Err(err) => {
+            warn!("Unable to subscribe to events in {channel}: {err}");
+
+            vec![]
+        }
+    }
+}
+
+/// Removes the subscriptions to the events of a channel that has been left.
+async fn unsubscribe_channel(
+    twitch_config: &TwitchConfig,
+    channel: &str,
+    subscription_ids: &[String],
+) {
+    let client = match get_twitch_client(twitch_config).await {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("Unable to unsubscribe from events in {channel}: {err}");
+            return;
+        }
+    };
+
+    for subscription_id in subscription_ids {
+        if let Err(err) = unsubscribe(&client, &twitch_config.api_url, subscription_id).await {
+            warn!("Unable to unsubscribe from {subscription_id} in {channel}: {err}");
+        }
+    }
+}
+
+/// Receives channel events that are not sent through IRC, such as channel point redemptions and polls,
+/// and forwards them to the terminal.
+/// <https://dev.twitch.tv/docs/eventsub/handling-websocket-events/>
+pub async fn twitch_eventsub(
+    config: CompleteConfig,
+    tx: Sender<TwitchToTerminalAction>,
+    mut rx: Receiver<TwitchAction>,
+) {
+    if config.twitch.anonymous || !config.twitch.eventsub {
+        return;
+    }
+
+    info!("Spawned Twitch EventSub thread.");
+
+    // Channels are subscribed to as they are joined, starting with the first one unless it is picked on the dashboard.
+    // The IDs of the subscriptions of each channel are kept to unsubscribe from them when it is left.
+    let mut channels: HashMap<String, Vec<String>> = HashMap::new();
+
+    if config.terminal.first_state != State::Dashboard {
+        channels.insert(config.twitch.channel.clone(), vec![]);
+    }
+
+    let mut recent_messages = VecDeque::with_capacity(RECENT_MESSAGES);
+    let mut retry = 1;
+
+    loop {
+        let mut connection = match Connection::connect(&config.twitch.eventsub_url).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                warn!("Unable to connect to EventSub: {err}");
+
+                sleep(Duration::from_secs(retry)).await;
+                retry = min(retry * 2, 30);
+
+                continue;
+            }
+        };
+
+        retry = 1;
+
+        for (channel, subscription_ids) in &mut channels {
+            *subscription_ids =
+                subscribe_channel(&config.twitch, &connection.session_id, channel).await;
+        }
+
+        loop {
+            tokio::select! {
+                Ok(action) = rx.recv() => {
+                    match action {
+                        TwitchAction::Join(channel) if !channels.contains_key(&channel) => {
+                            let subscription_ids = subscribe_channel(&config.twitch, &connection.session_id, &channel).await;
+
+                            channels.insert(channel, subscription_ids);
+                        }
+                        TwitchAction::Part(channel) => {
+                            if let Some(subscription_ids) = channels.remove(&channel) {
+                                unsubscribe_channel(&config.twitch, &channel, &subscription_ids).await;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                message = timeout(connection.keepalive, next_message(&mut connection.socket)) => {
+                    let (message_id, message) = match message {
+                        Ok(Ok(message)) => message,
+                        Ok(Err(err)) => {
+                            warn!("Lost connection to EventSub: {err}");
+                            break;
+                        }
+                        Err(_) => {
+                            warn!("EventSub stopped sending keepalive messages.");
+                            break;
+                        }
+                    };
+
+                    if recent_messages.contains(&message_id) {
+                        continue;
+                    }
+
+                    if recent_messages.len() == RECENT_MESSAGES {
+                        recent_messages.pop_front();
+                    }
+
+                    recent_messages.push_back(message_id);
+
+                    match message {
+                        EventSubMessage::Notification { subscription_type, event } => {
+                            match ChannelEvent::from_notification(&subscription_type, &event) {
+                                Ok(Some((channel, event))) => {
+                                    tx.send(TwitchToTerminalAction::ChannelEvent(channel, event)).await.unwrap();
+                                }
+                                Ok(None) => {}
+                                Err(err) => warn!("Unable to parse {subscription_type} event: {err}"),
+                            }
+                        }
+                        // Subscriptions carry over to the new connection, which replaces this one once it is welcomed.
+                        EventSubMessage::Reconnect(url) => match Connection::connect(&url).await {
+                            Ok(new_connection) => connection = new_connection,
+                            Err(err) => {
+                                warn!("Unable to reconnect to EventSub: {err}");
+                                break;
+                            }
+                        },
+                        EventSubMessage::Revocation { subscription_type, status } => {
+                            warn!("Subscription to {subscription_type} was revoked: {status}");
+                        }
+                        EventSubMessage::Welcome(_) | EventSubMessage::Keepalive => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::SinkExt;
+    use tokio::{net::TcpListener, sync::broadcast};
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+    use crate::twitch::mock_server::mock_server;
+
+    fn welcome(keepalive: &str, reconnect_url: &str) -> String {
+        format!(
+            r#"{{"metadata":{{"message_id":"welcome-{keepalive}","message_type":"session_welcome","message_timestamp":"2023-07-19T14:56:51.634234626Z"}},"payload":{{"session":{{"id":"AQoQILE98gtqShGmLD7AM6yJThAB","status":"connected","connected_at":"2023-07-19T14:56:51.616329898Z","keepalive_timeout_seconds":{keepalive},"reconnect_url":{reconnect_url}}}}}}}"#
+        )
+    }
+
+    fn follow(message_id: &str, user: &str) -> String {
+        format!(
+            r#"{{"metadata":{{"message_id":"{message_id}","message_type":"notification","message_timestamp":"2023-07-19T10:11:12.123Z","subscription_type":"channel.follow","subscription_version":"2"}},"payload":{{"subscription":{{"id":"f1c2a387-161a-49f9-a165-0f21d7a4e1c4","status":"enabled","type":"channel.follow","version":"2","cost":0,"condition":{{"broadcaster_user_id":"12826","moderator_user_id":"12826"}},"transport":{{"method":"websocket","session_id":"AQoQexAWVYKSTIu4ec_2VAxyuhAB"}},"created_at":"2023-07-19T14:56:51.634234626Z"}},"event":{{"user_id":"1337","user_login":"{user}","user_name":"{user}","broadcaster_user_id":"12826","broadcaster_user_login":"twitch","broadcaster_user_name":"Twitch","followed_at":"2023-07-15T18:16:11.17106713Z"}}}}}}"#
+        )
+    }
+
+    #[test]
+    fn test_subscription_type_allows_role() {
+        let follow = &SUBSCRIPTION_TYPES[1];
+        let redemption = &SUBSCRIPTION_TYPES[0];
+
+        assert!(follow.allows(Role::Broadcaster));
+        assert!(follow.allows(Role::Moderator));
+        assert!(!follow.allows(Role::Viewer));
+
+        assert!(redemption.allows(Role::Broadcaster));
+        assert!(!redemption.allows(Role::Moderator));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_returns_id() {
+        let api_url = mock_server(vec![
+            (
+                "202 Accepted",
+                r#"{"data":[{"id":"f1c2a387-161a-49f9-a165-0f21d7a4e1c4","status":"enabled","type":"channel.follow","version":"2","condition":{"broadcaster_user_id":"12826","moderator_user_id":"12826"},"created_at":"2023-07-19T14:56:51.634234626Z","transport":{"method":"websocket","session_id":"AQoQexAWVYKSTIu4ec_2VAxyuhAB"},"cost":0}],"total":1,"total_cost":0,"max_total_cost":10}"#,
+            ),
+            ("409 Conflict", r#"{"error":"Conflict","status":409,"message":"subscription already exists"}"#),
+            ("204 No Content", ""),
+        ])
+        .await;
+
+        let client = Client::new();
+        let follow = &SUBSCRIPTION_TYPES[1];
+
+        assert_eq!(
+            subscribe(&client, &api_url, "session", "12826", "12826", follow)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("f1c2a387-161a-49f9-a165-0f21d7a4e1c4")
+        );
+        assert_eq!(
+            subscribe(&client, &api_url, "session", "12826", "12826", follow)
+                .await
+                .unwrap(),
+            None
+        );
+
+        unsubscribe(&client, &api_url, "f1c2a387-161a-49f9-a165-0f21d7a4e1c4")
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_reconnect() {
+        let message = EventSubMessage::parse(
+            r#"{"metadata":{"message_id":"84c1e79a-2a4b-4c13-ba0b-4312293e9308","message_type":"session_reconnect","message_timestamp":"2022-11-18T09:10:11.634234626Z"},"payload":{"session":{"id":"AQoQexAWVYKSTIu4ec_2VAxyuhAB","status":"reconnecting","keepalive_timeout_seconds":null,"reconnect_url":"wss://eventsub.wss.twitch.tv?...","connected_at":"2022-11-16T10:11:12.634234626Z"}}}"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(message.0, "84c1e79a-2a4b-4c13-ba0b-4312293e9308");
+        assert!(
+            matches!(message.1, EventSubMessage::Reconnect(ref url) if url == "wss://eventsub.wss.twitch.tv?...")
+        );
+
+        let (_, message) = EventSubMessage::parse(&welcome("10", "null"))
+            .unwrap()
+            .unwrap();
+
+        assert!(
+            matches!(message, EventSubMessage::Welcome(session) if session.keepalive_timeout_seconds == Some(10))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let reconnect_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        let reconnect_url = format!("ws://{}", reconnect_listener.local_addr().unwrap());
+
+        let mut config = CompleteConfig::default();
+        config.terminal.first_state = State::Dashboard;
+        config.twitch.eventsub_url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = accept_async(stream).await.unwrap();
+
+            for message in [
+                welcome("10", "null"),
+                follow("1", "foo"),
+                // Messages that were already received are skipped.
+                follow("1", "foo"),
+                format!(
+                    r#"{{"metadata":{{"message_id":"2","message_type":"session_reconnect","message_timestamp":"2022-11-18T09:10:11.634234626Z"}},"payload":{{"session":{{"id":"AQoQexAWVYKSTIu4ec_2VAxyuhAB","status":"reconnecting","keepalive_timeout_seconds":null,"reconnect_url":"{reconnect_url}","connected_at":"2022-11-16T10:11:12.634234626Z"}}}}}}"#
+                ),
+            ] {
+                socket
+                    .send(tungstenite::Message::text(message))
+                    .await
+                    .unwrap();
+            }
+
+            let (stream, _) = reconnect_listener.accept().await.unwrap();
+            let mut socket = accept_async(stream).await.unwrap();
+
+            for message in [welcome("null", "null"), follow("3", "bar")] {
+                socket
+                    .send(tungstenite::Message::text(message))
+                    .await
+                    .unwrap();
+            }
+
+            // Keeps the connection open until the test is over.
+            socket.next().await;
+        });
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let (_action_tx, action_rx) = broadcast::channel(100);
+
+        tokio::spawn(twitch_eventsub(config, tx, action_rx));
+
+        for user in ["foo", "bar"] {
+            let Some(TwitchToTerminalAction::ChannelEvent(channel, event)) = rx.recv().await else {
+                panic!("Expected a channel event");
+            };
+
+            assert_eq!(channel, "twitch");
+            assert_eq!(event, ChannelEvent::Follow(user.to_string()));
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use std::{
+    fs::{read_to_string, File},
+    io::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use color_eyre::{
+    eyre::{bail, ContextCompat},
+    Result,
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::{task, time::sleep};
+
+use crate::{
+    handlers::config::TwitchConfig,
+    twitch::{
+        oauth::{strip_oauth_prefix, validate_token},
+        token_command::run_token_command,
+    },
+    utils::pathing::config_path,
+};
+
+/// Scopes requested when logging in, covering everything that `twt` can do.
+/// <https://dev.twitch.tv/docs/authentication/scopes/>
+pub const SCOPES: [&str; 22] = [
+    "chat:read",
+    "chat:edit",
+    "channel:moderate",
+    "user:read:follows",
+    "user:read:emotes",
+    "user:manage:whispers",
+    "moderator:manage:banned_users",
+    "moderator:manage:chat_messages",
+    "moderator:manage:chat_settings",
+    "moderator:manage:warnings",
+    "moderator:manage:shield_mode",
+    "channel:manage:moderators",
+    "channel:manage:vips",
+    "whispers:read",
+    "channel:read:redemptions",
+    "moderator:read:followers",
+    "channel:manage:polls",
+    "channel:manage:predictions",
+    "channel:read:hype_train",
+    "moderator:read:shoutouts",
+    "channel:read:ads",
+    "user:read:moderated_channels",
+];
+
+/// Tokens are refreshed this long before they expire, so that requests in flight do not fail.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Where the tokens from logging in are stored, next to the config.
+const LOGIN_FILE: &str = "login.toml";
+
+/// A pending login, waiting for the user to enter the code.
+/// <https://dev.twitch.tv/docs/authentication/getting-tokens-oauth/#device-code-grant-flow>
+#[derive(Deserialize, Debug)]
+struct DeviceCode {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    /// Seconds until the device code expires.
+    expires_in: u64,
+    /// Seconds to wait between polls for the token.
+    interval: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    /// Seconds until the access token expires.
+    expires_in: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct AuthError {
+    message: String,
+}
+
+/// Tokens received by logging in through `twt login`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoredLogin {
+    /// Login name of the user that the tokens belong to.
+    pub login: String,
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+impl StoredLogin {
+    /// The stored tokens, if the user has logged in before.
+    pub fn load() -> Option<Self> {
+        let content = read_to_string(config_path(LOGIN_FILE)).ok()?;
+
+        toml::from_str(&content)
+            .map_err(|err| warn!("Unable to read the stored login: {err}"))
+            .ok()
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut options = File::options();
+        options.write(true).create(true).truncate(true);
+
+        // The tokens give access to the account, so only the user should be able to read them.
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(config_path(LOGIN_FILE))?;
+
+        file.write_all(toml::to_string(self)?.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// The access token in the format used by the config.
+    pub fn token(&self) -> String {
+        format!("oauth:{}", self.access_token)
+    }
+}
+
+/// Where the token in use came from, which decides how a new one is gotten once it expires.
+#[derive(Clone)]
+enum TokenSource {
+    /// Tokens from `twt login`, which are refreshed through Twitch.
+    Login {
+        client_id: String,
+        auth_url: String,
+        tokens: StoredLogin,
+    },
+    /// A token printed by `twitch.token_command`, which is run again for a new one.
+    Command { command: String, token: String },
+}
+
+impl TokenSource {
+    /// The access token, without the `oauth:` prefix.
+    fn access_token(&self) -> &str {
+        match self {
+            Self::Login { tokens, .. } => &tokens.access_token,
+            Self::Command { token, .. } => strip_oauth_prefix(token),
+        }
+    }
+}
+
+/// The token that is in use, which is renewed once it expires.
+#[derive(Clone)]
+struct Session {
+    source: TokenSource,
+    /// When the access token expires, once it is known.
+    expires_at: Option<Instant>,
+}
+
+static SESSION: Lazy<Mutex<Option<Session>>> = Lazy::new(Mutex::default);
+
+/// Only one refresh can happen at a time, as refresh tokens can only be used once.
+static REFRESH: Lazy<tokio::sync::Mutex<()>> = Lazy::new(tokio::sync::Mutex::default);
+
+fn set_session(source: TokenSource) {
+    *SESSION.lock().unwrap() = Some(Session {
+        source,
+        expires_at: None,
+    });
+}
+
+/// Uses the stored tokens for every request to Twitch, refreshing them when they expire.
+pub fn start_session(twitch_config: &TwitchConfig, tokens: StoredLogin) {
+    set_session(TokenSource::Login {
+        client_id: twitch_config.client_id.clone(),
+        auth_url: twitch_config.auth_url.clone(),
+        tokens,
+    });
+}
+
+/// Uses the token from `twitch.token_command` for every request to Twitch,
+/// running the command again when the token expires or is rejected.
+pub fn start_command_session(command: String, token: String) {
+    set_session(TokenSource::Command { command, token });
+}
+
+/// Sets when the access token of the session expires, if it is the one that was validated.
+pub fn set_session_expiry(access_token: &str, expires_in: Duration) {
+    if let Some(session) = SESSION.lock().unwrap().as_mut() {
+        if session.source.access_token() == access_token {
+            session.expires_at = Some(Instant::now() + expires_in);
+        }
+    }
+}
+
+/// Marks the access token of the session as expired, after Twitch rejected it.
+/// Returns `false` if there is no session to renew.
+pub fn expire_session() -> bool {
+    SESSION.lock().unwrap().as_mut().is_some_and(|session| {
+        session.expires_at = Some(Instant::now());
+        true
+    })
+}
+
+/// Gets a new token for the session, along with when it expires if that is known.
+async fn renew(source: TokenSource) -> Result<(TokenSource, Option<Instant>)> {
+    match source {
+        TokenSource::Login {
+            client_id,
+            auth_url,
+            mut tokens,
+        } => {
+            info!("Refreshing the access token.");
+
+            let response =
+                refresh_access_token(&Client::new(), &auth_url, &client_id, &tokens.refresh_token)
+                    .await?;
+
+            tokens.access_token = response.access_token;
+            tokens.refresh_token = response.refresh_token;
+
+            if let Err(err) = tokens.save() {
+                warn!("Unable to store the refreshed tokens: {err}");
+            }
+
+            Ok((
+                TokenSource::Login {
+                    client_id,
+                    auth_url,
+                    tokens,
+                },
+                Some(Instant::now() + Duration::from_secs(response.expires_in)),
+            ))
+        }
+        TokenSource::Command { command, .. } => {
+            info!("Running the token command for a new token.");
+
+            let token = task::spawn_blocking({
+                let command = command.clone();
+                move || run_token_command(&command)
+            })
+            .await??;
+
+            // The expiry is found out once the new token is validated.
+            Ok((TokenSource::Command { command, token }, None))
+        }
+    }
+}
+
+/// The token of the session, renewing it first if it has expired.
+/// Returns `None` if the token did not come from `twt login` or `twitch.token_command`.
+pub async fn session_token() -> Result<Option<String>> {
+    let _refresh = REFRESH.lock().await;
+
+    let Some(session) = SESSION.lock().unwrap().clone() else {
+        return Ok(None);
+    };
+
+    if session.expires_at.map_or(true, |expires_at| {
+        Instant::now() + EXPIRY_MARGIN < expires_at
+    }) {
+        return Ok(Some(format!("oauth:{}", session.source.access_token())));
+    }
+
+    let (source, expires_at) = renew(session.source).await?;
+    let token = format!("oauth:{}", source.access_token());
+
+    SESSION
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|session| {
+            session.source = source;
+            session.expires_at = expires_at;
+        })
+        .context("Token session has ended")?;
+
+    Ok(Some(token))
+}
+
+/// The reason Twitch gave for an authentication request failing.
+async fn auth_error(response: reqwest::Response) -> String {
+    let status = response.status();
+
+    response
+        .json::<AuthError>()
+        .await
+        .map_or_else(|_| status.to_string(), |error| error.message)
+}
+
+async fn request_device_code(
+    client: &Client,
+    auth_url: &str,
+    client_id: &str,
+) -> Result<DeviceCode> {
+    let response = client
+        .post(format!("{auth_url}/device"))
+        .form(&[("client_id", client_id), ("scopes", &SCOPES.join(" "))])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        bail!("Unable to start logging in: {}", auth_error(response).await);
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Waits for the user to enter the code, polling at the interval Twitch asked for.
+async fn poll_device_token(
+    client: &Client,
+    auth_url: &str,
+    client_id: &str,
+    device_code: &DeviceCode,
+) -> Result<TokenResponse> {
+    let mut interval = Duration::from_secs(device_code.interval);
+    let expires_at = Instant::now() + Duration::from_secs(device_code.expires_in);
+
+    while Instant::now() < expires_at {
+        sleep(interval).await;
+
+        let response = client
+            .post(format!("{auth_url}/token"))
+            .form(&[
+                ("client_id", client_id),
+                ("scopes", &SCOPES.join(" ")),
+                ("device_code", &device_code.device_code),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ])
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            return Ok(response.json().await?);
+        }
+
+        match auth_error(response).await.as_str() {
+            "authorization_pending" => {}
+            "slow_down" => interval += Duration::from_secs(5),
+            error => bail!("Unable to log in: {error}"),
+        }
+    }
+
+    bail!("The code has expired, run `twt login` again.")
+}
+
+async fn refresh_access_token(
+    client: &Client,
+    auth_url: &str,
+    client_id: &str,
+    refresh_token: &str,
+) -> Result<TokenResponse> {
+    let response = client
+        .post(format!("{auth_url}/token"))
+        .form(&[
+            ("client_id", client_id),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        bail!(
+            "Unable to refresh the access token, run `twt login` again: {}",
+            auth_error(response).await
+        );
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Logs in through the device code flow, with the user entering a code in their browser.
+async fn device_login(twitch_config: &TwitchConfig) -> Result<StoredLogin> {
+    if twitch_config.client_id.is_empty() {
+        bail!("Logging in needs the client ID of an application registered as a public client at https://dev.twitch.tv/console/apps, set as `client_id` in the [twitch] section of the config.");
+    }
+
+    let client = Client::new();
+    let auth_url = &twitch_config.auth_url;
+    let client_id = &twitch_config.client_id;
+
+    let device_code = request_device_code(&client, auth_url, client_id).await?;
+
+    println!(
+        "To log in, go to {} and enter the code {}",
+        device_code.verification_uri, device_code.user_code
+    );
+
+    if webbrowser::open(&device_code.verification_uri).is_err() {
+        info!("Unable to open the login page in a browser.");
+    }
+
+    let response = poll_device_token(&client, auth_url, client_id, &device_code).await?;
+
+    let validated = validate_token(auth_url, &response.access_token).await?;
+
+    Ok(StoredLogin {
+        login: validated.login,
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+    })
+}
+
+/// Runs `twt login`, storing the tokens so that they are used from then on.
+pub async fn login(twitch_config: &TwitchConfig) -> Result<StoredLogin> {
+    let tokens = device_login(twitch_config).await?;
+
+    tokens.save()?;
+
+    println!(
+        "Logged in as {}. The tokens are stored in {}",
+        tokens.login,
+        config_path(LOGIN_FILE)
+    );
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitch::mock_server::mock_server;
+
+    #[tokio::test]
+    async fn test_poll_device_token() {
+        let auth_url = mock_server(vec![
+            (
+                "400 Bad Request",
+                r#"{"status":400,"message":"authorization_pending"}"#,
+            ),
+            (
+                "200 OK",
+                r#"{"access_token":"access","expires_in":14124,"refresh_token":"refresh","scope":["chat:read"],"token_type":"bearer"}"#,
+            ),
+        ])
+        .await;
+
+        let device_code = DeviceCode {
+            device_code: "device".to_string(),
+            user_code: "ABCDEFGH".to_string(),
+            verification_uri: "https://www.twitch.tv/activate".to_string(),
+            expires_in: 60,
+            interval: 0,
+        };
+
+        let response = poll_device_token(&Client::new(), &auth_url, "client", &device_code)
+            .await
+            .unwrap();
+
+        assert_eq!(response.access_token, "access");
+        assert_eq!(response.refresh_token, "refresh");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_rejected() {
+        let auth_url = mock_server(vec![(
+            "400 Bad Request",
+            r#"{"status":400,"message":"Invalid refresh token"}"#,
+        )])
+        .await;
+
+        let err = refresh_access_token(&Client::new(), &auth_url, "client", "refresh")
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().ends_with("Invalid refresh token"));
+    }
+}
@@ -1,7 +1,26 @@
-mod badges;
+pub mod badges;
+pub mod channel_event;
 pub mod channels;
+pub mod cheermotes;
+pub mod clear_chat;
 mod connection;
+pub mod eventsub;
+pub mod login;
+#[cfg(test)]
+mod mock_server;
+pub mod moderation;
+pub mod notice;
 pub mod oauth;
+pub mod polls;
+pub mod rate_limit;
+pub mod reply;
+pub mod room_state;
+pub mod scopes;
+pub mod stream_info;
+pub mod token_command;
+pub mod user_notice;
+pub mod user_state;
+pub mod whispers;
 
 use std::{collections::HashMap, hash::BuildHasher};
 
@@ -9,23 +28,39 @@ use color_eyre::Result;
 use futures::StreamExt;
 use irc::{
     client::prelude::Capability,
-    proto::{Command, Message},
+    proto::{message::Tag, Command, Message},
 };
 use log::{debug, info};
-use tokio::sync::{broadcast::Receiver, mpsc::Sender};
+use tokio::{
+    sync::{broadcast::Receiver, mpsc::Sender},
+    time::{sleep_until, Instant},
+};
 
 use crate::{
     emotes::{get_twitch_emote, DownloadedEmotes},
     handlers::{
         config::CompleteConfig,
-        data::{DataBuilder, TwitchToTerminalAction},
+        data::{DataBuilder, RawMessageData, TwitchToTerminalAction},
         state::State,
     },
     twitch::{
-        badges::retrieve_user_badges,
-        connection::{client_stream_reconnect, wait_client_stream},
+        badges::Badge,
+        clear_chat::ClearChat,
+        connection::{client_stream_reconnect, wait_client_stream, TwitchClient},
+        login::expire_session,
+        moderation::ModerationCommand,
+        notice::Notice,
+        polls::VoteCommand,
+        rate_limit::RateLimiter,
+        reply::ReplyParent,
+        room_state::RoomState,
+        scopes::missing_scopes,
+        user_notice::UserNotice,
+        user_state::UserState,
+        whispers::send_whisper,
     },
     utils::{
+        colors::hex_to_color,
         emotes::emotes_enabled,
         text::{clean_message, parse_message_action},
     },
@@ -34,10 +69,32 @@ use crate::{
 #[derive(Debug, Clone)]
 pub enum TwitchAction {
     Privmsg(String),
+    /// Send a message as a reply to another message.
+    Reply(String, ReplyParent),
+    /// Join a channel if it has not been joined yet, and make it the current channel.
     Join(String),
+    Part(String),
+    /// Send a whisper to a user, with the recipient's login name and the message.
+    Whisper(String, String),
+    /// Run a moderation command in the current channel through the Helix API.
+    Moderate(ModerationCommand),
+    /// Create, end or resolve a poll or prediction in the current channel through the Helix API.
+    Vote(VoteCommand),
     ClearMessages,
 }
 
+/// IRC targets are prefixed with `#`, whereas channels are stored without it.
+fn channel_name(target: &str) -> Option<String> {
+    target.strip_prefix('#').map(ToString::to_string)
+}
+
+/// The name a user has chosen to be shown with, falling back to their login name.
+fn display_name<S: BuildHasher>(login: &str, tags: &HashMap<&str, &str, S>) -> String {
+    tags.get("display-name")
+        .filter(|name| !name.is_empty())
+        .map_or_else(|| login.to_string(), ToString::to_string)
+}
+
 pub async fn twitch_irc(
     mut config: CompleteConfig,
     tx: Sender<TwitchToTerminalAction>,
@@ -63,12 +120,29 @@ pub async fn twitch_irc(
     let enable_emotes = emotes_enabled(&config.frontend);
 
     let data_builder = DataBuilder::new(&config.frontend.datetime_format);
-    let mut room_state_startup = false;
+
+    // Anything the token was not granted the scopes for is pointed out before it is used.
+    for (feature, scopes) in missing_scopes(
+        &config.twitch,
+        enable_emotes && config.frontend.twitch_emotes,
+    ) {
+        tx.send(data_builder.system(format!(
+            "{} will not work, as the token is missing the following scopes: {}.",
+            feature.description(),
+            scopes.join(", ")
+        )))
+        .await
+        .unwrap();
+    }
+
+    // Room states of every joined channel, only the first one of each channel is displayed.
+    let mut room_states: HashMap<String, RoomState> = HashMap::new();
 
     let (mut client, mut stream) =
         wait_client_stream(tx.clone(), data_builder, config.clone()).await;
 
-    let sender = client.sender();
+    // The current channel is joined when connecting to the server.
+    let mut joined_channels = vec![config.twitch.channel.clone()];
 
     // Request commands capabilities
     if client
@@ -90,7 +164,12 @@ pub async fn twitch_irc(
 
     let mut connected = true;
 
+    // Messages that would exceed Twitch's rate limits are held back until they can be sent.
+    let mut rate_limiter = RateLimiter::default();
+
     loop {
+        let next_send = rate_limiter.next_send(Instant::now());
+
         tokio::select! {
             biased;
 
@@ -101,27 +180,105 @@ pub async fn twitch_irc(
                     TwitchAction::Privmsg(message) => {
                         debug!("Sending message to Twitch: {}", message);
 
-                        client
-                            .send_privmsg(current_channel, message)
-                            .unwrap();
+                        rate_limiter.push(config.twitch.channel.clone(), Command::PRIVMSG(current_channel, message).into());
+
+                        send_queued_messages(&client, &mut rate_limiter, tx.clone(), data_builder).await;
+                    }
+                    TwitchAction::Reply(message, reply_parent) => {
+                        debug!("Replying to message {} on Twitch: {}", reply_parent.message_id, message);
+
+                        // Replies are regular messages with the parent message as a client tag.
+                        rate_limiter.push(config.twitch.channel.clone(), Message {
+                            tags: Some(vec![Tag("reply-parent-msg-id".to_string(), Some(reply_parent.message_id))]),
+                            prefix: None,
+                            command: Command::PRIVMSG(current_channel, message),
+                        });
+
+                        send_queued_messages(&client, &mut rate_limiter, tx.clone(), data_builder).await;
                     }
                     TwitchAction::Join(channel) => {
-                        debug!("Switching to channel {channel}");
+                        if joined_channels.contains(&channel) {
+                            debug!("Switching to channel {channel}");
+                        } else {
+                            debug!("Joining channel {channel}");
 
-                        let channel_list = format!("#{channel}");
+                            if let Err(err) = client.send_join(format!("#{channel}")) {
+                                tx.send(data_builder.twitch(Some(channel.clone()), err.to_string())).await.unwrap();
+                            }
 
-                        // Leave previous channel
-                        if let Err(err) = sender.send_part(current_channel) {
-                            tx.send(data_builder.twitch(err.to_string())).await.unwrap();
+                            joined_channels.push(channel.clone());
                         }
 
-                        // Join specified channel
-                        if let Err(err) = sender.send_join(&channel_list) {
-                            tx.send(data_builder.twitch(err.to_string())).await.unwrap();
+                        // Messages are now sent to the specified channel
+                        config.twitch.channel = channel;
+                    }
+                    TwitchAction::Part(channel) => {
+                        debug!("Leaving channel {channel}");
+
+                        if let Err(err) = client.send_part(format!("#{channel}")) {
+                            tx.send(data_builder.twitch(None, err.to_string())).await.unwrap();
                         }
 
-                        // Set old channel to new channel
-                        config.twitch.channel = channel;
+                        joined_channels.retain(|c| c != &channel);
+                        room_states.remove(&channel);
+
+                        // Otherwise the parted channel would be joined again when reconnecting.
+                        if config.twitch.channel == channel {
+                            config.twitch.channel = joined_channels.first().cloned().unwrap_or_default();
+                        }
+                    }
+                    TwitchAction::Whisper(recipient, message) => {
+                        debug!("Whispering to {recipient}: {message}");
+
+                        // Helix requests are made in the background, so that chat keeps going while waiting for them.
+                        let (twitch_config, datetime_format, tx) =
+                            (config.twitch.clone(), config.frontend.datetime_format.clone(), tx.clone());
+
+                        tokio::spawn(async move {
+                            if let Err(err) = send_whisper(&twitch_config, &recipient, &message).await {
+                                let data_builder = DataBuilder::new(&datetime_format);
+
+                                tx.send(data_builder.system(format!("Unable to whisper {recipient}: {err}"))).await.unwrap();
+                            }
+                        });
+                    }
+                    TwitchAction::Moderate(command) => {
+                        debug!("Running moderation command in {}: {command:?}", config.twitch.channel);
+
+                        let (twitch_config, datetime_format, tx) =
+                            (config.twitch.clone(), config.frontend.datetime_format.clone(), tx.clone());
+
+                        tokio::spawn(async move {
+                            let channel = &twitch_config.channel;
+
+                            let text = match command.execute(&twitch_config, channel).await {
+                                Ok(description) => description,
+                                Err(err) => format!("Moderation command failed: {err}"),
+                            };
+
+                            let data_builder = DataBuilder::new(&datetime_format);
+
+                            tx.send(data_builder.twitch(Some(channel.clone()), text)).await.unwrap();
+                        });
+                    }
+                    TwitchAction::Vote(command) => {
+                        debug!("Running poll or prediction command in {}: {command:?}", config.twitch.channel);
+
+                        let (twitch_config, datetime_format, tx) =
+                            (config.twitch.clone(), config.frontend.datetime_format.clone(), tx.clone());
+
+                        tokio::spawn(async move {
+                            let channel = &twitch_config.channel;
+
+                            let text = match command.execute(&twitch_config, channel).await {
+                                Ok(description) => description,
+                                Err(err) => format!("Poll or prediction command failed: {err}"),
+                            };
+
+                            let data_builder = DataBuilder::new(&datetime_format);
+
+                            tx.send(data_builder.twitch(Some(channel.clone()), text)).await.unwrap();
+                        });
                     }
                     TwitchAction::ClearMessages => {
                         client.send(Command::Raw("CLEARCHAT".to_string(), vec![])).unwrap();
@@ -136,9 +293,7 @@ pub async fn twitch_irc(
                             connected = true;
                         }
 
-                        if let Some(b) = handle_message_command(message, tx.clone(), data_builder, config.frontend.badges, room_state_startup, enable_emotes).await {
-                            room_state_startup = b;
-                        }
+                        handle_message_command(message, tx.clone(), data_builder, &mut room_states, &mut rate_limiter, enable_emotes).await;
                     }
                     Err(err) => {
                         connected = false;
@@ -147,14 +302,52 @@ pub async fn twitch_irc(
 
                         (client, stream) = client_stream_reconnect(err, tx.clone(), data_builder, &config).await;
 
+                        // Only the current channel is joined on connection, so rejoin the rest.
+                        for channel in joined_channels.iter().filter(|&c| c != &config.twitch.channel) {
+                            if let Err(err) = client.send_join(format!("#{channel}")) {
+                                tx.send(data_builder.twitch(Some(channel.clone()), err.to_string())).await.unwrap();
+                            }
+                        }
                     }
                 }
             }
+            () = sleep_until(next_send.unwrap_or_else(Instant::now)), if next_send.is_some() => {
+                send_queued_messages(&client, &mut rate_limiter, tx.clone(), data_builder).await;
+            }
             else => {}
         };
     }
 }
 
+/// Sends every queued message that is within the rate limits,
+/// and lets the user know about the messages that are still waiting.
+async fn send_queued_messages(
+    client: &TwitchClient,
+    rate_limiter: &mut RateLimiter,
+    tx: Sender<TwitchToTerminalAction>,
+    data_builder: DataBuilder<'_>,
+) {
+    let now = Instant::now();
+
+    for (channel, message) in rate_limiter.ready(now) {
+        if let Err(err) = client.send(message) {
+            tx.send(data_builder.system(format!("Unable to send message: {err}")))
+                .await
+                .unwrap();
+        } else {
+            tx.send(TwitchToTerminalAction::MessageSent(channel))
+                .await
+                .unwrap();
+        }
+    }
+
+    tx.send(TwitchToTerminalAction::OutgoingQueue(
+        rate_limiter.status(now),
+    ))
+    .await
+    .unwrap();
+}
+
 /// Emotes comming from twitch arrive in the `emote` tag.
 /// They have the format `<emote-id1>:<start>-<end>,.../<emote-id2>:...`
 /// This functions returns a list of emote name and id,
@@ -179,14 +372,41 @@ fn retrieve_twitch_emotes(message: &str, emotes: &str) -> Vec<(String, String)>
         .collect()
 }
 
+/// Downloads the emotes of a message from its tags if they are not downloaded yet,
+/// returning the name of every emote along with its filename.
+async fn download_twitch_emotes<S: BuildHasher>(
+    message: &str,
+    tags: &HashMap<&str, &str, S>,
+    enable_emotes: bool,
+) -> DownloadedEmotes {
+    let emotes = enable_emotes
+        .then(|| {
+            tags.get("emotes")
+                .map(|&e| retrieve_twitch_emotes(message, e))
+        })
+        .unwrap_or_default()
+        .unwrap_or_default();
+
+    futures::stream::iter(emotes.into_iter().map(|(name, filename)| async move {
+        get_twitch_emote(&filename).await?;
+        Ok((name, (filename, false)))
+    }))
+    .buffer_unordered(10)
+    .collect::<Vec<Result<(String, (String, bool))>>>()
+    .await
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
 async fn handle_message_command(
     message: Message,
     tx: Sender<TwitchToTerminalAction>,
     data_builder: DataBuilder<'_>,
-    badges: bool,
-    room_state_startup: bool,
+    room_states: &mut HashMap<String, RoomState>,
+    rate_limiter: &mut RateLimiter,
     enable_emotes: bool,
-) -> Option<bool> {
+) {
     let mut tags: HashMap<&str, &str> = HashMap::new();
 
     if let Some(ref ref_tags) = message.tags {
@@ -198,123 +418,207 @@ async fn handle_message_command(
     }
 
     match message.command {
-        Command::PRIVMSG(ref _target, ref msg) => {
+        Command::PRIVMSG(ref target, ref msg) => {
+            let Some(channel) = channel_name(target) else {
+                return;
+            };
+
             // Detects if the message contains an IRC CTCP Action, and return the message content.
             // WARNING: Emote parsing needs to be done *after* the message has been extracted from the action,
             // but *before* problematic unicode characters have been removed from it.
             let (msg, highlight) = parse_message_action(msg);
 
-            // Parse emotes from message tags
-            let emotes = enable_emotes
-                .then(|| tags.get("emotes").map(|&e| retrieve_twitch_emotes(msg, e)))
-                .unwrap_or_default()
-                .unwrap_or_default();
-
-            // Download emotes if they are not downloaded yet.
-            // Small optimisation of starting the download here, and only await this block at the last moment.
-            let emotes =
-                futures::stream::iter(emotes.into_iter().map(|(name, filename)| async move {
-                    get_twitch_emote(&filename).await?;
-                    Ok((name, (filename, false)))
-                }))
-                .buffer_unordered(10)
-                .collect::<Vec<Result<(String, (String, bool))>>>();
+            // Parse emotes from message tags, and download them if they are not downloaded yet.
+            // Small optimisation of creating the download here, and only await it at the last moment.
+            let emotes = download_twitch_emotes(msg, &tags, enable_emotes);
 
             // lowercase username from message
-            let mut name = message.source_nickname().unwrap().to_string();
-
-            retrieve_user_badges(&mut name, &message, badges);
+            let name = display_name(message.source_nickname().unwrap(), &tags);
 
             // Remove invalid unicode characters from the message.
             let cleaned_message = clean_message(msg);
 
             let message_id = tags.get("id").map(|&s| s.to_string());
             let user_id = tags.get("user-id").map(|&s| s.to_string());
+            let reply_parent = ReplyParent::from_tags(&tags);
+            let color = tags.get("color").and_then(|&c| hex_to_color(c));
+            let bits = tags.get("bits").and_then(|&b| b.parse().ok());
 
             debug!("Message received from twitch: {name} - {cleaned_message:?}");
 
-            let emotes = emotes.await.into_iter().flatten().collect();
+            let emotes = emotes.await;
 
             tx.send(DataBuilder::user(
+                channel,
                 name,
                 user_id,
                 cleaned_message,
                 emotes,
                 message_id,
                 highlight,
+                reply_parent,
+                color,
+                Badge::from_tags(&tags),
+                bits,
             ))
             .await
             .unwrap();
         }
-        Command::NOTICE(ref _target, ref msg) => {
-            tx.send(data_builder.twitch(msg.to_string())).await.unwrap();
+        Command::NOTICE(ref target, ref msg) => {
+            // Twitch closes the connection after rejecting the token,
+            // so a token from `twt login` is refreshed before reconnecting.
+            if msg == "Login authentication failed" {
+                expire_session();
+            }
+
+            tx.send(TwitchToTerminalAction::Notice(
+                channel_name(target),
+                Notice::from_tags(&tags, msg),
+            ))
+            .await
+            .unwrap();
         }
-        Command::JOIN(ref channel, _, _) => {
-            tx.send(data_builder.twitch(format!("Joined {}", *channel)))
+        Command::JOIN(ref target, _, _) => {
+            tx.send(data_builder.twitch(channel_name(target), format!("Joined {target}")))
                 .await
                 .unwrap();
         }
-        Command::Raw(ref cmd, ref _items) => {
+        // https://dev.twitch.tv/docs/irc/tags/#whisper-tags
+        Command::Raw(ref cmd, ref items) if cmd == "WHISPER" => {
+            let (Some(user), Some(msg)) = (message.source_nickname(), items.get(1)) else {
+                return;
+            };
+
+            debug!("Whisper received from twitch: {user} - {msg:?}");
+
+            let mut whisper = RawMessageData::new(
+                None,
+                user.to_string(),
+                tags.get("user-id").map(|&s| s.to_string()),
+                false,
+                clean_message(msg),
+                DownloadedEmotes::default(),
+                tags.get("message-id").map(|&s| s.to_string()),
+                false,
+                None,
+            );
+
+            whisper.color = tags.get("color").and_then(|&c| hex_to_color(c));
+
+            tx.send(TwitchToTerminalAction::Whisper(Box::new(whisper)))
+                .await
+                .unwrap();
+        }
+        // https://dev.twitch.tv/docs/irc/tags/#globaluserstate-tags
+        Command::Raw(ref cmd, _) if cmd == "GLOBALUSERSTATE" => {
+            tx.send(TwitchToTerminalAction::UserState(
+                None,
+                UserState::from_tags(&tags),
+            ))
+            .await
+            .unwrap();
+        }
+        Command::Raw(ref cmd, ref items) => {
+            // Every command handled below has the channel as its first parameter.
+            let Some(channel) = items.first().and_then(|target| channel_name(target)) else {
+                return;
+            };
+
             match cmd.as_ref() {
                 // https://dev.twitch.tv/docs/irc/tags/#roomstate-tags
                 "ROOMSTATE" => {
-                    // Only display roomstate on startup, since twitch
+                    // Only display roomstate when joining a channel, since twitch
                     // sends a NOTICE whenever roomstate changes.
-                    if !room_state_startup {
-                        handle_roomstate(&tx, &tags).await;
+                    let startup = !room_states.contains_key(&channel);
+
+                    let room_state = room_states.entry(channel.clone()).or_default();
+                    room_state.update(&tags);
+
+                    if startup {
+                        handle_roomstate(&tx, &channel, room_state).await;
                     }
 
-                    return Some(true);
+                    tx.send(TwitchToTerminalAction::RoomState(
+                        channel,
+                        room_state.clone(),
+                    ))
+                    .await
+                    .unwrap();
+                }
+                // https://dev.twitch.tv/docs/irc/tags/#userstate-tags
+                "USERSTATE" => {
+                    let user_state = UserState::from_tags(&tags);
+
+                    // Moderators and broadcasters are allowed to send more messages.
+                    rate_limiter.set_elevated(&channel, user_state.is_moderator());
+
+                    // Twitch echoes the user state with the ID of a message once it has accepted it.
+                    if let Some(message_id) = tags.get("id") {
+                        tx.send(TwitchToTerminalAction::MessageConfirmed(
+                            channel.clone(),
+                            (*message_id).to_string(),
+                        ))
+                        .await
+                        .unwrap();
+                    }
+
+                    tx.send(TwitchToTerminalAction::UserState(Some(channel), user_state))
+                        .await
+                        .unwrap();
                 }
                 // https://dev.twitch.tv/docs/irc/tags/#usernotice-tags
                 "USERNOTICE" => {
-                    if let Some(value) = tags.get("system-msg") {
-                        tx.send(data_builder.twitch((*value).to_string()))
-                            .await
-                            .unwrap();
-                    }
+                    let Some(user_notice) = UserNotice::from_tags(&tags) else {
+                        return;
+                    };
+
+                    // The message the user attached to the event, if any.
+                    let msg = items.get(1).map_or("", String::as_str);
+
+                    let emotes = download_twitch_emotes(msg, &tags, enable_emotes);
+
+                    let name = display_name(tags.get("login").copied().unwrap_or("Twitch"), &tags);
+
+                    let cleaned_message = clean_message(msg);
+
+                    debug!(
+                        "User notice received from twitch: {user_notice:?} - {cleaned_message:?}"
+                    );
+
+                    tx.send(DataBuilder::user_notice(
+                        channel,
+                        name,
+                        tags.get("user-id").map(|&s| s.to_string()),
+                        cleaned_message,
+                        emotes.await,
+                        tags.get("id").map(|&s| s.to_string()),
+                        user_notice,
+                        tags.get("color").and_then(|&c| hex_to_color(c)),
+                        Badge::from_tags(&tags),
+                    ))
+                    .await
+                    .unwrap();
                 }
                 // https://dev.twitch.tv/docs/irc/tags/#clearchat-tags
                 "CLEARCHAT" => {
-                    let user_id = tags.get("target-user-id").map(|&s| s.to_string());
-
-                    tx.send(TwitchToTerminalAction::ClearChat(user_id.clone()))
-                        .await
-                        .unwrap();
-
-                    // User was either timed out or banned
-                    if user_id.is_some() {
-                        let ban_duration = tags.get("ban-duration").map(|&s| s.to_string());
-
-                        // TODO: In both cases of this branch, replace "User" with the username that the punishment was inflicted upon
-
-                        // User was timed out
-                        if let Some(duration) = ban_duration {
-                            tx.send(
-                                data_builder
-                                    .twitch(format!("User was timed out for {duration} seconds")),
-                            )
-                            .await
-                            .unwrap();
-                        }
-                        // User was banned
-                        else {
-                            tx.send(data_builder.twitch("User banned".to_string()))
-                                .await
-                                .unwrap();
-                        }
-                    } else {
-                        tx.send(data_builder.twitch("Chat cleared by a moderator.".to_string()))
-                            .await
-                            .unwrap();
-                    }
+                    // The notice is created once the messages have been purged,
+                    // as the amount of purged messages is only known then.
+                    tx.send(TwitchToTerminalAction::ClearChat(
+                        channel,
+                        ClearChat::new(&tags, items),
+                    ))
+                    .await
+                    .unwrap();
                 }
                 // https://dev.twitch.tv/docs/irc/tags/#clearmsg-tags
                 "CLEARMSG" => {
                     if let Some(id) = tags.get("target-msg-id") {
-                        tx.send(TwitchToTerminalAction::DeleteMessage((*id).to_string()))
-                            .await
-                            .unwrap();
+                        tx.send(TwitchToTerminalAction::DeleteMessage(
+                            channel,
+                            (*id).to_string(),
+                        ))
+                        .await
+                        .unwrap();
                     }
                 }
                 _ => (),
@@ -322,52 +626,31 @@ async fn handle_message_command(
         }
         _ => (),
     }
-
-    None
 }
 
-pub async fn handle_roomstate<S: BuildHasher>(
+pub async fn handle_roomstate(
     tx: &Sender<TwitchToTerminalAction>,
-    tags: &HashMap<&str, &str, S>,
+    channel: &str,
+    room_state: &RoomState,
 ) {
-    let mut room_state = String::new();
-
-    for (name, value) in tags {
-        match *name {
-            "emote-only" if *value == "1" => {
-                room_state.push_str("The channel is emote-only.\n");
-            }
-            "followers-only" if *value != "-1" => {
-                room_state.push_str("The channel is followers-only.\n");
-            }
-            "subs-only" if *value == "1" => {
-                room_state.push_str("The channel is subscribers-only.\n");
-            }
-            "slow" if *value != "0" => {
-                room_state.push_str("The channel has a ");
-                room_state.push_str(value);
-                room_state.push_str("s slowmode.\n");
-            }
-            _ => (),
-        }
-    }
-
-    // Trim last newline
-    room_state.pop();
+    let room_state = room_state.descriptions().join("\n");
 
     if room_state.is_empty() {
         return;
     }
 
-    let message_id = tags.get("target-msg-id").map(|&s| s.to_string());
-
     tx.send(DataBuilder::user(
+        channel.to_string(),
         String::from("Info"),
         None,
         room_state,
         DownloadedEmotes::default(),
-        message_id,
+        None,
         false,
+        None,
+        None,
+        vec![],
+        None,
     ))
     .await
     .unwrap();
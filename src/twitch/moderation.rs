@@ -0,0 +1,504 @@
+use color_eyre::{eyre::bail, Result};
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    handlers::config::TwitchConfig,
+    twitch::{
+        oauth::{get_channel_id, get_twitch_client, get_twitch_client_id},
+        scopes::has_scope,
+    },
+};
+
+/// Chat settings to change, with the ones left as `None` being kept as they are.
+/// <https://dev.twitch.tv/docs/api/reference/#update-chat-settings>
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChatSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emote_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follower_mode: Option<bool>,
+    /// Minutes users have to follow the channel for before chatting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follower_mode_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_mode: Option<bool>,
+    /// Seconds users have to wait between messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_mode_wait_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscriber_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique_chat_mode: Option<bool>,
+}
+
+/// Chat commands that Twitch no longer accepts through IRC, and are sent through the Helix API instead.
+/// These require the current user to be a moderator or the broadcaster of the channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModerationCommand {
+    /// Bans a user, or times them out if there is a duration in seconds.
+    Ban {
+        user: String,
+        duration: Option<u64>,
+        reason: Option<String>,
+    },
+    /// Removes the ban or timeout of a user.
+    Unban(String),
+    /// Deletes a single message by its ID.
+    Delete(String),
+    ChatSettings(ChatSettings),
+    Mod(String),
+    Unmod(String),
+    Vip(String),
+    Unvip(String),
+    Warn {
+        user: String,
+        reason: String,
+    },
+    ShieldMode(bool),
+}
+
+#[derive(Deserialize)]
+struct HelixError {
+    message: String,
+}
+
+/// The reason Helix gave for rejecting a request, falling back to the status if there is none.
+pub async fn helix_error(response: Response) -> String {
+    let status = response.status();
+
+    response
+        .json::<HelixError>()
+        .await
+        .map_or_else(|_| status.to_string(), |error| error.message)
+}
+
+/// The longest timeout that Twitch allows, being two weeks in seconds.
+const MAX_TIMEOUT_DURATION: u64 = 1_209_600;
+
+/// Parses durations such as `600`, `10m` or `1h` into seconds.
+/// Durations without a unit are multiplied by `unit`, and durations that do not fit are `None`.
+pub fn parse_duration(duration: &str, unit: u64) -> Option<u64> {
+    let (amount, multiplier) = match duration.char_indices().last()? {
+        (i, 's') => (&duration[..i], 1),
+        (i, 'm') => (&duration[..i], 60),
+        (i, 'h') => (&duration[..i], 60 * 60),
+        (i, 'd') => (&duration[..i], 60 * 60 * 24),
+        (i, 'w') => (&duration[..i], 60 * 60 * 24 * 7),
+        _ => (duration, unit),
+    };
+
+    amount.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// The remaining arguments joined back together, if there are any.
+fn reason(arguments: &[&str]) -> Option<String> {
+    (!arguments.is_empty()).then(|| arguments.join(" "))
+}
+
+impl ModerationCommand {
+    /// Parses a slash command, returning `None` if it is not a moderation command,
+    /// or its usage if the arguments are not valid.
+    pub fn parse(input: &str) -> Option<Result<Self, &'static str>> {
+        let mut words = input.strip_prefix('/')?.split_whitespace();
+
+        let name = words.next()?;
+        let arguments = words.collect::<Vec<&str>>();
+
+        let user = || {
+            arguments
+                .first()
+                .map(|user| user.trim_start_matches('@').to_lowercase())
+        };
+
+        let settings = |settings: ChatSettings| Some(Ok(Self::ChatSettings(settings)));
+
+        let command = match name {
+            "ban" => user()
+                .map(|user| Self::Ban {
+                    user,
+                    duration: None,
+                    reason: reason(&arguments[1..]),
+                })
+                .ok_or("/ban <user> [reason]"),
+            "timeout" => user()
+                .and_then(|user| {
+                    // Anything starting with a digit is meant as a duration, so a mistyped one is not sent as the reason.
+                    let (duration, reason_start) = match arguments.get(1) {
+                        Some(d) if d.starts_with(|c: char| c.is_ascii_digit()) => {
+                            (parse_duration(d, 1)?, 2)
+                        }
+                        Some(_) | None => (600, 1),
+                    };
+
+                    if duration > MAX_TIMEOUT_DURATION {
+                        return None;
+                    }
+
+                    Some(Self::Ban {
+                        user,
+                        duration: Some(duration),
+                        reason: reason(arguments.get(reason_start..)?),
+                    })
+                })
+                .ok_or("/timeout <user> [duration] [reason]"),
+            "unban" | "untimeout" => user().map(Self::Unban).ok_or("/unban <user>"),
+            "delete" => arguments
+                .first()
+                .map(|id| Self::Delete((*id).to_string()))
+                .ok_or("/delete <message id>"),
+            "slow" => {
+                let Some(wait_time) = arguments.first().map_or(Some(30), |d| parse_duration(d, 1))
+                else {
+                    return Some(Err("/slow [duration]"));
+                };
+
+                return settings(ChatSettings {
+                    slow_mode: Some(true),
+                    slow_mode_wait_time: Some(wait_time),
+                    ..ChatSettings::default()
+                });
+            }
+            "slowoff" => {
+                return settings(ChatSettings {
+                    slow_mode: Some(false),
+                    ..ChatSettings::default()
+                })
+            }
+            "followers" => {
+                let Some(duration) = arguments.first().map_or(Some(0), |d| {
+                    parse_duration(d, 60).map(|seconds| seconds / 60)
+                }) else {
+                    return Some(Err("/followers [duration]"));
+                };
+
+                return settings(ChatSettings {
+                    follower_mode: Some(true),
+                    follower_mode_duration: Some(duration),
+                    ..ChatSettings::default()
+                });
+            }
+            "followersoff" => {
+                return settings(ChatSettings {
+                    follower_mode: Some(false),
+                    ..ChatSettings::default()
+                })
+            }
+            "emoteonly" | "emoteonlyoff" => {
+                return settings(ChatSettings {
+                    emote_mode: Some(name == "emoteonly"),
+                    ..ChatSettings::default()
+                })
+            }
+            "subscribers" | "subscribersoff" => {
+                return settings(ChatSettings {
+                    subscriber_mode: Some(name == "subscribers"),
+                    ..ChatSettings::default()
+                })
+            }
+            "uniquechat" | "r9kbeta" | "uniquechatoff" | "r9kbetaoff" => {
+                return settings(ChatSettings {
+                    unique_chat_mode: Some(!name.ends_with("off")),
+                    ..ChatSettings::default()
+                })
+            }
+            "mod" => user().map(Self::Mod).ok_or("/mod <user>"),
+            "unmod" => user().map(Self::Unmod).ok_or("/unmod <user>"),
+            "vip" => user().map(Self::Vip).ok_or("/vip <user>"),
+            "unvip" => user().map(Self::Unvip).ok_or("/unvip <user>"),
+            "warn" => user()
+                .zip(reason(arguments.get(1..).unwrap_or_default()))
+                .map(|(user, reason)| Self::Warn { user, reason })
+                .ok_or("/warn <user> <reason>"),
+            "shield" => Ok(Self::ShieldMode(true)),
+            "shieldoff" => Ok(Self::ShieldMode(false)),
+            _ => return None,
+        };
+
+        Some(command)
+    }
+
+    /// What happened, once the command went through.
+    fn description(&self) -> String {
+        match self {
+            Self::Ban {
+                user,
+                duration: None,
+                ..
+            } => format!("Banned {user}."),
+            Self::Ban {
+                user,
+                duration: Some(duration),
+                ..
+            } => format!("Timed out {user} for {duration}s."),
+            Self::Unban(user) => format!("Removed the ban or timeout of {user}."),
+            Self::Delete(_) => "Deleted the message.".to_string(),
+            Self::ChatSettings(_) => "Updated the chat settings.".to_string(),
+            Self::Mod(user) => format!("Added {user} as a moderator."),
+            Self::Unmod(user) => format!("Removed {user} as a moderator."),
+            Self::Vip(user) => format!("Added {user} as a VIP."),
+            Self::Unvip(user) => format!("Removed {user} as a VIP."),
+            Self::Warn { user, .. } => format!("Warned {user}."),
+            Self::ShieldMode(true) => "Shield mode is now on.".to_string(),
+            Self::ShieldMode(false) => "Shield mode is now off.".to_string(),
+        }
+    }
+
+    /// The scope the token needs for this command.
+    const fn scope(&self) -> &'static str {
+        match self {
+            Self::Ban { .. } | Self::Unban(_) => "moderator:manage:banned_users",
+            Self::Delete(_) => "moderator:manage:chat_messages",
+            Self::ChatSettings(_) => "moderator:manage:chat_settings",
+            Self::Mod(_) | Self::Unmod(_) => "channel:manage:moderators",
+            Self::Vip(_) | Self::Unvip(_) => "channel:manage:vips",
+            Self::Warn { .. } => "moderator:manage:warnings",
+            Self::ShieldMode(_) => "moderator:manage:shield_mode",
+        }
+    }
+
+    /// The user the command is about, if any.
+    fn user(&self) -> Option<&str> {
+        match self {
+            Self::Ban { user, .. }
+            | Self::Unban(user)
+            | Self::Mod(user)
+            | Self::Unmod(user)
+            | Self::Vip(user)
+            | Self::Unvip(user)
+            | Self::Warn { user, .. } => Some(user),
+            Self::Delete(_) | Self::ChatSettings(_) | Self::ShieldMode(_) => None,
+        }
+    }
+
+    /// Builds the request for this command, with the IDs of everyone involved.
+    fn request(
+        &self,
+        client: &Client,
+        api_url: &str,
+        broadcaster_id: i32,
+        moderator_id: &str,
+        user_id: Option<i32>,
+    ) -> RequestBuilder {
+        let user_id = user_id.unwrap_or_default();
+
+        let ids = format!("broadcaster_id={broadcaster_id}&moderator_id={moderator_id}");
+
+        match self {
+            Self::Ban {
+                duration, reason, ..
+            } => client
+                .post(format!("{api_url}/moderation/bans?{ids}"))
+                .json(&json!({
+                    "data": {
+                        "user_id": user_id.to_string(),
+                        "duration": duration,
+                        "reason": reason.as_deref().unwrap_or_default(),
+                    }
+                })),
+            Self::Unban(_) => {
+                client.delete(format!("{api_url}/moderation/bans?{ids}&user_id={user_id}"))
+            }
+            Self::Delete(message_id) => client.delete(format!(
+                "{api_url}/moderation/chat?{ids}&message_id={message_id}"
+            )),
+            Self::ChatSettings(settings) => client
+                .patch(format!("{api_url}/chat/settings?{ids}"))
+                .json(settings),
+            Self::Mod(_) => client.post(format!(
+                "{api_url}/moderation/moderators?broadcaster_id={broadcaster_id}&user_id={user_id}"
+            )),
+            Self::Unmod(_) => client.delete(format!(
+                "{api_url}/moderation/moderators?broadcaster_id={broadcaster_id}&user_id={user_id}"
+            )),
+            Self::Vip(_) => client.post(format!(
+                "{api_url}/channels/vips?broadcaster_id={broadcaster_id}&user_id={user_id}"
+            )),
+            Self::Unvip(_) => client.delete(format!(
+                "{api_url}/channels/vips?broadcaster_id={broadcaster_id}&user_id={user_id}"
+            )),
+            Self::Warn { reason, .. } => client
+                .post(format!("{api_url}/moderation/warnings?{ids}"))
+                .json(&json!({
+                    "data": {
+                        "user_id": user_id.to_string(),
+                        "reason": reason,
+                    }
+                })),
+            Self::ShieldMode(active) => client
+                .put(format!("{api_url}/moderation/shield_mode?{ids}"))
+                .json(&json!({ "is_active": active })),
+        }
+    }
+
+    /// Sends the command through the client, returning what happened or the reason Twitch gave for rejecting it.
+    async fn send(
+        &self,
+        client: &Client,
+        api_url: &str,
+        broadcaster_id: i32,
+        moderator_id: &str,
+    ) -> Result<String> {
+        let user_id = match self.user() {
+            Some(user) => Some(get_channel_id(client, api_url, user).await?),
+            None => None,
+        };
+
+        let response = self
+            .request(client, api_url, broadcaster_id, moderator_id, user_id)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            bail!(helix_error(response).await);
+        }
+
+        Ok(self.description())
+    }
+
+    /// Executes the command in a channel, as the current user.
+    /// <https://dev.twitch.tv/docs/api/reference/#moderation>
+    pub async fn execute(&self, twitch_config: &TwitchConfig, channel: &str) -> Result<String> {
+        if !has_scope(twitch_config, self.scope()) {
+            bail!("the token is missing the `{}` scope", self.scope());
+        }
+
+        let api_url = &twitch_config.api_url;
+
+        let client = get_twitch_client(twitch_config).await?;
+
+        let moderator_id = &get_twitch_client_id(twitch_config).await?.user_id;
+        let broadcaster_id = get_channel_id(&client, api_url, channel).await?;
+
+        self.send(&client, api_url, broadcaster_id, moderator_id)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitch::mock_server::mock_server;
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(
+            ModerationCommand::parse("/timeout @Xithrius 10m spamming links"),
+            Some(Ok(ModerationCommand::Ban {
+                user: "xithrius".to_string(),
+                duration: Some(600),
+                reason: Some("spamming links".to_string()),
+            }))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/timeout xithrius spamming"),
+            Some(Ok(ModerationCommand::Ban {
+                user: "xithrius".to_string(),
+                duration: Some(600),
+                reason: Some("spamming".to_string()),
+            }))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/timeout"),
+            Some(Err("/timeout <user> [duration] [reason]"))
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90", 1), Some(90));
+        assert_eq!(parse_duration("2h", 1), Some(7200));
+        assert_eq!(parse_duration("10", 60), Some(600));
+        assert_eq!(parse_duration("soon", 1), None);
+        assert_eq!(parse_duration("99999999999999w", 1), None);
+        assert_eq!(
+            ModerationCommand::parse("/timeout xithrius 99999999999999w"),
+            Some(Err("/timeout <user> [duration] [reason]"))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/timeout xithrius 5y"),
+            Some(Err("/timeout <user> [duration] [reason]"))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/timeout xithrius 3w"),
+            Some(Err("/timeout <user> [duration] [reason]"))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/timeout xithrius 2w"),
+            Some(Ok(ModerationCommand::Ban {
+                user: "xithrius".to_string(),
+                duration: Some(MAX_TIMEOUT_DURATION),
+                reason: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_chat_settings() {
+        assert_eq!(
+            ModerationCommand::parse("/slow 1m"),
+            Some(Ok(ModerationCommand::ChatSettings(ChatSettings {
+                slow_mode: Some(true),
+                slow_mode_wait_time: Some(60),
+                ..ChatSettings::default()
+            })))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/followers 10"),
+            Some(Ok(ModerationCommand::ChatSettings(ChatSettings {
+                follower_mode: Some(true),
+                follower_mode_duration: Some(10),
+                ..ChatSettings::default()
+            })))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/slow soon"),
+            Some(Err("/slow [duration]"))
+        );
+    }
+
+    #[test]
+    fn test_parse_other_commands() {
+        assert_eq!(ModerationCommand::parse("/me waves"), None);
+        assert_eq!(ModerationCommand::parse("hello /ban"), None);
+        assert_eq!(
+            ModerationCommand::parse("/warn xithrius"),
+            Some(Err("/warn <user> <reason>"))
+        );
+        assert_eq!(
+            ModerationCommand::parse("/shieldoff"),
+            Some(Ok(ModerationCommand::ShieldMode(false)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_reports_helix_errors() {
+        let api_url = mock_server(vec![
+            ("200 OK", r#"{"data":[{"id":"1234"}]}"#),
+            (
+                "400 Bad Request",
+                r#"{"error":"Bad Request","status":400,"message":"The user specified in the user_id field is already banned."}"#,
+            ),
+            ("200 OK", r#"{"data":[{"id":"1234"}]}"#),
+            ("204 No Content", ""),
+        ])
+        .await;
+
+        let client = Client::new();
+        let ban = ModerationCommand::parse("/ban xithrius").unwrap().unwrap();
+
+        let err = ban.send(&client, &api_url, 1, "1").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The user specified in the user_id field is already banned."
+        );
+
+        let vip = ModerationCommand::parse("/vip xithrius").unwrap().unwrap();
+
+        assert_eq!(
+            vip.send(&client, &api_url, 1, "1").await.unwrap(),
+            "Added xithrius as a VIP."
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+/// Appended to a message that Twitch rejected as a duplicate, so that it is no longer the same as the last one.
+const DUPLICATE_SUFFIX: &str = " \u{E0000}";
+
+/// Why Twitch did not let a message through, keyed on the `msg-id` tag of a `NOTICE`.
+/// <https://dev.twitch.tv/docs/irc/msg-id/>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoticeKind {
+    Duplicate,
+    RateLimit,
+    Banned,
+    TimedOut,
+    FollowersOnly,
+    SubsOnly,
+    EmoteOnly,
+    SlowMode,
+    /// The message was not unique (r9k mode).
+    Unique,
+    ChannelSuspended,
+    /// The account needs a verified email or phone number to chat.
+    Verification,
+    /// Any other `msg-id`, which is not about a message being rejected.
+    Other(String),
+}
+
+/// A message from Twitch, sent through the `NOTICE` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+    pub kind: NoticeKind,
+    /// The message that Twitch displays for the notice.
+    pub message: String,
+}
+
+impl Notice {
+    pub fn from_tags<S: BuildHasher>(tags: &HashMap<&str, &str, S>, message: &str) -> Self {
+        let kind = match tags.get("msg-id").copied().unwrap_or_default() {
+            "msg_duplicate" => NoticeKind::Duplicate,
+            "msg_ratelimit" => NoticeKind::RateLimit,
+            "msg_banned" => NoticeKind::Banned,
+            "msg_timedout" => NoticeKind::TimedOut,
+            "msg_followersonly" | "msg_followersonly_followed" | "msg_followersonly_zero" => {
+                NoticeKind::FollowersOnly
+            }
+            "msg_subsonly" => NoticeKind::SubsOnly,
+            "msg_emoteonly" => NoticeKind::EmoteOnly,
+            "msg_slowmode" => NoticeKind::SlowMode,
+            "msg_r9k" => NoticeKind::Unique,
+            "msg_channel_suspended" => NoticeKind::ChannelSuspended,
+            "msg_verified_email" | "msg_requires_verified_phone_number" => NoticeKind::Verification,
+            other => NoticeKind::Other(other.to_string()),
+        };
+
+        Self {
+            kind,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl NoticeKind {
+    /// A short reason for the message being rejected, or `None` if this notice is not about a rejected message.
+    pub const fn reason(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Duplicate => "identical to your previous message",
+            Self::RateLimit => "sent too quickly",
+            Self::Banned => "banned from the channel",
+            Self::TimedOut => "timed out",
+            Self::FollowersOnly => "channel is followers-only",
+            Self::SubsOnly => "channel is subscribers-only",
+            Self::EmoteOnly => "channel is emote-only",
+            Self::SlowMode => "channel is in slow mode",
+            Self::Unique => "message is not unique",
+            Self::ChannelSuspended => "channel is suspended",
+            Self::Verification => "account needs to be verified",
+            Self::Other(_) => return None,
+        })
+    }
+}
+
+/// A message sent by the current user, kept as it was typed so that it can be sent again if Twitch rejects it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingMessage {
+    /// Tells the messages of the current user in a channel apart, until Twitch has confirmed or rejected them.
+    pub id: usize,
+    pub text: String,
+    /// Why Twitch rejected the message, if it did.
+    pub failure: Option<NoticeKind>,
+}
+
+impl OutgoingMessage {
+    pub const fn new(id: usize, text: String) -> Self {
+        Self {
+            id,
+            text,
+            failure: None,
+        }
+    }
+
+    /// The text to send when retrying the message.
+    /// Duplicates have an invisible suffix toggled, so that the retry is never identical to the last message.
+    pub fn retry_text(&self) -> String {
+        if self.failure != Some(NoticeKind::Duplicate) {
+            return self.text.clone();
+        }
+
+        self.text.strip_suffix(DUPLICATE_SUFFIX).map_or_else(
+            || format!("{}{DUPLICATE_SUFFIX}", self.text),
+            ToString::to_string,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notice_from_tags() {
+        let notice = Notice::from_tags(
+            &HashMap::from([("msg-id", "msg_followersonly_zero")]),
+            "This room is in followers-only mode.",
+        );
+
+        assert_eq!(notice.kind, NoticeKind::FollowersOnly);
+        assert_eq!(notice.kind.reason(), Some("channel is followers-only"));
+
+        let notice = Notice::from_tags(&HashMap::from([("msg-id", "host_on")]), "");
+
+        assert_eq!(notice.kind, NoticeKind::Other("host_on".to_string()));
+        assert_eq!(notice.kind.reason(), None);
+    }
+
+    #[test]
+    fn test_retry_duplicate() {
+        let mut outgoing = OutgoingMessage::new(0, "hello".to_string());
+
+        assert_eq!(outgoing.retry_text(), "hello");
+
+        outgoing.failure = Some(NoticeKind::Duplicate);
+        let retry = outgoing.retry_text();
+        assert_eq!(retry, format!("hello{DUPLICATE_SUFFIX}"));
+
+        // Retrying the retry goes back to the original text.
+        outgoing.text = retry;
+        assert_eq!(outgoing.retry_text(), "hello");
+    }
+}
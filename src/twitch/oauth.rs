@@ -1,10 +1,15 @@
 use color_eyre::{eyre::ContextCompat, Result};
 use reqwest::{
     header::{HeaderMap, HeaderValue, AUTHORIZATION},
-    Client,
+    Client, StatusCode,
 };
 use serde::Deserialize;
-use std::sync::OnceLock;
+use std::{sync::OnceLock, time::Duration};
+
+use crate::{
+    handlers::config::TwitchConfig,
+    twitch::login::{expire_session, session_token, set_session_expiry},
+};
 
 #[derive(Deserialize)]
 #[allow(dead_code)]
@@ -16,45 +21,120 @@ pub struct ClientId {
     pub expires_in: i32,
 }
 
-pub async fn get_twitch_client_id(token: Option<&str>) -> Result<&ClientId> {
-    static TWITCH_CLIENT_ID: OnceLock<ClientId> = OnceLock::new();
+/// Strips the `oauth:` prefix if it exists
+pub fn strip_oauth_prefix(token: &str) -> &str {
+    token.strip_prefix("oauth:").unwrap_or(token)
+}
 
-    if let Some(id) = TWITCH_CLIENT_ID.get() {
-        return Ok(id);
+/// The token with the `oauth:` prefix added if it has none at all, which IRC needs but is easily left out.
+/// Tokens with a mistyped prefix are returned as they are, for them to be reported.
+pub fn with_oauth_prefix(token: &str) -> String {
+    if token.is_empty() || token.to_lowercase().starts_with("oauth") {
+        token.to_string()
+    } else {
+        format!("oauth:{token}")
+    }
+}
+
+/// Problems with the format of a token that can be found without asking Twitch,
+/// being the reason the token is not valid.
+pub fn check_token_format(token: &str) -> Result<(), &'static str> {
+    let Some(token) = token.strip_prefix("oauth:") else {
+        return Err(if token.to_lowercase().starts_with("oauth") {
+            "has to start with `oauth:`, in lowercase and with a colon"
+        } else {
+            "is missing the `oauth:` prefix at the start"
+        });
+    };
+
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("is malformed, as it should only contain letters and numbers after `oauth:`");
     }
 
-    let token = token.context("Twitch token is empty")?;
+    Ok(())
+}
 
-    // Strips the `oauth:` prefix if it exists
-    let token = token.strip_prefix("oauth:").unwrap_or(token);
+/// If the request failed because Twitch did not accept the token.
+pub fn is_unauthorized(err: &color_eyre::Report) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        == Some(StatusCode::UNAUTHORIZED)
+}
 
-    let client = Client::new();
+/// If the request failed because Twitch could not be reached.
+pub fn is_connection_error(err: &color_eyre::Report) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|err| err.is_connect() || err.is_timeout())
+}
 
-    let data = client
-        .get("https://id.twitch.tv/oauth2/validate")
+/// The token to authenticate with, being the one from `twt login` or the token command if either is in use,
+/// refreshed beforehand if it has expired.
+pub async fn current_token(twitch_config: &TwitchConfig) -> Result<Option<String>> {
+    Ok(session_token()
+        .await?
+        .or_else(|| twitch_config.token.clone()))
+}
+
+/// Information about the token, which fails if it is invalid or has expired.
+/// <https://dev.twitch.tv/docs/authentication/validate-tokens/>
+pub async fn validate_token(auth_url: &str, token: &str) -> Result<ClientId> {
+    let token = strip_oauth_prefix(token);
+
+    let client_id = Client::new()
+        .get(format!("{auth_url}/validate"))
         .header(AUTHORIZATION, &format!("OAuth {token}"))
         .send()
         .await?
-        .error_for_status()
-        .unwrap();
+        .error_for_status()?
+        .json::<ClientId>()
+        .await?;
+
+    set_session_expiry(
+        token,
+        Duration::from_secs(client_id.expires_in.max(0) as u64),
+    );
+
+    Ok(client_id)
+}
+
+pub async fn get_twitch_client_id(twitch_config: &TwitchConfig) -> Result<&ClientId> {
+    static TWITCH_CLIENT_ID: OnceLock<ClientId> = OnceLock::new();
+
+    if let Some(id) = TWITCH_CLIENT_ID.get() {
+        return Ok(id);
+    }
+
+    let token = current_token(twitch_config)
+        .await?
+        .context("Twitch token is empty")?;
+
+    let client_id = match validate_token(&twitch_config.auth_url, &token).await {
+        Ok(client_id) => client_id,
+        // Tokens from `twt login` or the token command can be renewed, and validated again.
+        Err(_) if expire_session() => {
+            let token = current_token(twitch_config)
+                .await?
+                .context("Twitch token is empty")?;
 
-    let client_id = data.json::<ClientId>().await?;
+            validate_token(&twitch_config.auth_url, &token).await?
+        }
+        Err(err) => return Err(err),
+    };
 
     Ok(TWITCH_CLIENT_ID.get_or_init(|| client_id))
 }
 
-pub async fn get_twitch_client(oauth_token: Option<&str>) -> Result<Client> {
-    let token = oauth_token
-        .context("Twitch token is empty")?
-        .strip_prefix("oauth:")
-        .context("token does not start with `oauth:`")?;
+pub async fn get_twitch_client(twitch_config: &TwitchConfig) -> Result<Client> {
+    let client_id = &get_twitch_client_id(twitch_config).await?.client_id;
 
-    let client_id = &get_twitch_client_id(Some(token)).await?.client_id;
+    let token = current_token(twitch_config)
+        .await?
+        .context("Twitch token is empty")?;
 
     let mut headers = HeaderMap::new();
     headers.insert(
         AUTHORIZATION,
-        HeaderValue::from_str(&format!("Bearer {token}"))?,
+        HeaderValue::from_str(&format!("Bearer {}", strip_oauth_prefix(&token)))?,
     );
     headers.insert("Client-Id", HeaderValue::from_str(client_id)?);
 
@@ -71,9 +151,9 @@ pub struct ChannelList {
     data: Vec<Channel>,
 }
 
-pub async fn get_channel_id(client: &Client, channel: &str) -> Result<i32> {
+pub async fn get_channel_id(client: &Client, api_url: &str, channel: &str) -> Result<i32> {
     Ok(client
-        .get(format!("https://api.twitch.tv/helix/users?login={channel}",))
+        .get(format!("{api_url}/users?login={channel}",))
         .send()
         .await?
         .error_for_status()?
@@ -85,3 +165,40 @@ pub async fn get_channel_id(client: &Client, channel: &str) -> Result<i32> {
         .id
         .parse()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_token_format() {
+        assert_eq!(check_token_format("oauth:abcdef0123456789"), Ok(()));
+        assert_eq!(
+            check_token_format("abcdef0123456789"),
+            Err("is missing the `oauth:` prefix at the start")
+        );
+        assert_eq!(
+            check_token_format("OAuth abcdef0123456789"),
+            Err("has to start with `oauth:`, in lowercase and with a colon")
+        );
+        assert!(check_token_format("oauth:oauth:abcdef").is_err());
+        assert!(check_token_format("oauth:").is_err());
+    }
+
+    #[test]
+    fn test_with_oauth_prefix() {
+        assert_eq!(
+            with_oauth_prefix("abcdef0123456789"),
+            "oauth:abcdef0123456789"
+        );
+        assert_eq!(
+            with_oauth_prefix("oauth:abcdef0123456789"),
+            "oauth:abcdef0123456789"
+        );
+        assert_eq!(
+            with_oauth_prefix("OAuth abcdef0123456789"),
+            "OAuth abcdef0123456789"
+        );
+        assert_eq!(with_oauth_prefix(""), "");
+    }
+}
@@ -0,0 +1,383 @@
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::{eyre::bail, Result};
+use log::warn;
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use serde_with::{serde_as, DisplayFromStr};
+use tokio::sync::oneshot::Receiver as OSReceiver;
+
+use crate::{
+    handlers::config::TwitchConfig,
+    twitch::{
+        channel_event::{Poll, Prediction},
+        moderation::helix_error,
+        oauth::{get_channel_id, get_twitch_client},
+        scopes::has_scope,
+    },
+};
+
+/// Limits that Twitch puts on polls and predictions.
+/// <https://dev.twitch.tv/docs/api/reference/#create-poll>
+pub const POLL_TITLE_LIMIT: usize = 60;
+pub const POLL_CHOICES: (usize, usize) = (2, 5);
+pub const POLL_DURATION: (u64, u64) = (15, 1800);
+pub const PREDICTION_TITLE_LIMIT: usize = 45;
+pub const PREDICTION_OUTCOMES: (usize, usize) = (2, 10);
+pub const PREDICTION_WINDOW: (u64, u64) = (30, 1800);
+/// Limit of the title of each poll choice and prediction outcome.
+pub const OPTION_TITLE_LIMIT: usize = 25;
+
+/// How long polls and predictions are still shown for once they have ended, along with their result.
+const RESULT_SHOWN_FOR: Duration = Duration::seconds(60);
+
+/// Either scope lets the broadcaster see their polls, the latter also lets them be managed.
+pub const POLL_SCOPES: [&str; 2] = ["channel:read:polls", "channel:manage:polls"];
+pub const PREDICTION_SCOPES: [&str; 2] = ["channel:read:predictions", "channel:manage:predictions"];
+
+/// Helix does not send when voting ends, only when it started and for how long.
+#[serde_as]
+#[derive(Deserialize)]
+struct HelixPoll {
+    #[serde(flatten)]
+    poll: Poll,
+    #[serde_as(as = "DisplayFromStr")]
+    started_at: DateTime<Utc>,
+    duration: i64,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+struct HelixPrediction {
+    #[serde(flatten)]
+    prediction: Prediction,
+    #[serde_as(as = "DisplayFromStr")]
+    created_at: DateTime<Utc>,
+    prediction_window: i64,
+}
+
+#[derive(Deserialize)]
+struct HelixList<T> {
+    data: Vec<T>,
+}
+
+/// The most recent poll and prediction of a channel, as of the last check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Votes {
+    pub poll: Option<Poll>,
+    pub prediction: Option<Prediction>,
+}
+
+/// If a poll or prediction is still going on, or ended recently enough for its result to be shown.
+pub fn is_shown(ended_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    ended_at.map_or(true, |ended_at| now - ended_at < RESULT_SHOWN_FOR)
+}
+
+/// Time left until a deadline, such as `1:05 left`.
+pub fn countdown(until: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (until - now).num_seconds().max(0);
+
+    format!("{}:{:02} left", seconds / 60, seconds % 60)
+}
+
+/// The share of the total that an amount is, rounded down to a whole percentage.
+pub fn percentage(amount: u64, total: u64) -> u64 {
+    (amount * 100).checked_div(total).unwrap_or_default()
+}
+
+/// The most recent item of a Helix endpoint that lists polls or predictions.
+async fn get_latest<T: DeserializeOwned>(client: &Client, url: &str) -> Result<Option<T>> {
+    Ok(client
+        .get(url)
+        .query(&[("first", 1)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<HelixList<T>>()
+        .await?
+        .data
+        .into_iter()
+        .next())
+}
+
+/// The most recent poll and prediction of a channel, which only its broadcaster can see through Helix.
+/// <https://dev.twitch.tv/docs/api/reference/#get-polls>
+pub async fn get_votes(client: &Client, api_url: &str, broadcaster_id: i32) -> Result<Votes> {
+    let poll = get_latest::<HelixPoll>(
+        client,
+        &format!("{api_url}/polls?broadcaster_id={broadcaster_id}"),
+    )
+    .await?
+    .map(
+        |HelixPoll {
+             mut poll,
+             started_at,
+             duration,
+         }| {
+            poll.ends_at = Some(started_at + Duration::seconds(duration));
+            poll
+        },
+    );
+
+    let prediction = get_latest::<HelixPrediction>(
+        client,
+        &format!("{api_url}/predictions?broadcaster_id={broadcaster_id}"),
+    )
+    .await?
+    .map(
+        |HelixPrediction {
+             mut prediction,
+             created_at,
+             prediction_window,
+         }| {
+            prediction.locks_at = Some(created_at + Duration::seconds(prediction_window));
+            prediction
+        },
+    );
+
+    Ok(Votes { poll, prediction })
+}
+
+/// If the token can see the polls or predictions of its own channel.
+pub fn can_read_votes(twitch_config: &TwitchConfig) -> bool {
+    POLL_SCOPES
+        .iter()
+        .chain(&PREDICTION_SCOPES)
+        .any(|scope| has_scope(twitch_config, scope))
+}
+
+/// Checks the poll and prediction of a channel in the background.
+/// The receiver is closed without a value if the check failed.
+pub fn query_votes(twitch_config: &TwitchConfig, channel: String) -> OSReceiver<Votes> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let twitch_config = twitch_config.clone();
+
+    tokio::spawn(async move {
+        let votes = async {
+            let client = get_twitch_client(&twitch_config).await?;
+            let broadcaster_id = get_channel_id(&client, &twitch_config.api_url, &channel).await?;
+
+            get_votes(&client, &twitch_config.api_url, broadcaster_id).await
+        };
+
+        match votes.await {
+            Ok(votes) => {
+                if tx.send(votes).is_err() {
+                    warn!("Unable to send polls and predictions to main thread.");
+                }
+            }
+            Err(e) => {
+                warn!("Unable to get polls and predictions of {channel}: {e}");
+            }
+        }
+    });
+
+    rx
+}
+
+/// Polls and predictions that the broadcaster can run in their own channel.
+/// <https://dev.twitch.tv/docs/api/reference/#create-poll>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoteCommand {
+    CreatePoll {
+        title: String,
+        choices: Vec<String>,
+        /// Seconds that voting is open for.
+        duration: u64,
+    },
+    /// Ends the poll with this ID early, with its results still being shown.
+    EndPoll(String),
+    CreatePrediction {
+        title: String,
+        outcomes: Vec<String>,
+        /// Seconds that predictions are taken for.
+        window: u64,
+    },
+    LockPrediction(String),
+    /// Resolves the prediction with the first ID, the second one being the outcome that won.
+    ResolvePrediction(String, String),
+    /// Cancels the prediction with this ID, refunding the channel points.
+    CancelPrediction(String),
+}
+
+impl VoteCommand {
+    /// What happened, once the command went through.
+    const fn description(&self) -> &'static str {
+        match self {
+            Self::CreatePoll { .. } => "Started the poll.",
+            Self::EndPoll(_) => "Ended the poll.",
+            Self::CreatePrediction { .. } => "Started the prediction.",
+            Self::LockPrediction(_) => "Locked the prediction.",
+            Self::ResolvePrediction(..) => "Resolved the prediction.",
+            Self::CancelPrediction(_) => "Canceled the prediction.",
+        }
+    }
+
+    const fn scope(&self) -> &'static str {
+        match self {
+            Self::CreatePoll { .. } | Self::EndPoll(_) => POLL_SCOPES[1],
+            _ => PREDICTION_SCOPES[1],
+        }
+    }
+
+    /// Sends the command through the client, returning what happened or the reason Twitch gave for rejecting it.
+    async fn send(&self, client: &Client, api_url: &str, broadcaster_id: i32) -> Result<String> {
+        let broadcaster_id = broadcaster_id.to_string();
+
+        let request = match self {
+            Self::CreatePoll {
+                title,
+                choices,
+                duration,
+            } => client.post(format!("{api_url}/polls")).json(&json!({
+                "broadcaster_id": broadcaster_id,
+                "title": title,
+                "choices": choices.iter().map(|title| json!({ "title": title })).collect::<Vec<_>>(),
+                "duration": duration,
+            })),
+            Self::EndPoll(id) => client.patch(format!("{api_url}/polls")).json(&json!({
+                "broadcaster_id": broadcaster_id,
+                "id": id,
+                "status": "TERMINATED",
+            })),
+            Self::CreatePrediction {
+                title,
+                outcomes,
+                window,
+            } => client.post(format!("{api_url}/predictions")).json(&json!({
+                "broadcaster_id": broadcaster_id,
+                "title": title,
+                "outcomes": outcomes.iter().map(|title| json!({ "title": title })).collect::<Vec<_>>(),
+                "prediction_window": window,
+            })),
+            Self::LockPrediction(id) => client.patch(format!("{api_url}/predictions")).json(&json!({
+                "broadcaster_id": broadcaster_id,
+                "id": id,
+                "status": "LOCKED",
+            })),
+            Self::ResolvePrediction(id, winning_outcome_id) => {
+                client.patch(format!("{api_url}/predictions")).json(&json!({
+                    "broadcaster_id": broadcaster_id,
+                    "id": id,
+                    "status": "RESOLVED",
+                    "winning_outcome_id": winning_outcome_id,
+                }))
+            }
+            Self::CancelPrediction(id) => client.patch(format!("{api_url}/predictions")).json(&json!({
+                "broadcaster_id": broadcaster_id,
+                "id": id,
+                "status": "CANCELED",
+            })),
+        };
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            bail!(helix_error(response).await);
+        }
+
+        Ok(self.description().to_string())
+    }
+
+    /// Executes the command in a channel, which Twitch only allows for its broadcaster.
+    pub async fn execute(&self, twitch_config: &TwitchConfig, channel: &str) -> Result<String> {
+        if !has_scope(twitch_config, self.scope()) {
+            bail!("the token is missing the `{}` scope", self.scope());
+        }
+
+        let api_url = &twitch_config.api_url;
+
+        let client = get_twitch_client(twitch_config).await?;
+
+        let broadcaster_id = get_channel_id(&client, api_url, channel).await?;
+
+        self.send(&client, api_url, broadcaster_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::twitch::mock_server::mock_server;
+
+    #[test]
+    fn test_countdown() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(countdown(now + Duration::seconds(65), now), "1:05 left");
+        assert_eq!(countdown(now - Duration::seconds(5), now), "0:00 left");
+    }
+
+    #[test]
+    fn test_is_shown() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+
+        assert!(is_shown(None, now));
+        assert!(is_shown(Some(now - Duration::seconds(30)), now));
+        assert!(!is_shown(Some(now - Duration::seconds(90)), now));
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(1, 3), 33);
+        assert_eq!(percentage(0, 0), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_votes() {
+        let api_url = mock_server(vec![
+            (
+                "200 OK",
+                r#"{"data":[{"id":"ed961efd-8a3f-4cf5-a9d0-e616c590cd2a","broadcaster_id":"55696719","broadcaster_name":"TwitchDev","broadcaster_login":"twitchdev","title":"Heads or Tails?","choices":[{"id":"4c123012-1351-4f33-84b7-43856e7a0f47","title":"Heads","votes":0,"channel_points_votes":0,"bits_votes":0},{"id":"279087e3-54a7-467e-bcd0-c1393fcea4f0","title":"Tails","votes":0,"channel_points_votes":0,"bits_votes":0}],"bits_voting_enabled":false,"bits_per_vote":0,"channel_points_voting_enabled":false,"channel_points_per_vote":0,"status":"ACTIVE","duration":1800,"started_at":"2021-03-19T06:08:33.871278372Z"}],"pagination":{}}"#,
+            ),
+            ("200 OK", r#"{"data":[],"pagination":{}}"#),
+        ])
+        .await;
+
+        let votes = get_votes(&Client::new(), &api_url, 55_696_719)
+            .await
+            .unwrap();
+
+        let poll = votes.poll.unwrap();
+
+        assert_eq!(poll.title, "Heads or Tails?");
+        assert_eq!(
+            poll.ends_at,
+            Some(
+                Utc.with_ymd_and_hms(2021, 3, 19, 6, 38, 33).unwrap()
+                    + Duration::nanoseconds(871_278_372)
+            )
+        );
+        assert!(!poll.ended());
+        assert_eq!(votes.prediction, None);
+    }
+
+    #[tokio::test]
+    async fn test_send_reports_helix_errors() {
+        let api_url = mock_server(
+            vec![(
+                "400 Bad Request",
+                r#"{"error":"Bad Request","status":400,"message":"The broadcaster already has an active poll."}"#,
+            )],
+        )
+        .await;
+
+        let command = VoteCommand::CreatePoll {
+            title: "Heads or Tails?".to_string(),
+            choices: vec!["Heads".to_string(), "Tails".to_string()],
+            duration: 60,
+        };
+
+        assert_eq!(
+            command
+                .send(&Client::new(), &api_url, 1)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "The broadcaster already has an active poll."
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+use irc::proto::Message;
+use tokio::time::Instant;
+
+/// Twitch counts sent messages over a sliding window of this length.
+/// <https://dev.twitch.tv/docs/irc/#rate-limits>
+const MESSAGE_WINDOW: Duration = Duration::from_secs(30);
+
+/// Messages that can be sent within the window by a regular user.
+const USER_LIMIT: usize = 20;
+
+/// Messages that can be sent within the window in channels where the user is a moderator or the broadcaster.
+const MODERATOR_LIMIT: usize = 100;
+
+/// What the user should know about messages waiting to be sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStatus {
+    pub pending: usize,
+    /// When the first queued message is going to be sent.
+    pub next_send: Option<std::time::Instant>,
+}
+
+impl QueueStatus {
+    /// A short description of the queue, or `None` if nothing is waiting to be sent.
+    pub fn description(&self) -> Option<String> {
+        if self.pending == 0 {
+            return None;
+        }
+
+        let seconds = self.next_send.map_or(0, |next_send| {
+            next_send
+                .saturating_duration_since(std::time::Instant::now())
+                .as_millis()
+                .div_ceil(1000)
+        });
+
+        Some(format!("{} queued, next in {seconds}s", self.pending))
+    }
+}
+
+/// Holds back outgoing messages so that Twitch's rate limits are never exceeded,
+/// sending them in order once there is room for them.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    /// When the messages within the current window were sent, from oldest to newest.
+    sent: VecDeque<Instant>,
+    /// Messages waiting to be sent, along with the channel they are sent to.
+    queue: VecDeque<(String, Message)>,
+    /// Channels where the user is a moderator or the broadcaster.
+    elevated: HashSet<String>,
+}
+
+impl RateLimiter {
+    pub fn set_elevated(&mut self, channel: &str, elevated: bool) {
+        if elevated {
+            self.elevated.insert(channel.to_string());
+        } else {
+            self.elevated.remove(channel);
+        }
+    }
+
+    fn limit(&self, channel: &str) -> usize {
+        if self.elevated.contains(channel) {
+            MODERATOR_LIMIT
+        } else {
+            USER_LIMIT
+        }
+    }
+
+    pub fn push(&mut self, channel: String, message: Message) {
+        self.queue.push_back((channel, message));
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while self
+            .sent
+            .front()
+            .is_some_and(|&sent| sent + MESSAGE_WINDOW <= now)
+        {
+            self.sent.pop_front();
+        }
+    }
+
+    /// When the first queued message can be sent, or `None` if the queue is empty.
+    pub fn next_send(&self, now: Instant) -> Option<Instant> {
+        let (channel, _) = self.queue.front()?;
+
+        let recent = self
+            .sent
+            .iter()
+            .filter(|&&sent| sent + MESSAGE_WINDOW > now)
+            .collect::<Vec<_>>();
+
+        let limit = self.limit(channel);
+
+        if recent.len() < limit {
+            Some(now)
+        } else {
+            Some(*recent[recent.len() - limit] + MESSAGE_WINDOW)
+        }
+    }
+
+    /// Takes every queued message that can be sent right now along with its channel, marking them as sent.
+    pub fn ready(&mut self, now: Instant) -> Vec<(String, Message)> {
+        self.prune(now);
+
+        let mut ready = vec![];
+
+        while let Some((channel, _)) = self.queue.front() {
+            if self.sent.len() >= self.limit(channel) {
+                break;
+            }
+
+            if let Some(queued) = self.queue.pop_front() {
+                self.sent.push_back(now);
+                ready.push(queued);
+            }
+        }
+
+        ready
+    }
+
+    pub fn status(&self, now: Instant) -> QueueStatus {
+        QueueStatus {
+            pending: self.queue.len(),
+            next_send: self.next_send(now).map(Instant::into_std),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use irc::proto::Command;
+
+    use super::*;
+
+    fn privmsg(text: &str) -> Message {
+        Command::PRIVMSG("#xithrius".to_string(), text.to_string()).into()
+    }
+
+    #[test]
+    fn test_messages_queued_over_limit() {
+        let mut rate_limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        for i in 0..25 {
+            rate_limiter.push("xithrius".to_string(), privmsg(&i.to_string()));
+        }
+
+        assert_eq!(rate_limiter.ready(now).len(), USER_LIMIT);
+        assert_eq!(rate_limiter.status(now).pending, 5);
+        assert_eq!(rate_limiter.next_send(now), Some(now + MESSAGE_WINDOW));
+
+        assert!(rate_limiter.ready(now + Duration::from_secs(29)).is_empty());
+        assert_eq!(rate_limiter.ready(now + MESSAGE_WINDOW).len(), 5);
+        assert_eq!(rate_limiter.next_send(now + MESSAGE_WINDOW), None);
+    }
+
+    #[test]
+    fn test_elevated_limit() {
+        let mut rate_limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        rate_limiter.set_elevated("xithrius", true);
+
+        for i in 0..25 {
+            rate_limiter.push("xithrius".to_string(), privmsg(&i.to_string()));
+        }
+
+        assert_eq!(rate_limiter.ready(now).len(), 25);
+        assert_eq!(rate_limiter.status(now), QueueStatus::default());
+    }
+}
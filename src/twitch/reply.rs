@@ -0,0 +1,83 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+/// The message that a chat message is replying to.
+/// <https://dev.twitch.tv/docs/irc/tags/#privmsg-tags>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplyParent {
+    /// ID of the message being replied to.
+    pub message_id: String,
+    /// ID of the top-level message that started the reply thread.
+    pub thread_id: String,
+    /// Login name of the author of the message being replied to.
+    pub user_login: String,
+    /// Text of the message being replied to.
+    pub body: String,
+}
+
+impl ReplyParent {
+    /// Reply tags are only present if the message is a reply,
+    /// with the thread tags falling back to the parent if they are missing.
+    pub fn from_tags<S: BuildHasher>(tags: &HashMap<&str, &str, S>) -> Option<Self> {
+        let message_id = (*tags.get("reply-parent-msg-id")?).to_string();
+
+        let thread_id = tags
+            .get("reply-thread-parent-msg-id")
+            .map_or_else(|| message_id.clone(), ToString::to_string);
+
+        Some(Self {
+            message_id,
+            thread_id,
+            user_login: tags
+                .get("reply-parent-user-login")
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            body: tags
+                .get("reply-parent-msg-body")
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_parent_from_tags() {
+        let tags = HashMap::from([
+            ("id", "c"),
+            ("reply-parent-msg-id", "b"),
+            ("reply-parent-user-login", "xithrius"),
+            ("reply-parent-msg-body", "hello there"),
+            ("reply-thread-parent-msg-id", "a"),
+        ]);
+
+        assert_eq!(
+            ReplyParent::from_tags(&tags),
+            Some(ReplyParent {
+                message_id: "b".to_string(),
+                thread_id: "a".to_string(),
+                user_login: "xithrius".to_string(),
+                body: "hello there".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_reply_parent_without_tags() {
+        let tags = HashMap::from([("id", "c"), ("user-id", "123")]);
+
+        assert_eq!(ReplyParent::from_tags(&tags), None);
+    }
+
+    #[test]
+    fn test_reply_thread_defaults_to_parent() {
+        let tags = HashMap::from([("reply-parent-msg-id", "b")]);
+
+        let reply_parent = ReplyParent::from_tags(&tags).unwrap();
+
+        assert_eq!(reply_parent.thread_id, "b");
+        assert!(reply_parent.user_login.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+/// The chat settings of a channel, as sent through the `ROOMSTATE` command.
+/// <https://dev.twitch.tv/docs/irc/tags/#roomstate-tags>
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomState {
+    /// If only emotes are allowed in chat.
+    pub emote_only: bool,
+    /// The amount of minutes a user has to follow the channel before chatting, if enabled.
+    pub followers_only: Option<u64>,
+    /// If only subscribers are allowed to chat.
+    pub subs_only: bool,
+    /// The amount of seconds users have to wait between messages.
+    pub slow: u64,
+    /// If messages must be unique (r9k mode).
+    pub r9k: bool,
+}
+
+impl RoomState {
+    /// Twitch sends the full room state on join, then only the tags that changed.
+    /// Unknown or missing tags leave the current value untouched.
+    pub fn update<S: BuildHasher>(&mut self, tags: &HashMap<&str, &str, S>) {
+        for (name, value) in tags {
+            match *name {
+                "emote-only" => self.emote_only = *value == "1",
+                "followers-only" => self.followers_only = value.parse().ok(),
+                "subs-only" => self.subs_only = *value == "1",
+                "slow" => self.slow = value.parse().unwrap_or_default(),
+                "r9k" => self.r9k = *value == "1",
+                _ => (),
+            }
+        }
+    }
+
+    /// Human readable descriptions of every restriction currently active.
+    pub fn descriptions(&self) -> Vec<String> {
+        let mut descriptions = vec![];
+
+        if self.emote_only {
+            descriptions.push("The channel is emote-only.".to_string());
+        }
+
+        if self.followers_only.is_some() {
+            descriptions.push("The channel is followers-only.".to_string());
+        }
+
+        if self.subs_only {
+            descriptions.push("The channel is subscribers-only.".to_string());
+        }
+
+        if self.slow != 0 {
+            descriptions.push(format!("The channel has a {}s slowmode.", self.slow));
+        }
+
+        if self.r9k {
+            descriptions.push("The channel is in unique-chat mode.".to_string());
+        }
+
+        descriptions
+    }
+
+    /// Short labels of every restriction currently active, to be shown in the chat title.
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels = vec![];
+
+        if self.emote_only {
+            labels.push("Emote-only".to_string());
+        }
+
+        match self.followers_only {
+            Some(0) => labels.push("Followers-only".to_string()),
+            Some(minutes) => labels.push(format!("Followers-only {minutes}m")),
+            None => (),
+        }
+
+        if self.subs_only {
+            labels.push("Subs-only".to_string());
+        }
+
+        if self.slow != 0 {
+            labels.push(format!("Slow {}s", self.slow));
+        }
+
+        if self.r9k {
+            labels.push("R9K".to_string());
+        }
+
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_full_room_state() {
+        let tags = HashMap::from([
+            ("emote-only", "0"),
+            ("followers-only", "10"),
+            ("r9k", "0"),
+            ("slow", "30"),
+            ("subs-only", "1"),
+        ]);
+
+        let mut room_state = RoomState::default();
+        room_state.update(&tags);
+
+        assert_eq!(
+            room_state,
+            RoomState {
+                emote_only: false,
+                followers_only: Some(10),
+                subs_only: true,
+                slow: 30,
+                r9k: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_update_partial_room_state() {
+        let mut room_state = RoomState {
+            slow: 30,
+            ..RoomState::default()
+        };
+
+        room_state.update(&HashMap::from([
+            ("followers-only", "-1"),
+            ("emote-only", "1"),
+        ]));
+
+        assert!(room_state.emote_only);
+        assert_eq!(room_state.followers_only, None);
+        assert_eq!(room_state.slow, 30);
+    }
+
+    #[test]
+    fn test_labels() {
+        let room_state = RoomState {
+            followers_only: Some(10),
+            slow: 30,
+            r9k: true,
+            ..RoomState::default()
+        };
+
+        assert_eq!(
+            room_state.labels(),
+            ["Followers-only 10m", "Slow 30s", "R9K"]
+        );
+        assert!(RoomState::default().labels().is_empty());
+    }
+}
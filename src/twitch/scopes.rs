@@ -0,0 +1,132 @@
+use crate::handlers::config::TwitchConfig;
+
+/// Scope needed to read chat at all, without which the token cannot be used.
+pub const CHAT_READ: &str = "chat:read";
+
+/// Parts of `twt` that need scopes which the token might not have been granted.
+/// <https://dev.twitch.tv/docs/authentication/scopes/>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    SendMessages,
+    Following,
+    UserEmotes,
+    Whispers,
+    Moderation,
+}
+
+impl Feature {
+    pub const ALL: [Self; 5] = [
+        Self::SendMessages,
+        Self::Following,
+        Self::UserEmotes,
+        Self::Whispers,
+        Self::Moderation,
+    ];
+
+    pub const fn scopes(self) -> &'static [&'static str] {
+        match self {
+            Self::SendMessages => &["chat:edit"],
+            Self::Following => &["user:read:follows"],
+            Self::UserEmotes => &["user:read:emotes"],
+            Self::Whispers => &["user:manage:whispers"],
+            Self::Moderation => &[
+                "moderator:manage:banned_users",
+                "moderator:manage:chat_messages",
+                "moderator:manage:chat_settings",
+                "moderator:manage:warnings",
+                "moderator:manage:shield_mode",
+                "channel:manage:moderators",
+                "channel:manage:vips",
+            ],
+        }
+    }
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::SendMessages => "Sending messages",
+            Self::Following => "The followed channels list",
+            Self::UserEmotes => "Your own Twitch emotes",
+            Self::Whispers => "Sending whispers",
+            Self::Moderation => "Moderation commands",
+        }
+    }
+
+    /// If the token was granted every scope this feature needs.
+    pub fn is_enabled(self, twitch_config: &TwitchConfig) -> bool {
+        self.scopes()
+            .iter()
+            .all(|scope| has_scope(twitch_config, scope))
+    }
+}
+
+/// If the token was granted a scope, assuming it was when the scopes are not known.
+pub fn has_scope(twitch_config: &TwitchConfig, scope: &str) -> bool {
+    twitch_config
+        .granted_scopes
+        .as_ref()
+        .map_or(true, |scopes| scopes.iter().any(|s| s == scope))
+}
+
+/// Every feature that is missing some of its scopes, and which scopes those are.
+/// Own emotes are only reported when Twitch emotes are enabled.
+pub fn missing_scopes(
+    twitch_config: &TwitchConfig,
+    twitch_emotes: bool,
+) -> Vec<(Feature, Vec<&'static str>)> {
+    Feature::ALL
+        .into_iter()
+        .filter(|feature| *feature != Feature::UserEmotes || twitch_emotes)
+        .filter_map(|feature| {
+            let missing = feature
+                .scopes()
+                .iter()
+                .copied()
+                .filter(|scope| !has_scope(twitch_config, scope))
+                .collect::<Vec<&str>>();
+
+            (!missing.is_empty()).then_some((feature, missing))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn twitch_config(scopes: &[&str]) -> TwitchConfig {
+        TwitchConfig {
+            granted_scopes: Some(scopes.iter().map(ToString::to_string).collect()),
+            ..TwitchConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_missing_scopes() {
+        let config = twitch_config(&[
+            "chat:read",
+            "chat:edit",
+            "user:manage:whispers",
+            "moderator:manage:banned_users",
+        ]);
+
+        let missing = missing_scopes(&config, false);
+
+        assert_eq!(
+            missing.iter().map(|(f, _)| *f).collect::<Vec<Feature>>(),
+            [Feature::Following, Feature::Moderation]
+        );
+        assert_eq!(missing[1].1.len(), Feature::Moderation.scopes().len() - 1);
+        assert!(Feature::Whispers.is_enabled(&config));
+        assert!(!Feature::Following.is_enabled(&config));
+
+        assert_eq!(missing_scopes(&config, true)[1].0, Feature::UserEmotes);
+    }
+
+    #[test]
+    fn test_unknown_scopes() {
+        let config = TwitchConfig::default();
+
+        assert!(missing_scopes(&config, true).is_empty());
+        assert!(has_scope(&config, "user:read:follows"));
+    }
+}
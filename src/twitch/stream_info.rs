@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use log::warn;
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::sync::oneshot::Receiver as OSReceiver;
+
+use crate::{
+    handlers::config::TwitchConfig,
+    twitch::oauth::{get_channel_id, get_twitch_client},
+};
+
+#[derive(Deserialize)]
+struct Stream {
+    title: String,
+    game_name: String,
+    viewer_count: u64,
+    started_at: String,
+}
+
+#[derive(Deserialize)]
+struct StreamList {
+    data: Vec<Stream>,
+}
+
+/// What a live channel is streaming.
+/// <https://dev.twitch.tv/docs/api/reference/#get-streams>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    pub title: String,
+    /// Name of the category, such as `Just Chatting`.
+    pub game_name: String,
+    pub viewer_count: u64,
+    pub started_at: DateTime<Utc>,
+}
+
+/// If a channel is live, as of the last time it was checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum StreamStatus {
+    /// The channel has not been checked yet.
+    #[default]
+    Unknown,
+    Offline,
+    Live(StreamInfo),
+}
+
+impl StreamInfo {
+    /// How long the stream has been live for, such as `2h 05m`.
+    pub fn uptime(&self, now: DateTime<Utc>) -> String {
+        let minutes = (now - self.started_at).num_minutes().max(0);
+
+        if minutes < 60 {
+            format!("{minutes}m")
+        } else {
+            format!("{}h {:02}m", minutes / 60, minutes % 60)
+        }
+    }
+}
+
+impl StreamStatus {
+    fn from_list(list: StreamList) -> Result<Self> {
+        let Some(stream) = list.data.into_iter().next() else {
+            return Ok(Self::Offline);
+        };
+
+        Ok(Self::Live(StreamInfo {
+            title: stream.title,
+            game_name: stream.game_name,
+            viewer_count: stream.viewer_count,
+            started_at: DateTime::parse_from_rfc3339(&stream.started_at)?.with_timezone(&Utc),
+        }))
+    }
+}
+
+/// The stream of a channel, which is only listed while it is live.
+pub async fn get_stream_status(
+    client: &Client,
+    api_url: &str,
+    channel_id: i32,
+) -> Result<StreamStatus> {
+    let list = client
+        .get(format!("{api_url}/streams?user_id={channel_id}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<StreamList>()
+        .await?;
+
+    StreamStatus::from_list(list)
+}
+
+async fn get_channel_stream_status(
+    twitch_config: &TwitchConfig,
+    channel: &str,
+) -> Result<StreamStatus> {
+    let client = get_twitch_client(twitch_config).await?;
+
+    let channel_id = get_channel_id(&client, &twitch_config.api_url, channel).await?;
+
+    get_stream_status(&client, &twitch_config.api_url, channel_id).await
+}
+
+/// Checks if a channel is live in the background.
+/// The receiver is closed without a value if the check failed.
+pub fn query_stream_status(
+    twitch_config: &TwitchConfig,
+    channel: String,
+) -> OSReceiver<StreamStatus> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let twitch_config = twitch_config.clone();
+
+    tokio::spawn(async move {
+        match get_channel_stream_status(&twitch_config, &channel).await {
+            Ok(status) => {
+                if tx.send(status).is_err() {
+                    warn!("Unable to send stream status to main thread.");
+                }
+            }
+            Err(e) => {
+                warn!("Unable to get stream status of {channel}: {e}");
+            }
+        }
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_live_stream_status() {
+        let list = serde_json::from_str::<StreamList>(
+            r#"{"data":[{"id":"40952121085","user_id":"101051819","user_login":"afro","user_name":"Afro","game_id":"32982","game_name":"Grand Theft Auto V","type":"live","title":"Jacob: Digital Den Laptops & Tablets","viewer_count":1490,"started_at":"2021-03-10T03:18:11Z","language":"en","thumbnail_url":"","tag_ids":[],"tags":["English"],"is_mature":false}],"pagination":{}}"#,
+        )
+        .unwrap();
+
+        let StreamStatus::Live(info) = StreamStatus::from_list(list).unwrap() else {
+            panic!("Stream should be live");
+        };
+
+        assert_eq!(info.game_name, "Grand Theft Auto V");
+        assert_eq!(info.viewer_count, 1490);
+        assert_eq!(
+            info.uptime(Utc.with_ymd_and_hms(2021, 3, 10, 5, 23, 0).unwrap()),
+            "2h 04m"
+        );
+        assert_eq!(
+            info.uptime(Utc.with_ymd_and_hms(2021, 3, 10, 3, 20, 0).unwrap()),
+            "1m"
+        );
+    }
+
+    #[test]
+    fn test_offline_stream_status() {
+        let list = serde_json::from_str::<StreamList>(r#"{"data":[],"pagination":{}}"#).unwrap();
+
+        assert_eq!(
+            StreamStatus::from_list(list).unwrap(),
+            StreamStatus::Offline
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::process::Command;
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+
+/// The shell that `twitch.token_command` is run through, so that pipes and arguments work as they would in a terminal.
+fn shell(command: &str) -> Command {
+    #[cfg(windows)]
+    let (program, flag) = ("cmd", "/C");
+    #[cfg(not(windows))]
+    let (program, flag) = ("sh", "-c");
+
+    let mut shell = Command::new(program);
+    shell.args([flag, command]);
+
+    shell
+}
+
+/// Runs `twitch.token_command`, the first line of its output being the token.
+///
+/// Secret stores such as `pass` print the secret on the first line, and usually without the `oauth:` prefix,
+/// which is added if it is missing.
+pub fn run_token_command(command: &str) -> Result<String> {
+    let output = shell(command)
+        .output()
+        .wrap_err_with(|| format!("Unable to run the token command `{command}`"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        match stderr.trim() {
+            "" => bail!("The token command `{command}` failed ({}).", output.status),
+            stderr => bail!(
+                "The token command `{command}` failed ({}): {stderr}",
+                output.status
+            ),
+        }
+    }
+
+    let Ok(stdout) = String::from_utf8(output.stdout) else {
+        bail!("The token command `{command}` did not output valid text.");
+    };
+
+    let token = stdout.lines().next().unwrap_or_default().trim();
+
+    if token.is_empty() {
+        bail!("The token command `{command}` did not output a token.");
+    }
+
+    Ok(if token.starts_with("oauth:") {
+        token.to_string()
+    } else {
+        format!("oauth:{token}")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_command_output() {
+        assert_eq!(run_token_command("echo abc123").unwrap(), "oauth:abc123");
+        assert_eq!(
+            run_token_command("echo oauth:abc123").unwrap(),
+            "oauth:abc123"
+        );
+    }
+
+    #[test]
+    fn test_token_command_failure() {
+        let err = run_token_command("exit 3").unwrap_err().to_string();
+        assert!(
+            err.starts_with("The token command `exit 3` failed"),
+            "{err}"
+        );
+
+        let err = run_token_command("exit 0").unwrap_err().to_string();
+        assert_eq!(err, "The token command `exit 0` did not output a token.");
+    }
+}
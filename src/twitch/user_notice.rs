@@ -0,0 +1,186 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::utils::styles::NO_COLOR;
+
+/// The kind of event that a `USERNOTICE` was sent for, keyed on its `msg-id` tag.
+/// <https://dev.twitch.tv/docs/irc/tags/#usernotice-tags>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserNoticeKind {
+    Sub,
+    /// Cumulative amount of months the user has been subscribed for.
+    Resub(u64),
+    SubGift,
+    /// Amount of subscriptions gifted at once.
+    SubMysteryGift(u64),
+    /// A gifted subscription continued by the user that received it.
+    GiftUpgrade,
+    /// Amount of viewers that came along with the raid.
+    Raid(u64),
+    /// Announcements are highlighted with a color chosen by the moderator.
+    Announcement(Color),
+    /// Amount of bits the user has cheered to reach a new badge tier.
+    BitsBadgeTier(u64),
+    /// Any other `msg-id`, such as rituals.
+    Other(String),
+}
+
+/// A channel event, such as a subscription or a raid, sent through the `USERNOTICE` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserNotice {
+    pub kind: UserNoticeKind,
+    /// The message that Twitch displays for the event, which does not include the user's own message.
+    pub system_message: String,
+}
+
+/// Primary announcements use the channel's accent color, which is not sent through IRC.
+fn announcement_color(color: &str) -> Color {
+    match color {
+        "BLUE" => Color::LightBlue,
+        "GREEN" => Color::LightGreen,
+        "ORANGE" => Color::Rgb(255, 165, 0),
+        _ => Color::LightMagenta,
+    }
+}
+
+impl UserNotice {
+    pub fn from_tags<S: BuildHasher>(tags: &HashMap<&str, &str, S>) -> Option<Self> {
+        let number = |tag: &str| {
+            tags.get(tag)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default()
+        };
+
+        let kind = match *tags.get("msg-id")? {
+            "sub" => UserNoticeKind::Sub,
+            "resub" => UserNoticeKind::Resub(number("msg-param-cumulative-months")),
+            "subgift" => UserNoticeKind::SubGift,
+            "submysterygift" => UserNoticeKind::SubMysteryGift(number("msg-param-mass-gift-count")),
+            "giftpaidupgrade" | "anongiftpaidupgrade" | "primepaidupgrade" => {
+                UserNoticeKind::GiftUpgrade
+            }
+            "raid" => UserNoticeKind::Raid(number("msg-param-viewerCount")),
+            "announcement" => UserNoticeKind::Announcement(announcement_color(
+                tags.get("msg-param-color").copied().unwrap_or_default(),
+            )),
+            "bitsbadgetier" => UserNoticeKind::BitsBadgeTier(number("msg-param-threshold")),
+            other => UserNoticeKind::Other(other.to_string()),
+        };
+
+        Some(Self {
+            kind,
+            system_message: tags
+                .get("system-msg")
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Style of the line describing the event.
+    pub fn style(&self) -> Style {
+        if *NO_COLOR {
+            return Style::default().add_modifier(Modifier::BOLD);
+        }
+
+        let color = match self.kind {
+            UserNoticeKind::Sub | UserNoticeKind::Resub(_) | UserNoticeKind::GiftUpgrade => {
+                Color::LightMagenta
+            }
+            UserNoticeKind::SubGift | UserNoticeKind::SubMysteryGift(_) => Color::LightCyan,
+            UserNoticeKind::Raid(_) => Color::LightYellow,
+            UserNoticeKind::Announcement(color) => color,
+            UserNoticeKind::BitsBadgeTier(_) => Color::LightBlue,
+            UserNoticeKind::Other(_) => Color::Gray,
+        };
+
+        Style::default().fg(color).add_modifier(Modifier::BOLD)
+    }
+
+    /// Style of the message the user attached to the event, only announcements are tinted.
+    pub fn message_style(&self) -> Option<Style> {
+        match self.kind {
+            UserNoticeKind::Announcement(color) if !*NO_COLOR => Some(Style::default().fg(color)),
+            _ => None,
+        }
+    }
+
+    const fn symbol(&self) -> char {
+        match self.kind {
+            UserNoticeKind::Sub | UserNoticeKind::Resub(_) | UserNoticeKind::GiftUpgrade => {
+                '\u{2605}'
+            }
+            UserNoticeKind::SubGift | UserNoticeKind::SubMysteryGift(_) => '\u{2740}',
+            UserNoticeKind::Raid(_) => '\u{2691}',
+            UserNoticeKind::Announcement(_) => '\u{25B6}',
+            UserNoticeKind::BitsBadgeTier(_) => '\u{25C6}',
+            UserNoticeKind::Other(_) => '\u{2022}',
+        }
+    }
+
+    /// Lines describing the event, shown above the message the user attached to it.
+    pub fn to_lines(&self, width: usize) -> Vec<Line<'static>> {
+        let description = if self.system_message.is_empty() {
+            match self.kind {
+                UserNoticeKind::Announcement(_) => "Announcement",
+                _ => "Channel event",
+            }
+        } else {
+            &self.system_message
+        };
+
+        textwrap::wrap(&format!("{} {description}", self.symbol()), width)
+            .into_iter()
+            .map(|line| Line::from(Span::styled(line.into_owned(), self.style())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resub_from_tags() {
+        let tags = HashMap::from([
+            ("msg-id", "resub"),
+            ("msg-param-cumulative-months", "6"),
+            ("system-msg", "xithrius subscribed for 6 months!"),
+        ]);
+
+        assert_eq!(
+            UserNotice::from_tags(&tags),
+            Some(UserNotice {
+                kind: UserNoticeKind::Resub(6),
+                system_message: "xithrius subscribed for 6 months!".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_announcement_color() {
+        let tags = HashMap::from([("msg-id", "announcement"), ("msg-param-color", "GREEN")]);
+
+        let notice = UserNotice::from_tags(&tags).unwrap();
+
+        assert_eq!(notice.kind, UserNoticeKind::Announcement(Color::LightGreen));
+        assert!(notice.system_message.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_msg_id() {
+        let tags = HashMap::from([("msg-id", "ritual"), ("system-msg", "hello")]);
+
+        assert_eq!(
+            UserNotice::from_tags(&tags).unwrap().kind,
+            UserNoticeKind::Other("ritual".to_string())
+        );
+        assert_eq!(
+            UserNotice::from_tags(&HashMap::from([("login", "foo")])),
+            None
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+use crate::twitch::badges::Badge;
+
+/// How the current user appears in chat.
+///
+/// This is sent through `GLOBALUSERSTATE` after logging in,
+/// and through `USERSTATE` after joining a channel or sending a message in it.
+/// <https://dev.twitch.tv/docs/irc/tags/#userstate-tags>
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserState {
+    /// ID of the current user, which is only sent through `GLOBALUSERSTATE`.
+    pub user_id: Option<String>,
+    pub display_name: Option<String>,
+    /// Chat color as a hex string, or `None` if the user has never chosen one.
+    pub color: Option<String>,
+    pub badges: Vec<Badge>,
+    pub moderator: bool,
+    pub vip: bool,
+    pub broadcaster: bool,
+    /// IDs of the emote sets that the user is allowed to use.
+    pub emote_sets: Vec<String>,
+}
+
+impl UserState {
+    pub fn from_tags<S: BuildHasher>(tags: &HashMap<&str, &str, S>) -> Self {
+        let badges = Badge::from_tags(tags);
+
+        let has_badge = |name: &str| badges.iter().any(|badge| badge.name == name);
+
+        Self {
+            user_id: tags.get("user-id").map(ToString::to_string),
+            display_name: tags
+                .get("display-name")
+                .filter(|name| !name.is_empty())
+                .map(ToString::to_string),
+            color: tags
+                .get("color")
+                .filter(|color| !color.is_empty())
+                .map(ToString::to_string),
+            moderator: tags.get("mod") == Some(&"1") || has_badge("moderator"),
+            vip: tags.contains_key("vip") || has_badge("vip"),
+            broadcaster: has_badge("broadcaster"),
+            badges,
+            emote_sets: tags
+                .get("emote-sets")
+                .map(|sets| {
+                    sets.split(',')
+                        .filter(|set| !set.is_empty())
+                        .map(ToString::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// If the user is allowed to moderate the channel this state was sent for.
+    pub const fn is_moderator(&self) -> bool {
+        self.moderator || self.broadcaster
+    }
+
+    /// If the user is not affected by slow mode in the channel this state was sent for.
+    /// <https://help.twitch.tv/s/article/chat-commands#slow>
+    pub const fn is_privileged(&self) -> bool {
+        self.is_moderator() || self.vip
+    }
+
+    /// The name shown in chat, the same way as it is for messages from other users.
+    pub fn name(&self, login: &str) -> String {
+        self.display_name.as_deref().unwrap_or(login).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moderator_user_state() {
+        let tags = HashMap::from([
+            ("badges", "moderator/1,subscriber/12"),
+            ("color", "#0000FF"),
+            ("display-name", "Xithrius"),
+            ("emote-sets", "0,300374282"),
+            ("mod", "1"),
+        ]);
+
+        let user_state = UserState::from_tags(&tags);
+
+        assert!(user_state.is_moderator());
+        assert!(user_state.is_privileged());
+        assert!(!user_state.vip);
+        assert_eq!(user_state.color.as_deref(), Some("#0000FF"));
+        assert_eq!(user_state.emote_sets, ["0", "300374282"]);
+        assert_eq!(user_state.badges.len(), 2);
+        assert_eq!(user_state.name("xithrius"), "Xithrius");
+    }
+
+    #[test]
+    fn test_broadcaster_without_display_name() {
+        let tags = HashMap::from([("badges", "broadcaster/1"), ("color", "")]);
+
+        let user_state = UserState::from_tags(&tags);
+
+        assert!(user_state.broadcaster);
+        assert!(user_state.is_moderator());
+        assert_eq!(user_state.color, None);
+        assert_eq!(user_state.name("xithrius"), "xithrius");
+    }
+}
@@ -0,0 +1,89 @@
+use color_eyre::{eyre::bail, Result};
+use serde::Serialize;
+
+use crate::{
+    handlers::config::TwitchConfig,
+    twitch::{
+        oauth::{get_channel_id, get_twitch_client, get_twitch_client_id},
+        scopes::Feature,
+    },
+};
+
+#[derive(Serialize)]
+struct WhisperBody<'a> {
+    message: &'a str,
+}
+
+/// Whispers can no longer be sent through IRC, so they are sent through the Helix API instead.
+/// This requires the `user:manage:whispers` scope.
+/// <https://dev.twitch.tv/docs/api/reference/#send-whisper>
+pub async fn send_whisper(
+    twitch_config: &TwitchConfig,
+    recipient: &str,
+    message: &str,
+) -> Result<()> {
+    if !Feature::Whispers.is_enabled(twitch_config) {
+        bail!("the token is missing the `user:manage:whispers` scope");
+    }
+
+    let api_url = &twitch_config.api_url;
+
+    let client = get_twitch_client(twitch_config).await?;
+
+    let from_user_id = &get_twitch_client_id(twitch_config).await?.user_id;
+    let to_user_id = get_channel_id(&client, api_url, recipient).await?;
+
+    client
+        .post(format!(
+            "{api_url}/whispers?from_user_id={from_user_id}&to_user_id={to_user_id}"
+        ))
+        .json(&WhisperBody { message })
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Splits a `/w <user> <message>` command into the recipient and the message,
+/// with the recipient being stripped of a leading `@`.
+pub fn parse_whisper_command(input: &str) -> Option<(String, String)> {
+    let arguments = input
+        .strip_prefix("/w ")
+        .or_else(|| input.strip_prefix("/whisper "))?;
+
+    let (recipient, message) = arguments.trim_start().split_once(' ')?;
+
+    let recipient = recipient.trim_start_matches('@').to_lowercase();
+    let message = message.trim();
+
+    if recipient.is_empty() || message.is_empty() {
+        return None;
+    }
+
+    Some((recipient, message.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_whisper_command() {
+        assert_eq!(
+            parse_whisper_command("/w @Xithrius hello there"),
+            Some(("xithrius".to_string(), "hello there".to_string()))
+        );
+        assert_eq!(
+            parse_whisper_command("/whisper xithrius hi"),
+            Some(("xithrius".to_string(), "hi".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_whisper_command_without_message() {
+        assert_eq!(parse_whisper_command("/w xithrius"), None);
+        assert_eq!(parse_whisper_command("/w xithrius   "), None);
+        assert_eq!(parse_whisper_command("/warn xithrius hi"), None);
+    }
+}
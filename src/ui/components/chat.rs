@@ -1,6 +1,6 @@
 use std::{collections::VecDeque, slice::Iter};
 
-use chrono::Local;
+use chrono::{Local, Utc};
 use tui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -13,6 +13,7 @@ use crate::{
     emotes::SharedEmotes,
     handlers::{
         app::SharedMessages,
+        channels::{ChannelData, SharedChannels},
         config::SharedCompleteConfig,
         data::MessageData,
         filters::SharedFilters,
@@ -22,10 +23,19 @@ use crate::{
             events::{Event, Key},
             scrolling::Scrolling,
         },
+        whispers::SharedWhispers,
     },
     terminal::TerminalAction,
+    twitch::{
+        rate_limit::QueueStatus,
+        scopes::Feature,
+        stream_info::{StreamInfo, StreamStatus},
+        TwitchAction,
+    },
     ui::components::{
-        following::FollowingWidget, ChannelSwitcherWidget, ChatInputWidget, Component,
+        following::FollowingWidget, panes::Panes, reply_thread::ReplyThreadWidget,
+        utils::message_lines, vote_form::VoteFormWidget, votes::VotesWidget,
+        whispers::WhispersWidget, ChannelSwitcherWidget, ChatInputWidget, Component,
         MessageSearchWidget,
     },
     utils::{
@@ -36,11 +46,17 @@ use crate::{
 
 pub struct ChatWidget {
     config: SharedCompleteConfig,
-    messages: SharedMessages,
+    channels: SharedChannels,
+    whispers: SharedWhispers,
+    panes: Panes,
     chat_input: ChatInputWidget,
     channel_input: ChannelSwitcherWidget,
     search_input: MessageSearchWidget,
     following: FollowingWidget,
+    reply_thread: ReplyThreadWidget,
+    whispers_popup: WhispersWidget,
+    votes: VotesWidget,
+    vote_form: VoteFormWidget,
     filters: SharedFilters,
     pub scroll_offset: Scrolling,
     // theme: Theme,
@@ -49,25 +65,41 @@ pub struct ChatWidget {
 impl ChatWidget {
     pub fn new(
         config: SharedCompleteConfig,
-        messages: SharedMessages,
+        channels: SharedChannels,
+        whispers: SharedWhispers,
         storage: &SharedStorage,
         emotes: &SharedEmotes,
         filters: SharedFilters,
     ) -> Self {
-        let chat_input = ChatInputWidget::new(config.clone(), storage.clone(), emotes.clone());
+        let chat_input = ChatInputWidget::new(
+            config.clone(),
+            storage.clone(),
+            emotes.clone(),
+            channels.clone(),
+        );
         let channel_input = ChannelSwitcherWidget::new(config.clone(), storage.clone());
         let search_input = MessageSearchWidget::new(config.clone());
         let following = FollowingWidget::new(config.clone());
+        let reply_thread = ReplyThreadWidget::new(config.clone());
+        let whispers_popup = WhispersWidget::new(config.clone(), whispers.clone());
+        let votes = VotesWidget::new(config.clone(), channels.clone());
+        let vote_form = VoteFormWidget::new(config.clone(), channels.clone());
 
         let scroll_offset = Scrolling::new(config.borrow().frontend.inverted_scrolling);
 
         Self {
             config,
-            messages,
+            channels,
+            whispers,
+            panes: Panes::default(),
             chat_input,
             channel_input,
             search_input,
             following,
+            reply_thread,
+            whispers_popup,
+            votes,
+            vote_form,
             filters,
             scroll_offset,
         }
@@ -79,10 +111,53 @@ impl ChatWidget {
             self.config.borrow().twitch.channel).as_str()).unwrap();
     }
 
+    /// Anonymous users can only read chat, anything that needs a token is disabled.
+    /// The same goes for tokens that were not granted the scope to send messages.
+    fn read_only(&self) -> bool {
+        let config = self.config.borrow();
+
+        config.twitch.anonymous || !Feature::SendMessages.is_enabled(&config.twitch)
+    }
+
+    /// Messages of the channel currently being displayed.
+    fn active_messages(&self) -> SharedMessages {
+        self.channels
+            .borrow()
+            .active()
+            .map(|c| c.messages.clone())
+            .unwrap_or_default()
+    }
+
+    /// The message at the bottom of the chat window, which changes when scrolling.
+    fn selected_message(&self) -> Option<MessageData> {
+        let filters = self.filters.borrow();
+
+        self.active_messages()
+            .borrow()
+            .iter()
+            .filter(|m| !filters.filtered(m))
+            .nth(self.scroll_offset.get_offset())
+            .cloned()
+    }
+
+    /// If the channel is currently visible in one of the panes.
+    pub fn shows_channel(&self, name: &str) -> bool {
+        self.panes.contains(name)
+    }
+
+    /// If the whisper conversation with a user is currently on screen.
+    pub fn shows_whisper_conversation(&self, user: &str) -> bool {
+        self.whispers_popup.shows_conversation(user)
+    }
+
+    pub fn set_queue_status(&self, queue_status: QueueStatus) {
+        self.chat_input.set_queue_status(queue_status);
+    }
+
     pub fn get_messages<'a>(
         &self,
-        frame: &Frame,
         area: Rect,
+        mut scroll: usize,
         messages_data: &'a VecDeque<MessageData>,
     ) -> VecDeque<Line<'a>> {
         // Accounting for not all heights of rows to be the same due to text wrapping,
@@ -96,24 +171,10 @@ impl ChatWidget {
             general_chunk_height -= 2;
         }
 
-        let mut scroll = self.scroll_offset.get_offset();
-
-        // Horizontal chunks represents the list within the main chat window.
-        let h_chunk = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Min(1)])
-            .split(frame.area());
-
-        let message_chunk_width = h_chunk[0].width as usize;
-
         let config = self.config.borrow();
 
         'outer: for data in messages_data {
-            if self
-                .filters
-                .borrow()
-                .contaminated(data.payload.clone().as_str())
-            {
+            if self.filters.borrow().filtered(data) {
                 continue;
             }
 
@@ -132,9 +193,10 @@ impl ChatWidget {
 
             let search = self.search_input.to_string();
 
-            let lines = data.to_vec(
-                &self.config.borrow().frontend,
-                message_chunk_width,
+            let lines = message_lines(
+                data,
+                &config.frontend,
+                area.width,
                 if self.search_input.is_focused() {
                     Some(&search)
                 } else {
@@ -162,47 +224,51 @@ impl ChatWidget {
 
         messages
     }
-}
-
-impl Component for ChatWidget {
-    fn draw(&mut self, f: &mut Frame, area: Option<Rect>) {
-        let r = area.map_or_else(|| f.area(), |a| a);
 
+    /// Draws the messages of a single channel, along with its title and scroll offset.
+    fn draw_pane(&self, f: &mut Frame, area: Rect, channel_name: &str, focused: bool) {
         let config = self.config.borrow();
 
-        let mut v_constraints = vec![Constraint::Min(1)];
-
-        if self.chat_input.is_focused() || self.search_input.is_focused() {
-            v_constraints.push(Constraint::Length(3));
-        }
-
-        let v_chunks_binding = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(self.config.borrow().frontend.margin)
-            .constraints(v_constraints)
-            .split(r);
-
-        let mut v_chunks: Iter<Rect> = v_chunks_binding.iter();
-
-        let first_v_chunk = v_chunks.next().unwrap();
+        let (messages, scroll, moderator, room_state, stream_status) =
+            self.channels.borrow().get(channel_name).map_or_else(
+                || {
+                    (
+                        SharedMessages::default(),
+                        0,
+                        false,
+                        vec![],
+                        StreamStatus::default(),
+                    )
+                },
+                |c| {
+                    (
+                        c.messages.clone(),
+                        // The stored offset of the focused channel is only updated when switching away from it.
+                        if focused {
+                            self.scroll_offset.get_offset()
+                        } else {
+                            c.scroll_offset
+                        },
+                        c.is_moderator(),
+                        c.room_state.labels(),
+                        c.stream_status.clone(),
+                    )
+                },
+            );
 
-        if self.messages.borrow().len() > self.config.borrow().terminal.maximum_messages {
-            self.messages
-                .borrow_mut()
-                .truncate(self.config.borrow().terminal.maximum_messages);
-        }
+        let room_state = room_state.join(", ");
 
-        let messages_data = self.messages.borrow();
+        let messages_data = messages.borrow();
 
-        let messages = self.get_messages(f, *first_v_chunk, &messages_data);
+        let lines = self.get_messages(area, scroll, &messages_data);
 
         let current_time = Local::now()
             .format(&config.frontend.datetime_format)
             .to_string();
 
-        let spans = [
+        let mut spans = vec![
             TitleStyle::Combined("Time", &current_time),
-            TitleStyle::Combined("Channel", config.twitch.channel.as_str()),
+            TitleStyle::Combined("Channel", channel_name),
             TitleStyle::Custom(Span::styled(
                 if self.filters.borrow().reversed() {
                     "retliF"
@@ -223,60 +289,176 @@ impl Component for ChatWidget {
             )),
         ];
 
-        let chat_title = if self.config.borrow().frontend.title_shown {
+        let (viewers, uptime) = match &stream_status {
+            StreamStatus::Live(info) => (info.viewer_count.to_string(), info.uptime(Utc::now())),
+            StreamStatus::Offline | StreamStatus::Unknown => (String::new(), String::new()),
+        };
+
+        match &stream_status {
+            StreamStatus::Live(StreamInfo {
+                title, game_name, ..
+            }) => {
+                spans.push(TitleStyle::Custom(Span::styled(
+                    "Live",
+                    if *NO_COLOR {
+                        Style::default().add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().add_modifier(Modifier::BOLD).fg(Color::Red)
+                    },
+                )));
+                spans.push(TitleStyle::Combined("Title", title));
+
+                if !game_name.is_empty() {
+                    spans.push(TitleStyle::Combined("Category", game_name));
+                }
+
+                spans.push(TitleStyle::Combined("Viewers", &viewers));
+                spans.push(TitleStyle::Combined("Uptime", &uptime));
+            }
+            StreamStatus::Offline => spans.push(TitleStyle::Single("Offline")),
+            StreamStatus::Unknown => {}
+        }
+
+        if !room_state.is_empty() {
+            spans.push(TitleStyle::Combined("Room", &room_state));
+        }
+
+        if self.filters.borrow().user_notices() {
+            spans.push(TitleStyle::Single("Events hidden"));
+        }
+
+        if self.read_only() {
+            spans.push(TitleStyle::Single("Read-only"));
+        } else if moderator {
+            spans.push(TitleStyle::Single("Moderator"));
+        }
+
+        let unread_whispers = self.whispers.borrow().unread();
+
+        // New whispers are only pointed out in the pane that chat input is sent to.
+        if focused && unread_whispers > 0 {
+            spans.push(TitleStyle::Custom(Span::styled(
+                format!("Whispers: {unread_whispers}"),
+                if *NO_COLOR {
+                    Style::default()
+                } else {
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .fg(Color::Magenta)
+                },
+            )));
+        }
+
+        let chat_title = if config.frontend.title_shown {
             Line::from(title_line(&spans, *TITLE_STYLE))
         } else {
             Line::default()
         };
 
+        // Only highlight the focused pane when there are multiple to choose from.
+        let border_style = if focused && self.panes.len() > 1 && !*NO_COLOR {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+
         let mut final_messages = vec![];
 
-        for item in messages {
+        for item in lines {
             final_messages.push(ListItem::new(Text::from(item)));
         }
 
-        let list = if self.config.borrow().frontend.hide_chat_border {
+        let list = if config.frontend.hide_chat_border {
             List::new(final_messages)
         } else {
             List::new(final_messages).block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_type(self.config.borrow().frontend.border_type.clone().into())
+                    .border_type(config.frontend.border_type.clone().into())
+                    .border_style(border_style)
                     .title(chat_title),
             )
         }
         .style(*TEXT_DARK_STYLE);
 
-        f.render_widget(list, *first_v_chunk);
+        f.render_widget(list, area);
 
-        if self.config.borrow().frontend.show_scroll_offset {
+        if config.frontend.show_scroll_offset && focused {
             // Cannot scroll past the first message
             let message_amount = messages_data.len().saturating_sub(1);
 
-            let title_binding = format!(
-                "{} / {}",
-                self.scroll_offset.get_offset(),
-                message_amount.to_string().as_str()
-            );
+            let title_binding = format!("{} / {}", scroll, message_amount.to_string().as_str());
 
             let title = [TitleStyle::Single(&title_binding)];
 
             let bottom_block = Block::default()
                 .borders(Borders::BOTTOM | Borders::LEFT | Borders::RIGHT)
-                .border_type(self.config.borrow().frontend.border_type.clone().into())
+                .border_type(config.frontend.border_type.clone().into())
+                .border_style(border_style)
                 .title(title_line(&title, Style::default()))
                 .title_position(Position::Bottom)
                 .title_alignment(Alignment::Right);
 
-            let rect = Rect::new(
-                first_v_chunk.x,
-                first_v_chunk.bottom() - 1,
-                first_v_chunk.width,
-                1,
-            );
+            let rect = Rect::new(area.x, area.bottom() - 1, area.width, 1);
 
             f.render_widget(bottom_block, rect);
         }
+    }
+}
+
+impl Component for ChatWidget {
+    fn draw(&mut self, f: &mut Frame, area: Option<Rect>) {
+        let r = area.map_or_else(|| f.area(), |a| a);
+
+        let split_direction = self.config.borrow().frontend.split_direction.clone();
+
+        let mut v_constraints = vec![Constraint::Min(1)];
+
+        if self.chat_input.is_focused() || self.search_input.is_focused() {
+            v_constraints.push(Constraint::Length(3));
+        }
+
+        let v_chunks_binding = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(self.config.borrow().frontend.margin)
+            .constraints(v_constraints)
+            .split(r);
+
+        let mut v_chunks: Iter<Rect> = v_chunks_binding.iter();
+
+        let mut first_v_chunk = *v_chunks.next().unwrap();
+
+        // The poll and prediction of the current channel go above chat, leaving at least half of it visible.
+        let votes_height = self.votes.height().min(first_v_chunk.height / 2);
+
+        if votes_height > 0 {
+            let votes_area = Rect {
+                height: votes_height,
+                ..first_v_chunk
+            };
+
+            first_v_chunk.y += votes_height;
+            first_v_chunk.height -= votes_height;
+
+            self.votes.draw(f, Some(votes_area));
+        }
+
+        self.panes.sync(&self.channels.borrow());
+
+        if self.panes.is_empty() {
+            self.draw_pane(f, first_v_chunk, "", true);
+        } else {
+            let pane_count = u32::try_from(self.panes.len()).unwrap_or(1);
+
+            let pane_chunks = Layout::default()
+                .direction(split_direction.into())
+                .constraints(vec![Constraint::Ratio(1, pane_count); self.panes.len()])
+                .split(first_v_chunk);
+
+            for (index, (name, chunk)) in self.panes.iter().zip(pane_chunks.iter()).enumerate() {
+                self.draw_pane(f, *chunk, name, index == self.panes.focused());
+            }
+        }
 
         if self.chat_input.is_focused() {
             self.chat_input.draw(f, v_chunks.next().copied());
@@ -286,13 +468,20 @@ impl Component for ChatWidget {
             self.search_input.draw(f, v_chunks.next().copied());
         } else if self.following.is_focused() {
             self.following.draw(f, None);
+        } else if self.reply_thread.is_focused() {
+            self.reply_thread.draw(f, None);
+        } else if self.whispers_popup.is_focused() {
+            self.whispers_popup.draw(f, None);
+        } else if self.vote_form.is_focused() {
+            self.vote_form.draw(f, None);
         }
     }
 
     async fn event(&mut self, event: &Event) -> Option<TerminalAction> {
         if let Event::Input(key) = event {
-            let limit =
-                self.scroll_offset.get_offset() < self.messages.borrow().len().saturating_sub(1);
+            let message_count = self.active_messages().borrow().len();
+
+            let limit = self.scroll_offset.get_offset() < message_count.saturating_sub(1);
 
             if self.chat_input.is_focused() {
                 self.chat_input.event(event).await
@@ -302,26 +491,108 @@ impl Component for ChatWidget {
                 self.search_input.event(event).await
             } else if self.following.is_focused() {
                 self.following.event(event).await
+            } else if self.reply_thread.is_focused() {
+                self.reply_thread.event(event).await
+            } else if self.whispers_popup.is_focused() {
+                self.whispers_popup.event(event).await
+            } else if self.vote_form.is_focused() {
+                self.vote_form.event(event).await
             } else {
                 match key {
-                    Key::Char('i' | 'c') => self.chat_input.toggle_focus(),
-                    Key::Char('@') => self.chat_input.toggle_focus_with("@"),
-                    Key::Char('/') => self.chat_input.toggle_focus_with("/"),
+                    Key::Char('i' | 'c') if !self.read_only() => self.chat_input.toggle_focus(),
+                    Key::Char('@') if !self.read_only() => self.chat_input.toggle_focus_with("@"),
+                    Key::Char('/') if !self.read_only() => self.chat_input.toggle_focus_with("/"),
                     Key::Char('s') => self.channel_input.toggle_focus(),
                     Key::Ctrl('f') => self.search_input.toggle_focus(),
-                    Key::Char('f') => self.following.toggle_focus().await,
+                    Key::Char('f') if !self.read_only() => self.following.toggle_focus().await,
                     Key::Ctrl('t') => self.filters.borrow_mut().toggle(),
                     Key::Ctrl('r') => self.filters.borrow_mut().reverse(),
+                    Key::Ctrl('n') => self.filters.borrow_mut().toggle_user_notices(),
                     Key::Char('S') => return Some(TerminalAction::SwitchState(State::Dashboard)),
                     Key::Char('?' | 'h') => return Some(TerminalAction::SwitchState(State::Help)),
                     Key::Char('q') => return Some(TerminalAction::Quit),
                     Key::Char('o') => self.open_in_browser(),
+                    Key::Ctrl('x') => {
+                        let channel = self.channels.borrow().active().map(|c| c.name.clone());
+
+                        return channel.map(|c| TerminalAction::Enter(TwitchAction::Part(c)));
+                    }
+                    Key::Char(']') => {
+                        let channel = self.channels.borrow().next_name();
+
+                        return channel.map(|c| TerminalAction::Enter(TwitchAction::Join(c)));
+                    }
+                    Key::Char('[') => {
+                        let channel = self.channels.borrow().previous_name();
+
+                        return channel.map(|c| TerminalAction::Enter(TwitchAction::Join(c)));
+                    }
+                    Key::Char('r') if !self.read_only() => {
+                        if let Some(reply_parent) = self
+                            .selected_message()
+                            .filter(|m| !m.system)
+                            .and_then(|m| m.as_reply_parent())
+                        {
+                            self.chat_input.reply_to(reply_parent);
+                        }
+                    }
+                    Key::Char('R') if !self.read_only() => {
+                        let failed = self
+                            .channels
+                            .borrow()
+                            .active()
+                            .and_then(ChannelData::take_failed);
+
+                        return failed.and_then(|message| {
+                            let text = message.outgoing?.retry_text();
+
+                            Some(TerminalAction::Enter(match message.reply_parent {
+                                Some(reply_parent) => TwitchAction::Reply(text, reply_parent),
+                                None => TwitchAction::Privmsg(text),
+                            }))
+                        });
+                    }
+                    Key::Char('t') => {
+                        let thread = self.selected_message().and_then(|m| {
+                            let thread_id = m.thread_id()?;
+
+                            self.channels
+                                .borrow()
+                                .active()
+                                .map(|c| c.reply_thread(thread_id))
+                        });
+
+                        if let Some(messages) = thread.filter(|t| !t.is_empty()) {
+                            self.reply_thread.open(messages);
+                        }
+                    }
+                    Key::Char('w') => self.whispers_popup.toggle_focus(),
+                    Key::Char('v') => self.votes.toggle_collapsed(),
+                    Key::Char('V') if !self.read_only() => self.vote_form.toggle_focus(),
+                    Key::Char('|') => self.panes.split(&self.channels.borrow()),
+                    Key::Ctrl('w') => {
+                        return self
+                            .panes
+                            .close(&self.channels.borrow())
+                            .map(|c| TerminalAction::Enter(TwitchAction::Join(c)));
+                    }
+                    Key::Tab => {
+                        return self
+                            .panes
+                            .next(&self.channels.borrow())
+                            .map(|c| TerminalAction::Enter(TwitchAction::Join(c)));
+                    }
+                    Key::Char('m') => {
+                        let channel = self.channels.borrow().next_mentioned_name();
+
+                        return channel.map(|c| TerminalAction::Enter(TwitchAction::Join(c)));
+                    }
                     Key::Char('G') => {
                         self.scroll_offset.jump_to(0);
                     }
                     Key::Char('g') => {
                         // TODO: Make this not jump to nothingness
-                        self.scroll_offset.jump_to(self.messages.borrow().len());
+                        self.scroll_offset.jump_to(message_count);
                     }
                     Key::Esc => {
                         if self.scroll_offset.get_offset() == 0 {
@@ -1,15 +1,19 @@
-use std::fmt::Display;
+use std::{cell::Cell, fmt::Display, rc::Rc, time::Instant};
 use tui::{layout::Rect, Frame};
 
 use crate::{
     emotes::SharedEmotes,
     handlers::{
+        channels::SharedChannels,
         config::SharedCompleteConfig,
         storage::SharedStorage,
         user_input::events::{Event, Key},
     },
     terminal::TerminalAction,
-    twitch::TwitchAction,
+    twitch::{
+        moderation::ModerationCommand, rate_limit::QueueStatus, reply::ReplyParent,
+        whispers::parse_whisper_command, TwitchAction,
+    },
     ui::{
         components::{emote_picker::EmotePickerWidget, utils::InputWidget, Component},
         statics::{COMMANDS, TWITCH_MESSAGE_LIMIT},
@@ -22,16 +26,63 @@ pub struct ChatInputWidget {
     storage: SharedStorage,
     input: InputWidget<SharedStorage>,
     emote_picker: EmotePickerWidget,
+    /// The message that is being replied to, if any.
+    reply_parent: Option<ReplyParent>,
+    /// Messages waiting to be sent, shared with the visual indicator.
+    queue_status: Rc<Cell<QueueStatus>>,
 }
 
 impl ChatInputWidget {
-    pub fn new(config: SharedCompleteConfig, storage: SharedStorage, emotes: SharedEmotes) -> Self {
-        let input_validator =
-            Box::new(|_, s: String| -> bool { !s.is_empty() && s.len() < TWITCH_MESSAGE_LIMIT });
+    pub fn new(
+        config: SharedCompleteConfig,
+        storage: SharedStorage,
+        emotes: SharedEmotes,
+        channels: SharedChannels,
+    ) -> Self {
+        // Commands and whispers are not affected by slow mode, unlike `/me` which is sent to chat,
+        // but moderation commands need valid arguments.
+        let validator_channels = channels.clone();
+        let input_validator = Box::new(move |_, s: String| -> bool {
+            !s.is_empty()
+                && s.len() < TWITCH_MESSAGE_LIMIT
+                && !matches!(ModerationCommand::parse(&s), Some(Err(_)))
+                && ((s.starts_with('/') && !s.starts_with("/me "))
+                    || validator_channels
+                        .borrow()
+                        .active()
+                        .and_then(|channel| channel.slow_mode_remaining(Instant::now()))
+                        .is_none())
+        });
+
+        let queue_status = Rc::new(Cell::new(QueueStatus::default()));
+
+        // User should be known of how close they are to the message length limit,
+        // how many of their messages are being held back by rate limits,
+        // and what the chat settings of the channel will not let through.
+        let indicator_queue_status = queue_status.clone();
+        let visual_indicator = Box::new(move |s: String| -> String {
+            let mut indicator = format!("{} / {}", s.len(), TWITCH_MESSAGE_LIMIT);
+
+            if let Some(Err(usage)) = ModerationCommand::parse(&s) {
+                indicator = format!("{indicator} | usage: {usage}");
+            }
+
+            if let Some(queue) = indicator_queue_status.get().description() {
+                indicator = format!("{indicator} | {queue}");
+            }
+
+            if let Some(channel) = channels.borrow().active() {
+                if let Some(remaining) = channel.slow_mode_remaining(Instant::now()) {
+                    let seconds = remaining.as_millis().div_ceil(1000);
+
+                    indicator = format!("{indicator} | slow mode, {seconds}s left");
+                } else if let Some(warning) = channel.restriction_warning() {
+                    indicator = format!("{indicator} | channel is {warning}");
+                }
+            }
 
-        // User should be known of how close they are to the message length limit.
-        let visual_indicator =
-            Box::new(|s: String| -> String { format!("{} / {}", s.len(), TWITCH_MESSAGE_LIMIT) });
+            indicator
+        });
 
         let input_suggester = Box::new(|storage: SharedStorage, s: String| -> Option<String> {
             s.chars()
@@ -77,6 +128,8 @@ impl ChatInputWidget {
             storage,
             input,
             emote_picker,
+            reply_parent: None,
+            queue_status,
         }
     }
 
@@ -91,6 +144,26 @@ impl ChatInputWidget {
     pub fn toggle_focus_with(&mut self, s: &str) {
         self.input.toggle_focus_with(s);
     }
+
+    /// Focuses the input, with the next message being sent as a reply.
+    pub fn reply_to(&mut self, reply_parent: ReplyParent) {
+        self.input
+            .set_title(&format!("Reply to @{}", reply_parent.user_login));
+        self.reply_parent = Some(reply_parent);
+
+        if !self.is_focused() {
+            self.toggle_focus();
+        }
+    }
+
+    pub fn set_queue_status(&self, queue_status: QueueStatus) {
+        self.queue_status.set(queue_status);
+    }
+
+    fn stop_replying(&mut self) {
+        self.input.set_title("Chat");
+        self.reply_parent = None;
+    }
 }
 
 impl Display for ChatInputWidget {
@@ -122,10 +195,26 @@ impl Component for ChatInputWidget {
                     if self.input.is_valid() {
                         let current_input = self.input.to_string();
 
-                        let action =
-                            TerminalAction::Enter(TwitchAction::Privmsg(current_input.clone()));
+                        // Commands cannot be replies.
+                        let action = match self.reply_parent.take() {
+                            Some(reply_parent) if !current_input.starts_with('/') => {
+                                TwitchAction::Reply(current_input.clone(), reply_parent)
+                            }
+                            _ => match ModerationCommand::parse(&current_input) {
+                                Some(Ok(command)) => TwitchAction::Moderate(command),
+                                _ => parse_whisper_command(&current_input).map_or_else(
+                                    || TwitchAction::Privmsg(current_input.clone()),
+                                    |(recipient, message)| {
+                                        TwitchAction::Whisper(recipient, message)
+                                    },
+                                ),
+                            },
+                        };
+
+                        let action = TerminalAction::Enter(action);
 
                         self.input.clear();
+                        self.stop_replying();
 
                         if let Some(message) = current_input.strip_prefix('@') {
                             if self.config.borrow().storage.mentions {
@@ -148,6 +237,7 @@ impl Component for ChatInputWidget {
                     }
                 }
                 Key::Esc => {
+                    self.stop_replying();
                     self.input.toggle_focus();
                 }
                 _ => {
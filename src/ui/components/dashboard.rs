@@ -228,7 +228,9 @@ impl Component for DashboardWidget {
                 Key::Ctrl('p') => panic!("Manual panic triggered by user."),
                 Key::Char('q') => return Some(TerminalAction::Quit),
                 Key::Char('s') => self.channel_input.toggle_focus(),
-                Key::Char('f') => self.following.toggle_focus().await,
+                Key::Char('f') if !self.config.borrow().twitch.anonymous => {
+                    self.following.toggle_focus().await;
+                }
                 Key::Enter => {
                     let action = TerminalAction::Enter(TwitchAction::Join(
                         self.config.borrow().twitch.channel.clone(),
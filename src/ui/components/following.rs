@@ -5,7 +5,10 @@ use tui::{layout::Rect, Frame};
 use crate::{
     handlers::{config::SharedCompleteConfig, user_input::events::Event},
     terminal::TerminalAction,
-    twitch::{channels::Following, TwitchAction},
+    twitch::{
+        channels::{FollowedChannel, Following},
+        TwitchAction,
+    },
     ui::components::Component,
 };
 
@@ -25,7 +28,7 @@ static INCORRECT_SCOPES_ERROR_MESSAGE: Lazy<Vec<&'static str>> = Lazy::new(|| {
 pub struct FollowingWidget {
     #[allow(dead_code)]
     config: SharedCompleteConfig,
-    pub search_widget: SearchWidget<String, Following>,
+    pub search_widget: SearchWidget<FollowedChannel, Following>,
 }
 
 impl FollowingWidget {
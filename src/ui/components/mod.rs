@@ -7,7 +7,12 @@ mod error;
 mod following;
 mod help;
 mod message_search;
+mod panes;
+mod reply_thread;
 mod state_tabs;
+mod vote_form;
+mod votes;
+mod whispers;
 
 mod emote_picker;
 pub mod utils;
@@ -29,11 +34,12 @@ use tui::{layout::Rect, Frame};
 use crate::{
     emotes::SharedEmotes,
     handlers::{
-        app::SharedMessages,
+        channels::SharedChannels,
         config::SharedCompleteConfig,
         filters::SharedFilters,
         storage::SharedStorage,
         user_input::events::{Event, Key},
+        whispers::SharedWhispers,
     },
     terminal::TerminalAction,
 };
@@ -86,17 +92,25 @@ impl Components {
         config: &SharedCompleteConfig,
         storage: SharedStorage,
         filters: SharedFilters,
-        messages: SharedMessages,
+        channels: SharedChannels,
+        whispers: SharedWhispers,
         emotes: &SharedEmotes,
         startup_time: DateTime<Local>,
     ) -> Self {
         let window_size_error = ErrorWidget::new(WINDOW_SIZE_TOO_SMALL_ERROR.to_vec());
 
         Self {
-            tabs: StateTabsWidget::new(config.clone()),
+            tabs: StateTabsWidget::new(config.clone(), channels.clone()),
             debug: DebugWidget::new(config.clone(), startup_time),
 
-            chat: ChatWidget::new(config.clone(), messages, &storage, emotes, filters),
+            chat: ChatWidget::new(
+                config.clone(),
+                channels,
+                whispers,
+                &storage,
+                emotes,
+                filters,
+            ),
             dashboard: DashboardWidget::new(config.clone(), storage),
             help: HelpWidget::new(config.clone()),
             window_size_error,
@@ -0,0 +1,198 @@
+use crate::handlers::channels::Channels;
+
+/// The most amount of channels that can be shown next to each other.
+const MAX_PANES: usize = 4;
+
+/// Channels shown next to each other in chat.
+#[derive(Debug, Default)]
+pub struct Panes {
+    /// Names of the channels shown next to each other.
+    names: Vec<String>,
+    /// The pane that shows the active channel, which chat input is sent to.
+    focused: usize,
+}
+
+impl Panes {
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.names.iter()
+    }
+
+    pub const fn focused(&self) -> usize {
+        self.focused
+    }
+
+    /// If the channel is currently visible in one of the panes.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.iter().any(|pane| pane == name)
+    }
+
+    /// Removes panes of channels that have been left, and makes the focused pane show the active channel.
+    pub fn sync(&mut self, channels: &Channels) {
+        self.names.retain(|pane| channels.contains(pane));
+
+        let Some(active) = channels.active() else {
+            self.focused = 0;
+            return;
+        };
+
+        if let Some(index) = self.names.iter().position(|pane| *pane == active.name) {
+            self.focused = index;
+        } else if let Some(pane) = self.names.get_mut(self.focused) {
+            pane.clone_from(&active.name);
+        } else {
+            self.names.push(active.name.clone());
+            self.focused = self.names.len() - 1;
+        }
+    }
+
+    /// Opens a new pane with the first joined channel that is not visible yet.
+    pub fn split(&mut self, channels: &Channels) {
+        self.sync(channels);
+
+        if self.names.len() >= MAX_PANES {
+            return;
+        }
+
+        let channel = channels
+            .iter()
+            .find(|c| !self.contains(&c.name))
+            .map(|c| c.name.clone());
+
+        if let Some(name) = channel {
+            self.names.push(name);
+        }
+    }
+
+    /// Closes the focused pane, returning the channel of the pane that is focused next.
+    pub fn close(&mut self, channels: &Channels) -> Option<String> {
+        self.sync(channels);
+
+        if self.names.len() < 2 {
+            return None;
+        }
+
+        self.names.remove(self.focused);
+        self.focused = self.focused.min(self.names.len() - 1);
+
+        self.names.get(self.focused).cloned()
+    }
+
+    /// The channel of the pane after the focused one, if there is more than one pane.
+    pub fn next(&mut self, channels: &Channels) -> Option<String> {
+        self.sync(channels);
+
+        if self.names.len() < 2 {
+            return None;
+        }
+
+        self.names
+            .get((self.focused + 1) % self.names.len())
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Channels {
+        let mut channels = Channels::new(10);
+
+        for name in ["foo", "bar", "baz", "qux", "quux"] {
+            channels.join(name);
+        }
+
+        channels.set_active("foo");
+
+        channels
+    }
+
+    #[test]
+    fn test_sync_shows_active_channel() {
+        let mut channels = setup();
+        let mut panes = Panes::default();
+
+        panes.sync(&channels);
+        assert_eq!(panes.names, ["foo"]);
+
+        // The focused pane switches to the channel that became active.
+        channels.set_active("bar");
+        panes.sync(&channels);
+        assert_eq!(panes.names, ["bar"]);
+        assert_eq!(panes.focused, 0);
+    }
+
+    #[test]
+    fn test_sync_removes_parted_channels() {
+        let mut channels = setup();
+        let mut panes = Panes::default();
+
+        panes.split(&channels);
+        panes.split(&channels);
+        assert_eq!(panes.names, ["foo", "bar", "baz"]);
+
+        channels.set_active("baz");
+        channels.part("bar");
+        panes.sync(&channels);
+
+        assert_eq!(panes.names, ["foo", "baz"]);
+        assert_eq!(panes.focused, 1);
+    }
+
+    #[test]
+    fn test_split_up_to_maximum_panes() {
+        let channels = setup();
+        let mut panes = Panes::default();
+
+        for _ in 0..=MAX_PANES {
+            panes.split(&channels);
+        }
+
+        assert_eq!(panes.names, ["foo", "bar", "baz", "qux"]);
+        assert_eq!(panes.focused, 0);
+    }
+
+    #[test]
+    fn test_close_focused_pane() {
+        let mut channels = setup();
+        let mut panes = Panes::default();
+
+        assert_eq!(panes.close(&channels), None);
+
+        panes.split(&channels);
+        panes.split(&channels);
+
+        channels.set_active("baz");
+        assert_eq!(panes.close(&channels).as_deref(), Some("bar"));
+        assert_eq!(panes.names, ["foo", "bar"]);
+        assert_eq!(panes.focused, 1);
+
+        // The last pane is never closed.
+        channels.set_active("foo");
+        assert_eq!(panes.close(&channels).as_deref(), Some("bar"));
+        assert_eq!(panes.close(&channels), None);
+    }
+
+    #[test]
+    fn test_next_pane() {
+        let mut channels = setup();
+        let mut panes = Panes::default();
+
+        assert_eq!(panes.next(&channels), None);
+
+        panes.split(&channels);
+
+        assert_eq!(panes.next(&channels).as_deref(), Some("bar"));
+
+        channels.set_active("bar");
+        assert_eq!(panes.next(&channels).as_deref(), Some("foo"));
+    }
+}
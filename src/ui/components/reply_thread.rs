@@ -0,0 +1,95 @@
+use tui::{
+    layout::Rect,
+    widgets::{Block, Borders, Clear, List},
+    Frame,
+};
+
+use crate::{
+    handlers::{
+        config::SharedCompleteConfig,
+        data::MessageData,
+        user_input::events::{Event, Key},
+    },
+    terminal::TerminalAction,
+    ui::components::{
+        utils::{centered_rect, latest_message_items},
+        Component,
+    },
+    utils::{
+        styles::{TEXT_DARK_STYLE, TITLE_STYLE},
+        text::{title_line, TitleStyle},
+    },
+};
+
+pub struct ReplyThreadWidget {
+    config: SharedCompleteConfig,
+    /// Messages of the thread, from oldest to newest.
+    messages: Vec<MessageData>,
+    focused: bool,
+}
+
+impl ReplyThreadWidget {
+    pub const fn new(config: SharedCompleteConfig) -> Self {
+        Self {
+            config,
+            messages: vec![],
+            focused: false,
+        }
+    }
+
+    pub const fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.focused = !self.focused;
+    }
+
+    /// Shows the messages of a reply thread, sorted from oldest to newest.
+    pub fn open(&mut self, messages: Vec<MessageData>) {
+        self.messages = messages;
+        self.focused = true;
+    }
+}
+
+impl Component for ReplyThreadWidget {
+    fn draw(&mut self, f: &mut Frame, area: Option<Rect>) {
+        let r = area
+            .unwrap_or_else(|| centered_rect(80, 60, f.area().height.saturating_sub(6), f.area()));
+
+        let config = self.config.borrow();
+
+        // Only the most recent messages are shown if the thread does not fit.
+        let items = latest_message_items(&self.messages, &config.frontend, r);
+
+        let title_binding = [TitleStyle::Combined(
+            "Reply thread",
+            &self.messages.len().to_string(),
+        )];
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(config.frontend.border_type.clone().into())
+                    .title(title_line(&title_binding, *TITLE_STYLE)),
+            )
+            .style(*TEXT_DARK_STYLE);
+
+        f.render_widget(Clear, r);
+        f.render_widget(list, r);
+    }
+
+    async fn event(&mut self, event: &Event) -> Option<TerminalAction> {
+        if let Event::Input(key) = event {
+            match key {
+                Key::Esc | Key::Char('t') => self.toggle_focus(),
+                Key::Char('q') => return Some(TerminalAction::Quit),
+                Key::Ctrl('p') => panic!("Manual panic triggered by user."),
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
@@ -1,14 +1,18 @@
 use tui::{
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols::DOT,
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, Tabs},
     Frame,
 };
 
 use crate::{
-    handlers::{config::SharedCompleteConfig, state::State},
+    handlers::{
+        channels::{ChannelData, SharedChannels},
+        config::SharedCompleteConfig,
+        state::State,
+    },
     utils::{styles::STATE_TABS_STYLE, text::capitalize_first_char},
 };
 
@@ -17,20 +21,19 @@ const TABS_TO_RENDER: [State; 3] = [State::Dashboard, State::Normal, State::Help
 #[derive(Debug, Clone)]
 pub struct StateTabsWidget {
     _config: SharedCompleteConfig,
+    channels: SharedChannels,
 }
 
 impl StateTabsWidget {
-    pub const fn new(config: SharedCompleteConfig) -> Self {
-        Self { _config: config }
+    pub const fn new(config: SharedCompleteConfig, channels: SharedChannels) -> Self {
+        Self {
+            _config: config,
+            channels,
+        }
     }
 
-    pub fn draw(&self, f: &mut Frame, area: Option<Rect>, state: &State) {
-        let tab_titles = TABS_TO_RENDER
-            .iter()
-            .map(|t| Line::from(capitalize_first_char(&t.to_string())))
-            .collect::<Vec<Line>>();
-
-        let tabs = Tabs::new(tab_titles)
+    fn tabs(titles: Vec<Line<'_>>, selected: usize) -> Tabs<'_> {
+        Tabs::new(titles)
             .block(Block::default())
             .style(*STATE_TABS_STYLE)
             .highlight_style(
@@ -40,8 +43,72 @@ impl StateTabsWidget {
                     .add_modifier(Modifier::UNDERLINED),
             )
             .divider(DOT)
-            .select(TABS_TO_RENDER.iter().position(|s| s == state).unwrap());
+            .select(selected)
+    }
+
+    fn channel_title(channel: &ChannelData) -> Line<'static> {
+        let mut spans = vec![Span::raw(channel.name.clone())];
+
+        if channel.unread > 0 {
+            spans.push(Span::raw(format!(" ({})", channel.unread)));
+        }
+
+        if channel.mentions > 0 {
+            spans.push(Span::styled(
+                format!(" @{}", channel.mentions),
+                Style::default()
+                    .fg(Color::Red)
+                    .remove_modifier(Modifier::DIM)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        Line::from(spans)
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Option<Rect>, state: &State) {
+        let area = area.unwrap();
+
+        let tab_titles = TABS_TO_RENDER
+            .iter()
+            .map(|t| Line::from(capitalize_first_char(&t.to_string())))
+            .collect::<Vec<Line>>();
+
+        // Every title is padded by a space on both sides, with a divider in between titles.
+        let state_tabs_width = tab_titles.iter().map(|t| t.width() + 3).sum::<usize>() - 1;
+
+        let state_tabs = Self::tabs(
+            tab_titles,
+            TABS_TO_RENDER.iter().position(|s| s == state).unwrap(),
+        );
+
+        let channels = self.channels.borrow();
+
+        if channels.is_empty() {
+            f.render_widget(state_tabs, area);
+            return;
+        }
+
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(u16::try_from(state_tabs_width).unwrap_or(u16::MAX)),
+                Constraint::Length(1),
+                Constraint::Min(1),
+            ])
+            .split(area);
+
+        f.render_widget(state_tabs, layout[0]);
+        f.render_widget(Line::from(DOT).style(*STATE_TABS_STYLE), layout[1]);
+
+        let channel_titles = channels
+            .iter()
+            .map(Self::channel_title)
+            .collect::<Vec<Line>>();
 
-        f.render_widget(tabs, area.unwrap());
+        f.render_widget(
+            Self::tabs(channel_titles, channels.active_index()),
+            layout[2],
+        );
     }
 }
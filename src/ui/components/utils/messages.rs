@@ -0,0 +1,35 @@
+use tui::{layout::Rect, text::Line, widgets::ListItem};
+
+use crate::handlers::{config::FrontendConfig, data::MessageData};
+
+/// Wraps a message to fit in an area of the given width.
+pub fn message_lines<'a>(
+    message: &'a MessageData,
+    frontend_config: &FrontendConfig,
+    width: u16,
+    search_highlight: Option<&str>,
+    username_highlight: Option<&str>,
+) -> Vec<Line<'a>> {
+    // Messages are wrapped as if the area was surrounded by the window margin.
+    let width = width as usize + usize::from(frontend_config.margin) * 2;
+
+    message.to_vec(frontend_config, width, search_highlight, username_highlight)
+}
+
+/// The most recent lines of the messages, oldest first, that fit inside of a bordered area.
+pub fn latest_message_items<'a>(
+    messages: impl IntoIterator<Item = &'a MessageData>,
+    frontend_config: &FrontendConfig,
+    area: Rect,
+) -> Vec<ListItem<'a>> {
+    let height = area.height.saturating_sub(2) as usize;
+
+    let lines = messages
+        .into_iter()
+        .flat_map(|m| message_lines(m, frontend_config, area.width, None, None))
+        .collect::<Vec<Line>>();
+
+    let skipped = lines.len().saturating_sub(height);
+
+    lines.into_iter().skip(skipped).map(ListItem::new).collect()
+}
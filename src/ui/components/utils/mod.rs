@@ -1,4 +1,5 @@
 mod input_widget;
+mod messages;
 mod popups;
 mod search_widget;
 
@@ -7,5 +8,6 @@ pub use input_widget::{
     InputListener, // This is used in a test within src/utils/text.rs
     InputWidget,
 };
+pub use messages::{latest_message_items, message_lines};
 pub use popups::centered_rect;
-pub use search_widget::{SearchItemGetter, SearchWidget};
+pub use search_widget::{SearchItem, SearchItemGetter, SearchWidget};
@@ -34,16 +34,28 @@ use super::{centered_rect, InputWidget};
 
 static FUZZY_FINDER: Lazy<SkimMatcherV2> = Lazy::new(SkimMatcherV2::default);
 
+/// An item that can be searched for, being shown as its string.
+pub trait SearchItem: ToString {
+    /// The channel to join when the item is selected.
+    fn channel(&self) -> String;
+}
+
+impl SearchItem for String {
+    fn channel(&self) -> String {
+        self.clone()
+    }
+}
+
 pub trait SearchItemGetter<T>
 where
-    T: ToString,
+    T: SearchItem,
 {
     async fn get_items(&mut self) -> Result<Vec<T>>;
 }
 
 pub struct SearchWidget<T, U>
 where
-    T: ToString + Clone,
+    T: SearchItem + Clone,
     U: SearchItemGetter<T>,
 {
     config: SharedCompleteConfig,
@@ -63,7 +75,7 @@ where
 
 impl<T, U> SearchWidget<T, U>
 where
-    T: ToString + Clone,
+    T: SearchItem + Clone,
     U: SearchItemGetter<T>,
 {
     pub fn new(
@@ -146,7 +158,7 @@ where
 
 impl<T, U> Component for SearchWidget<T, U>
 where
-    T: ToString + Clone,
+    T: SearchItem + Clone,
     U: SearchItemGetter<T>,
 {
     fn draw(&mut self, f: &mut Frame, area: Option<Rect>) {
@@ -292,9 +304,9 @@ where
                                 return None;
                             }
 
-                            v.get(i).unwrap().to_string()
+                            v.get(i).unwrap().channel()
                         } else {
-                            self.items.as_ref().unwrap().get(i).unwrap().to_string()
+                            self.items.as_ref().unwrap().get(i).unwrap().channel()
                         }
                         .to_lowercase();
 
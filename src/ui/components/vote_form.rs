@@ -0,0 +1,467 @@
+use tui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{block::Position, Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    handlers::{
+        channels::SharedChannels,
+        config::SharedCompleteConfig,
+        user_input::events::{Event, Key},
+    },
+    terminal::TerminalAction,
+    twitch::{
+        moderation::parse_duration,
+        polls::{
+            VoteCommand, OPTION_TITLE_LIMIT, POLL_CHOICES, POLL_DURATION, POLL_TITLE_LIMIT,
+            PREDICTION_OUTCOMES, PREDICTION_TITLE_LIMIT, PREDICTION_WINDOW,
+        },
+        TwitchAction,
+    },
+    ui::components::{
+        utils::{centered_rect, InputWidget},
+        Component,
+    },
+    utils::{
+        styles::{TEXT_DARK_STYLE, TITLE_STYLE},
+        text::{title_line, TitleStyle},
+    },
+};
+
+/// Choices and outcomes are entered on a single line, separated by this.
+const OPTION_SEPARATOR: char = '|';
+
+fn parse_options(input: &str) -> Vec<String> {
+    input
+        .split(OPTION_SEPARATOR)
+        .map(str::trim)
+        .filter(|option| !option.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn valid_title(input: &str, limit: usize) -> bool {
+    let title = input.trim();
+
+    !title.is_empty() && title.chars().count() <= limit
+}
+
+fn valid_options(input: &str, (min, max): (usize, usize)) -> bool {
+    let options = parse_options(input);
+
+    (min..=max).contains(&options.len())
+        && options
+            .iter()
+            .all(|option| option.chars().count() <= OPTION_TITLE_LIMIT)
+}
+
+/// Seconds of a duration such as `90`, `90s` or `5m`, if it is within the range.
+fn parse_seconds(input: &str, (min, max): (u64, u64)) -> Option<u64> {
+    parse_duration(input.trim(), 1).filter(|seconds| (min..=max).contains(seconds))
+}
+
+/// The ID of the outcome that was entered, either by its number starting from 1, or its title.
+fn find_outcome(outcomes: &[(String, String)], input: &str) -> Option<String> {
+    let input = input.trim();
+
+    input
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| outcomes.get(number.checked_sub(1)?))
+        .or_else(|| {
+            outcomes
+                .iter()
+                .find(|(_, title)| title.eq_ignore_ascii_case(input))
+        })
+        .map(|(id, _)| id.clone())
+}
+
+fn title_field(config: &SharedCompleteConfig, limit: usize) -> InputWidget<()> {
+    InputWidget::new(
+        config.clone(),
+        "Title",
+        Some(((), Box::new(move |(), s: String| valid_title(&s, limit)))),
+        Some(Box::new(move |s: String| {
+            format!("{} / {limit}", s.trim().chars().count())
+        })),
+        None,
+    )
+}
+
+fn options_field(
+    config: &SharedCompleteConfig,
+    name: &str,
+    range: (usize, usize),
+) -> InputWidget<()> {
+    InputWidget::new(
+        config.clone(),
+        &format!("{name}, separated by {OPTION_SEPARATOR}"),
+        Some(((), Box::new(move |(), s: String| valid_options(&s, range)))),
+        Some(Box::new(move |s: String| {
+            format!("{} / {}", parse_options(&s).len(), range.1)
+        })),
+        None,
+    )
+}
+
+fn duration_field(config: &SharedCompleteConfig, name: &str, range: (u64, u64)) -> InputWidget<()> {
+    InputWidget::new(
+        config.clone(),
+        &format!("{name}, such as 90s or 5m"),
+        Some((
+            (),
+            Box::new(move |(), s: String| parse_seconds(&s, range).is_some()),
+        )),
+        Some(Box::new(move |_| {
+            format!("{}s to {}m", range.0, range.1 / 60)
+        })),
+        None,
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Form {
+    /// Picking what to do, from what the current poll and prediction allow.
+    Menu,
+    CreatePoll,
+    CreatePrediction,
+    /// Picking the winning outcome of the prediction with this ID, out of the outcome IDs and titles.
+    ResolvePrediction(String, Vec<(String, String)>),
+}
+
+/// A prediction of the current channel that has not ended yet.
+struct RunningPrediction {
+    id: String,
+    locked: bool,
+    /// IDs and titles of the outcomes.
+    outcomes: Vec<(String, String)>,
+}
+
+/// Popup to create, end and resolve polls and predictions in the current channel.
+pub struct VoteFormWidget {
+    config: SharedCompleteConfig,
+    channels: SharedChannels,
+    focused: bool,
+    form: Form,
+    fields: Vec<InputWidget<()>>,
+    /// Index of the field being typed in.
+    field: usize,
+}
+
+impl VoteFormWidget {
+    pub const fn new(config: SharedCompleteConfig, channels: SharedChannels) -> Self {
+        Self {
+            config,
+            channels,
+            focused: false,
+            form: Form::Menu,
+            fields: vec![],
+            field: 0,
+        }
+    }
+
+    pub const fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.focused = !self.focused;
+
+        self.open(Form::Menu);
+    }
+
+    fn open(&mut self, form: Form) {
+        self.fields = match &form {
+            Form::Menu => vec![],
+            Form::CreatePoll => vec![
+                title_field(&self.config, POLL_TITLE_LIMIT),
+                options_field(&self.config, "Choices", POLL_CHOICES),
+                duration_field(&self.config, "Duration", POLL_DURATION),
+            ],
+            Form::CreatePrediction => vec![
+                title_field(&self.config, PREDICTION_TITLE_LIMIT),
+                options_field(&self.config, "Outcomes", PREDICTION_OUTCOMES),
+                duration_field(&self.config, "Prediction window", PREDICTION_WINDOW),
+            ],
+            Form::ResolvePrediction(_, outcomes) => {
+                let outcomes = outcomes.clone();
+
+                vec![InputWidget::new(
+                    self.config.clone(),
+                    "Winning outcome, by number or title",
+                    Some((
+                        (),
+                        Box::new(move |(), s: String| find_outcome(&outcomes, &s).is_some()),
+                    )),
+                    None,
+                    None,
+                )]
+            }
+        };
+
+        self.form = form;
+        self.field = 0;
+    }
+
+    /// The poll that can still be ended by its ID, and the prediction that can still be locked or resolved.
+    fn running(&self) -> (Option<String>, Option<RunningPrediction>) {
+        let channels = self.channels.borrow();
+
+        let Some(channel) = channels.active() else {
+            return (None, None);
+        };
+
+        let poll = channel
+            .poll
+            .as_ref()
+            .filter(|poll| !poll.ended())
+            .map(|poll| poll.id.clone());
+
+        let prediction = channel
+            .prediction
+            .as_ref()
+            .filter(|prediction| !prediction.ended())
+            .map(|prediction| RunningPrediction {
+                id: prediction.id.clone(),
+                locked: prediction.locked(),
+                outcomes: prediction
+                    .outcomes
+                    .iter()
+                    .map(|outcome| (outcome.id.clone(), outcome.title.clone()))
+                    .collect(),
+            });
+
+        (poll, prediction)
+    }
+
+    /// The actions of the menu that can be taken right now, with their keys.
+    fn menu_items(&self) -> Vec<(char, &'static str)> {
+        let (poll, prediction) = self.running();
+
+        let mut items = vec![('p', "Create a poll"), ('d', "Create a prediction")];
+
+        if poll.is_some() {
+            items.push(('e', "End the poll"));
+        }
+
+        if let Some(prediction) = prediction {
+            if !prediction.locked {
+                items.push(('l', "Lock the prediction"));
+            }
+
+            items.push(('r', "Resolve the prediction"));
+            items.push(('c', "Cancel the prediction"));
+        }
+
+        items
+    }
+
+    fn menu_event(&mut self, c: char) -> Option<VoteCommand> {
+        let (poll, prediction) = self.running();
+
+        match (c, poll, prediction) {
+            ('p', _, _) => self.open(Form::CreatePoll),
+            ('d', _, _) => self.open(Form::CreatePrediction),
+            ('e', Some(id), _) => return Some(VoteCommand::EndPoll(id)),
+            ('l', _, Some(prediction)) if !prediction.locked => {
+                return Some(VoteCommand::LockPrediction(prediction.id));
+            }
+            ('r', _, Some(prediction)) => {
+                self.open(Form::ResolvePrediction(prediction.id, prediction.outcomes));
+            }
+            ('c', _, Some(prediction)) => {
+                return Some(VoteCommand::CancelPrediction(prediction.id));
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// The command from the filled in form, if every field is valid.
+    fn command(&self) -> Option<VoteCommand> {
+        if !self.fields.iter().all(InputWidget::is_valid) {
+            return None;
+        }
+
+        let value = |index: usize| self.fields[index].to_string();
+
+        Some(match &self.form {
+            Form::Menu => return None,
+            Form::CreatePoll => VoteCommand::CreatePoll {
+                title: value(0).trim().to_string(),
+                choices: parse_options(&value(1)),
+                duration: parse_seconds(&value(2), POLL_DURATION)?,
+            },
+            Form::CreatePrediction => VoteCommand::CreatePrediction {
+                title: value(0).trim().to_string(),
+                outcomes: parse_options(&value(1)),
+                window: parse_seconds(&value(2), PREDICTION_WINDOW)?,
+            },
+            Form::ResolvePrediction(id, outcomes) => {
+                VoteCommand::ResolvePrediction(id.clone(), find_outcome(outcomes, &value(0))?)
+            }
+        })
+    }
+
+    fn submit(&mut self, command: VoteCommand) -> TerminalAction {
+        self.focused = false;
+        self.open(Form::Menu);
+
+        TerminalAction::Enter(TwitchAction::Vote(command))
+    }
+
+    fn draw_menu(&self, f: &mut Frame) {
+        let items = self.menu_items();
+
+        let r = centered_rect(40, 60, items.len() as u16 + 2, f.area());
+
+        let lines = items
+            .iter()
+            .map(|(key, description)| {
+                Line::from(vec![
+                    Span::styled(key.to_string(), *TITLE_STYLE),
+                    Span::raw(format!("  {description}")),
+                ])
+            })
+            .collect::<Vec<Line>>();
+
+        let title = [TitleStyle::Single("Polls and predictions")];
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.config.borrow().frontend.border_type.clone().into())
+            .title(title_line(&title, *TITLE_STYLE));
+
+        f.render_widget(Clear, r);
+        f.render_widget(
+            Paragraph::new(lines).block(block).style(*TEXT_DARK_STYLE),
+            r,
+        );
+    }
+}
+
+impl Component for VoteFormWidget {
+    fn draw(&mut self, f: &mut Frame, area: Option<Rect>) {
+        let title = match self.form {
+            Form::Menu => return self.draw_menu(f),
+            Form::CreatePoll => "Create a poll",
+            Form::CreatePrediction => "Create a prediction",
+            Form::ResolvePrediction(..) => "Resolve the prediction",
+        };
+
+        let r = area
+            .unwrap_or_else(|| centered_rect(60, 60, self.fields.len() as u16 * 3 + 2, f.area()));
+
+        let title = [TitleStyle::Single(title)];
+        let hint = [TitleStyle::Single(
+            "Enter: next field or submit, Esc: close",
+        )];
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.config.borrow().frontend.border_type.clone().into())
+            .title(title_line(&title, *TITLE_STYLE))
+            .title(
+                Line::from(title_line(&hint, *TITLE_STYLE))
+                    .alignment(tui::layout::Alignment::Right),
+            )
+            .title_position(Position::Top);
+
+        let inner = block.inner(r);
+
+        f.render_widget(Clear, r);
+        f.render_widget(block, r);
+
+        // Every field places the cursor, so the one being typed in is drawn last.
+        let mut order = (0..self.fields.len()).collect::<Vec<usize>>();
+        order.rotate_left((self.field + 1) % self.fields.len().max(1));
+
+        for index in order {
+            let rect = Rect::new(
+                inner.x,
+                inner.y + index as u16 * 3,
+                inner.width,
+                3.min(inner.height.saturating_sub(index as u16 * 3)),
+            );
+
+            self.fields[index].draw(f, Some(rect));
+        }
+    }
+
+    async fn event(&mut self, event: &Event) -> Option<TerminalAction> {
+        if let Event::Input(key) = event {
+            match key {
+                Key::Esc => {
+                    if self.form == Form::Menu {
+                        self.focused = false;
+                    } else {
+                        self.open(Form::Menu);
+                    }
+                }
+                Key::Ctrl('p') => panic!("Manual panic triggered by user."),
+                Key::Char('q') if self.form == Form::Menu => return Some(TerminalAction::Quit),
+                Key::Char(c) if self.form == Form::Menu => {
+                    if let Some(command) = self.menu_event(*c) {
+                        return Some(self.submit(command));
+                    }
+                }
+                Key::Up => self.field = self.field.saturating_sub(1),
+                Key::Down => self.field = (self.field + 1).min(self.fields.len().saturating_sub(1)),
+                Key::Enter if self.field + 1 < self.fields.len() => self.field += 1,
+                Key::Enter => {
+                    if let Some(command) = self.command() {
+                        return Some(self.submit(command));
+                    }
+
+                    // Go back to the first field that needs fixing.
+                    if let Some(index) = self.fields.iter().position(|field| !field.is_valid()) {
+                        self.field = index;
+                    }
+                }
+                _ => {
+                    if let Some(field) = self.fields.get_mut(self.field) {
+                        field.event(event).await;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(parse_options(" Heads | Tails |"), vec!["Heads", "Tails"]);
+        assert!(valid_options("Heads | Tails", POLL_CHOICES));
+        assert!(!valid_options("Heads", POLL_CHOICES));
+        assert!(!valid_options(&"a".repeat(26), (1, 1)));
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("5m", POLL_DURATION), Some(300));
+        assert_eq!(parse_seconds("10", POLL_DURATION), None);
+        assert_eq!(parse_seconds("1h", PREDICTION_WINDOW), None);
+    }
+
+    #[test]
+    fn test_find_outcome() {
+        let outcomes = vec![
+            ("1".to_string(), "Yes".to_string()),
+            ("2".to_string(), "No".to_string()),
+        ];
+
+        assert_eq!(find_outcome(&outcomes, "2"), Some("2".to_string()));
+        assert_eq!(find_outcome(&outcomes, "yes"), Some("1".to_string()));
+        assert_eq!(find_outcome(&outcomes, "0"), None);
+        assert_eq!(find_outcome(&outcomes, "Maybe"), None);
+    }
+}
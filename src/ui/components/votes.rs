@@ -0,0 +1,283 @@
+use chrono::{DateTime, Utc};
+use tui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    handlers::{channels::SharedChannels, config::SharedCompleteConfig},
+    twitch::{
+        channel_event::{Poll, Prediction},
+        polls::{countdown, is_shown, percentage},
+    },
+    ui::components::Component,
+    utils::{
+        styles::{NO_COLOR, TEXT_DARK_STYLE, TITLE_STYLE},
+        text::{title_line, TitleStyle},
+    },
+};
+
+/// A poll choice or prediction outcome, along with how much it got.
+struct VoteOption {
+    title: String,
+    /// Votes or channel points, which the bar is drawn from.
+    amount: u64,
+    /// What the amount is, such as `12 votes`.
+    detail: String,
+    color: Color,
+    winner: bool,
+}
+
+/// What is shown of a poll or prediction.
+struct VoteSummary {
+    kind: &'static str,
+    title: String,
+    /// Time left, or if it is locked or has ended.
+    status: String,
+    options: Vec<VoteOption>,
+}
+
+impl VoteSummary {
+    fn from_poll(poll: &Poll, now: DateTime<Utc>) -> Self {
+        let winner = poll
+            .ended()
+            .then(|| poll.winner().map(|winner| winner.title.as_str()))
+            .flatten();
+
+        Self {
+            kind: "Poll",
+            title: poll.title.clone(),
+            status: if poll.ended() {
+                "Ended".to_string()
+            } else {
+                poll.ends_at
+                    .map(|ends_at| countdown(ends_at, now))
+                    .unwrap_or_default()
+            },
+            options: poll
+                .choices
+                .iter()
+                .map(|choice| VoteOption {
+                    title: choice.title.clone(),
+                    amount: choice.votes,
+                    detail: format!("{} votes", choice.votes),
+                    color: Color::Cyan,
+                    winner: winner == Some(choice.title.as_str()),
+                })
+                .collect(),
+        }
+    }
+
+    fn from_prediction(prediction: &Prediction, now: DateTime<Utc>) -> Self {
+        let winner = prediction.winner().map(|winner| winner.id.as_str());
+
+        Self {
+            kind: "Prediction",
+            title: prediction.title.clone(),
+            status: if prediction.ended() && winner.is_none() {
+                "Canceled".to_string()
+            } else if prediction.ended() {
+                "Ended".to_string()
+            } else if prediction.locked() {
+                "Locked".to_string()
+            } else {
+                prediction
+                    .locks_at
+                    .map(|locks_at| countdown(locks_at, now))
+                    .unwrap_or_default()
+            },
+            options: prediction
+                .outcomes
+                .iter()
+                .map(|outcome| VoteOption {
+                    title: outcome.title.clone(),
+                    amount: outcome.channel_points,
+                    detail: format!("{} points, {} users", outcome.channel_points, outcome.users),
+                    color: if outcome.color.eq_ignore_ascii_case("pink") {
+                        Color::Magenta
+                    } else {
+                        Color::Blue
+                    },
+                    winner: winner == Some(outcome.id.as_str()),
+                })
+                .collect(),
+        }
+    }
+
+    /// One line for each option, with the bars lined up next to each other.
+    fn option_lines(&self, width: usize) -> Vec<Line<'static>> {
+        let total = self.options.iter().map(|option| option.amount).sum();
+
+        let title_width = self
+            .options
+            .iter()
+            .map(|option| option.title.width())
+            .max()
+            .unwrap_or_default();
+
+        let results = self
+            .options
+            .iter()
+            .map(|option| {
+                format!(
+                    " {:>3}% {}",
+                    percentage(option.amount, total),
+                    option.detail
+                )
+            })
+            .collect::<Vec<String>>();
+
+        let results_width = results.iter().map(|r| r.width()).max().unwrap_or_default();
+
+        // Room for the winner mark in front of the title, and a space after it.
+        let bar_width = width.saturating_sub(2 + title_width + 1 + results_width);
+
+        self.options
+            .iter()
+            .zip(results)
+            .map(|(option, result)| {
+                let filled = bar_width * percentage(option.amount, total) as usize / 100;
+
+                let style = if option.winner {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+
+                let bar_style = if *NO_COLOR {
+                    Style::default()
+                } else {
+                    Style::default().fg(option.color)
+                };
+
+                Line::from(vec![
+                    Span::styled(if option.winner { "✔ " } else { "  " }, style),
+                    Span::styled(
+                        format!(
+                            "{}{} ",
+                            option.title,
+                            " ".repeat(title_width.saturating_sub(option.title.width()))
+                        ),
+                        style,
+                    ),
+                    Span::styled("█".repeat(filled), bar_style),
+                    Span::styled(
+                        "░".repeat(bar_width - filled),
+                        bar_style.add_modifier(Modifier::DIM),
+                    ),
+                    Span::styled(result, style),
+                ])
+            })
+            .collect()
+    }
+}
+
+/// The poll and prediction of the current channel, shown above chat while they go on.
+pub struct VotesWidget {
+    config: SharedCompleteConfig,
+    channels: SharedChannels,
+    /// Only the titles are shown when collapsed.
+    collapsed: bool,
+}
+
+impl VotesWidget {
+    pub const fn new(config: SharedCompleteConfig, channels: SharedChannels) -> Self {
+        Self {
+            config,
+            channels,
+            collapsed: false,
+        }
+    }
+
+    pub fn toggle_collapsed(&mut self) {
+        self.collapsed = !self.collapsed;
+    }
+
+    fn summaries(&self) -> Vec<VoteSummary> {
+        let now = Utc::now();
+
+        let channels = self.channels.borrow();
+
+        let Some(channel) = channels.active() else {
+            return vec![];
+        };
+
+        let poll = channel
+            .poll
+            .as_ref()
+            .filter(|poll| is_shown(poll.ended_at, now))
+            .map(|poll| VoteSummary::from_poll(poll, now));
+
+        let prediction = channel
+            .prediction
+            .as_ref()
+            .filter(|prediction| is_shown(prediction.ended_at, now))
+            .map(|prediction| VoteSummary::from_prediction(prediction, now));
+
+        poll.into_iter().chain(prediction).collect()
+    }
+
+    /// Rows needed to show everything, being zero when there is nothing to show.
+    pub fn height(&self) -> u16 {
+        self.summaries()
+            .iter()
+            .map(|summary| {
+                if self.collapsed {
+                    1
+                } else {
+                    summary.options.len() as u16 + 2
+                }
+            })
+            .sum()
+    }
+}
+
+impl Component for VotesWidget {
+    fn draw(&mut self, f: &mut Frame, area: Option<Rect>) {
+        let r = area.unwrap_or_else(|| f.area());
+
+        let summaries = self.summaries();
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(summaries.iter().map(|summary| {
+                Constraint::Length(if self.collapsed {
+                    1
+                } else {
+                    summary.options.len() as u16 + 2
+                })
+            }))
+            .split(r);
+
+        let border_type = self.config.borrow().frontend.border_type.clone();
+
+        for (summary, chunk) in summaries.iter().zip(chunks.iter()) {
+            let mut title = vec![TitleStyle::Combined(summary.kind, &summary.title)];
+
+            if !summary.status.is_empty() {
+                title.push(TitleStyle::Single(&summary.status));
+            }
+
+            let block = Block::default()
+                .borders(if self.collapsed {
+                    Borders::TOP
+                } else {
+                    Borders::ALL
+                })
+                .border_type(border_type.clone().into())
+                .title(title_line(&title, *TITLE_STYLE));
+
+            let lines = summary.option_lines(chunk.width.saturating_sub(2) as usize);
+
+            f.render_widget(Clear, *chunk);
+            f.render_widget(
+                Paragraph::new(lines).block(block).style(*TEXT_DARK_STYLE),
+                *chunk,
+            );
+        }
+    }
+}
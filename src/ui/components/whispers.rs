@@ -0,0 +1,263 @@
+use tui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    handlers::{
+        config::SharedCompleteConfig,
+        user_input::events::{Event, Key},
+        whispers::SharedWhispers,
+    },
+    terminal::TerminalAction,
+    twitch::TwitchAction,
+    ui::{
+        components::{
+            utils::{centered_rect, latest_message_items, InputWidget},
+            Component,
+        },
+        statics::TWITCH_MESSAGE_LIMIT,
+    },
+    utils::{
+        styles::{NO_COLOR, TEXT_DARK_STYLE, TITLE_STYLE},
+        text::{title_line, TitleStyle},
+    },
+};
+
+/// Width of the list of conversations on the left side of the popup.
+const CONVERSATIONS_WIDTH: u16 = 28;
+
+pub struct WhispersWidget {
+    config: SharedCompleteConfig,
+    whispers: SharedWhispers,
+    /// Login name of the user whose conversation is being displayed.
+    selected: Option<String>,
+    input: InputWidget<()>,
+    focused: bool,
+}
+
+impl WhispersWidget {
+    pub fn new(config: SharedCompleteConfig, whispers: SharedWhispers) -> Self {
+        let input_validator =
+            Box::new(|(), s: String| -> bool { !s.is_empty() && s.len() < TWITCH_MESSAGE_LIMIT });
+
+        let visual_indicator =
+            Box::new(|s: String| -> String { format!("{} / {}", s.len(), TWITCH_MESSAGE_LIMIT) });
+
+        let input = InputWidget::new(
+            config.clone(),
+            "Whisper",
+            Some(((), input_validator)),
+            Some(visual_indicator),
+            None,
+        );
+
+        Self {
+            config,
+            whispers,
+            selected: None,
+            input,
+            focused: false,
+        }
+    }
+
+    pub const fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Opens the popup on the most recent conversation if none was selected before.
+    pub fn toggle_focus(&mut self) {
+        self.focused = !self.focused;
+
+        if self.focused {
+            if self.selected.is_none() {
+                self.selected = self.whispers.borrow().get(0).map(|t| t.user.clone());
+            }
+
+            self.mark_selected_read();
+        }
+    }
+
+    /// If the conversation with a user is currently on screen.
+    pub fn shows_conversation(&self, user: &str) -> bool {
+        self.focused && self.selected.as_deref() == Some(user)
+    }
+
+    fn mark_selected_read(&self) {
+        if let Some(user) = &self.selected {
+            self.whispers.borrow_mut().mark_read(user);
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.selected
+            .as_ref()
+            .and_then(|user| self.whispers.borrow().position(user))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = self.whispers.borrow().get(index).map(|t| t.user.clone());
+        self.mark_selected_read();
+    }
+
+    fn next(&mut self) {
+        let last = self.whispers.borrow().len().saturating_sub(1);
+
+        self.select(self.selected_index().map_or(0, |i| (i + 1).min(last)));
+    }
+
+    fn previous(&mut self) {
+        self.select(self.selected_index().map_or(0, |i| i.saturating_sub(1)));
+    }
+
+    fn draw_conversations(&self, f: &mut Frame, area: Rect) {
+        let whispers = self.whispers.borrow();
+
+        let items = whispers
+            .iter()
+            .map(|thread| {
+                let mut spans = vec![Span::raw(thread.user.clone())];
+
+                if thread.unread > 0 {
+                    spans.push(Span::styled(
+                        format!(" ({})", thread.unread),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ));
+                }
+
+                ListItem::new(Line::from(spans))
+            })
+            .collect::<Vec<ListItem>>();
+
+        let title_binding = [TitleStyle::Single("Whispers")];
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(self.config.borrow().frontend.border_type.clone().into())
+                    .title(title_line(&title_binding, *TITLE_STYLE)),
+            )
+            .highlight_style(if *NO_COLOR {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+                    .bg(Color::LightGreen)
+                    .add_modifier(Modifier::BOLD)
+            });
+
+        let mut list_state = ListState::default().with_selected(self.selected_index());
+
+        f.render_stateful_widget(list, area, &mut list_state);
+    }
+
+    fn draw_messages(&self, f: &mut Frame, area: Rect) {
+        let config = self.config.borrow();
+        let whispers = self.whispers.borrow();
+
+        let thread = self.selected_index().and_then(|i| whispers.get(i));
+
+        // Only the most recent messages are shown if the conversation does not fit.
+        let items = if whispers.is_empty() {
+            vec![ListItem::new(
+                "No whispers yet, start a conversation with /w <user> <message>",
+            )]
+        } else {
+            thread
+                .map(|t| latest_message_items(t.messages.iter().rev(), &config.frontend, area))
+                .unwrap_or_default()
+        };
+
+        let user = thread.map_or("", |t| t.user.as_str());
+
+        let title_binding = [TitleStyle::Combined("Conversation", user)];
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(config.frontend.border_type.clone().into())
+                    .title(title_line(&title_binding, *TITLE_STYLE)),
+            )
+            .style(*TEXT_DARK_STYLE);
+
+        f.render_widget(list, area);
+    }
+}
+
+impl Component for WhispersWidget {
+    fn draw(&mut self, f: &mut Frame, area: Option<Rect>) {
+        let r = area
+            .unwrap_or_else(|| centered_rect(80, 60, f.area().height.saturating_sub(6), f.area()));
+
+        f.render_widget(Clear, r);
+
+        let mut v_constraints = vec![Constraint::Min(3)];
+
+        if self.input.is_focused() {
+            v_constraints.push(Constraint::Length(3));
+        }
+
+        let v_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(v_constraints)
+            .split(r);
+
+        let h_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(CONVERSATIONS_WIDTH), Constraint::Min(1)])
+            .split(v_chunks[0]);
+
+        self.draw_conversations(f, h_chunks[0]);
+        self.draw_messages(f, h_chunks[1]);
+
+        if self.input.is_focused() {
+            self.input.draw(f, Some(v_chunks[1]));
+        }
+    }
+
+    async fn event(&mut self, event: &Event) -> Option<TerminalAction> {
+        if let Event::Input(key) = event {
+            if self.input.is_focused() {
+                match key {
+                    Key::Enter => {
+                        if let (true, Some(user)) = (self.input.is_valid(), &self.selected) {
+                            let action =
+                                TwitchAction::Whisper(user.clone(), self.input.to_string());
+
+                            self.input.clear();
+
+                            return Some(TerminalAction::Enter(action));
+                        }
+                    }
+                    Key::Esc => self.input.toggle_focus(),
+                    _ => {
+                        self.input.event(event).await;
+                    }
+                }
+
+                return None;
+            }
+
+            match key {
+                Key::Esc | Key::Char('w') => self.toggle_focus(),
+                // Whispers can only be sent when logged in.
+                Key::Char('i' | 'c') | Key::Enter
+                    if self.selected.is_some() && !self.config.borrow().twitch.anonymous =>
+                {
+                    self.input.toggle_focus();
+                }
+                Key::ScrollDown | Key::Down | Key::Char('j') => self.next(),
+                Key::ScrollUp | Key::Up | Key::Char('k') => self.previous(),
+                Key::Char('q') => return Some(TerminalAction::Quit),
+                Key::Ctrl('p') => panic!("Manual panic triggered by user."),
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
@@ -31,10 +31,30 @@ pub static HELP_KEYBINDS: Lazy<Vec<(&str, Vec<(&str, &str)>)>> = Lazy::new(|| {
                 ("q", "Quit the application"),
                 ("s", "Open the recent channel search widget"),
                 ("f", "Open the followed channel search widget"),
+                ("Ctrl + x", "Leave the current channel"),
+                ("]", "Switch to the next joined channel"),
+                ("[", "Switch to the previous joined channel"),
+                ("m", "Switch to the next channel with unread mentions"),
+                ("r", "Reply to the message at the bottom of the chat window"),
+                (
+                    "t",
+                    "Show the reply thread of the message at the bottom of the chat window",
+                ),
+                ("R", "Retry the most recent message that Twitch rejected"),
+                ("w", "Open whisper conversations"),
+                ("v", "Collapse or expand the current poll and prediction"),
+                (
+                    "V",
+                    "Create, end or resolve a poll or prediction in your channel",
+                ),
+                ("|", "Show another joined channel in a split pane"),
+                ("Tab", "Focus the next split pane"),
+                ("Ctrl + w", "Close the focused split pane"),
                 ("S", "Go to the dashboard screen (start screen)"),
                 ("Ctrl + f", "Search messages"),
                 ("Ctrl + t", "Toggle the message filter"),
                 ("Ctrl + r", "Reverse the message filter"),
+                ("Ctrl + n", "Hide or show channel events"),
                 ("Ctrl + p", "Manually crash the application"),
                 ("Esc", "Go back to the previous window"),
             ],
@@ -91,16 +111,21 @@ pub static COMMANDS: Lazy<Vec<&str>> = Lazy::new(|| {
         "r9kbetaoff",
         "raid",
         "unraid",
+        "shield",
+        "shieldoff",
         "slow",
         "slowoff",
         "subscribers",
         "subscribersoff",
         "timeout",
         "untimeout",
+        "uniquechat",
+        "uniquechatoff",
         "vip",
         "unvip",
         "vips",
         "w",
+        "warn",
     ]
 });
 
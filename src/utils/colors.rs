@@ -1,5 +1,7 @@
 use tui::style::Color::{self, Rgb};
 
+use crate::handlers::config::Theme;
+
 /// <https://css-tricks.com/converting-color-spaces-in-javascript/#hsl-to-rgb/>
 pub fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> [u8; 3] {
     // Color intensity
@@ -32,6 +34,64 @@ pub fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> [u8; 3] {
     [red as u8, green as u8, blue as u8]
 }
 
+/// The inverse of [`hsl_to_rgb`], with the hue in degrees.
+pub fn rgb_to_hsl(red: u8, green: u8, blue: u8) -> (f64, f64, f64) {
+    let [r, g, b] = [red, green, blue].map(|c| f64::from(c) / 255.);
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+
+    let lightness = (max + min) / 2.;
+
+    // Shades of grey have no hue or saturation
+    if red == green && green == blue {
+        return (0., 0., lightness);
+    }
+
+    let delta = max - min;
+    let saturation = delta / (1. - (2. * lightness - 1.).abs());
+
+    let hue = if red >= green && red >= blue {
+        60. * ((g - b) / delta).rem_euclid(6.)
+    } else if green >= blue {
+        60. * ((b - r) / delta + 2.)
+    } else {
+        60. * ((r - g) / delta + 4.)
+    };
+
+    (hue, saturation, lightness)
+}
+
+/// Parses a color in the `#RRGGBB` format used by Twitch.
+pub fn hex_to_color(hex: &str) -> Option<Color> {
+    let value = hex.strip_prefix('#')?;
+
+    if value.len() != 6 {
+        return None;
+    }
+
+    u32::from_str_radix(value, 16).ok().map(u32_to_color)
+}
+
+/// Changes the lightness of a color so that it can be read on the background of a theme,
+/// as some users choose colors that are too dark or too bright to read.
+pub fn readable_color(color: Color, theme: &Theme) -> Color {
+    let Rgb(red, green, blue) = color else {
+        return color;
+    };
+
+    let (hue, saturation, lightness) = rgb_to_hsl(red, green, blue);
+
+    let lightness = match theme {
+        Theme::Light => lightness.min(0.4),
+        _ => lightness.max(0.6),
+    };
+
+    let [red, green, blue] = hsl_to_rgb(hue, saturation, lightness);
+
+    Rgb(red, green, blue)
+}
+
 // Convert a u32 to RGB values, discarding the highest byte.
 pub const fn u32_to_color(value: u32) -> Color {
     Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)
@@ -68,4 +128,36 @@ mod tests {
 
         assert_eq!(Rgb(138, 46, 160), rgb);
     }
+
+    #[test]
+    fn test_hex_to_color() {
+        assert_eq!(hex_to_color("#8A2EA0"), Some(Rgb(138, 46, 160)));
+        assert_eq!(hex_to_color("8A2EA0"), None);
+        assert_eq!(hex_to_color(""), None);
+    }
+
+    #[test]
+    fn test_rgb_to_hsl() {
+        assert_eq!(rgb_to_hsl(0, 0, 255), (240., 1., 0.5));
+        assert_eq!(rgb_to_hsl(255, 255, 255), (0., 0., 1.));
+    }
+
+    #[test]
+    fn test_readable_color() {
+        // Dark blue is lightened on dark backgrounds, and kept as is on light ones.
+        assert_eq!(
+            readable_color(Rgb(0, 0, 139), &Theme::Dark),
+            Rgb(50, 50, 255)
+        );
+        assert_eq!(
+            readable_color(Rgb(0, 0, 139), &Theme::Light),
+            Rgb(0, 0, 139)
+        );
+
+        // Yellow is darkened on light backgrounds.
+        assert_eq!(
+            readable_color(Rgb(255, 255, 0), &Theme::Light),
+            Rgb(204, 204, 0)
+        );
+    }
 }
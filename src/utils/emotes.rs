@@ -12,6 +12,11 @@ pub const fn emotes_enabled(frontend: &FrontendConfig) -> bool {
         || frontend.frankerfacez_emotes
 }
 
+/// If badges are drawn as images, which goes through the same graphics protocol as emotes.
+pub const fn badge_images_enabled(frontend: &FrontendConfig) -> bool {
+    frontend.badges && frontend.badge_images
+}
+
 pub const fn get_emote_offset(width: u16, cell_width: u16, cols: u16) -> (u16, u16) {
     let w = (width + if cols % 2 == 0 { 0 } else { cell_width } + 1) / 2;
 
@@ -88,6 +88,25 @@ define_style!(DASHBOARD_TITLE_COLOR_STYLE,
     fg: color!(Color::Rgb(135, 120, 165))
 );
 
+define_style!(REPLY_STYLE,
+    fg: color!(Color::Gray),
+    add_modifier: Modifier::ITALIC
+);
+
+define_style!(PURGED_STYLE,
+    fg: color!(Color::DarkGray),
+    add_modifier: if *NO_COLOR {
+        Modifier::CROSSED_OUT
+    } else {
+        Modifier::empty()
+    }
+);
+
+define_style!(FAILED_STYLE,
+    fg: color!(Color::LightRed),
+    add_modifier: Modifier::ITALIC
+);
+
 define_style!(SEARCH_STYLE,
     fg: color!(Color::Red),
     add_modifier: *BOLD
@@ -87,6 +87,37 @@ pub fn first_similarity_iter<'a>(
         })
 }
 
+/// A duration in seconds written out in words, such as `1 hour 30 minutes`.
+pub fn format_duration(seconds: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [
+        (86400, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+
+    if seconds == 0 {
+        return "0 seconds".to_string();
+    }
+
+    let mut remaining = seconds;
+
+    UNITS
+        .iter()
+        .filter_map(|&(unit_seconds, name)| {
+            let amount = remaining / unit_seconds;
+            remaining %= unit_seconds;
+
+            match amount {
+                0 => None,
+                1 => Some(format!("1 {name}")),
+                _ => Some(format!("{amount} {name}s")),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 /// <https://stackoverflow.com/a/38406885/>
 pub fn capitalize_first_char(s: &str) -> String {
     let mut c = s.chars();
@@ -195,6 +226,15 @@ mod tests {
         assert_eq!(s.width(), "[ Time: Some time ]".len());
     }
 
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0 seconds");
+        assert_eq!(format_duration(1), "1 second");
+        assert_eq!(format_duration(600), "10 minutes");
+        assert_eq!(format_duration(5430), "1 hour 30 minutes 30 seconds");
+        assert_eq!(format_duration(1_209_600), "14 days");
+    }
+
     #[test]
     fn test_first_similarity_no_search_no_output() {
         let v = vec!["asdf".to_string()];